@@ -0,0 +1,303 @@
+//! Atomic saves of the config file and timestamped backups of the versions they replace.
+//!
+//! Backups live in `$XDG_STATE_HOME/leftwm/backups`, in a directory per config file named
+//! after its canonical path, e.g. `%2Fhome%2Fme%2F.config%2Fleftwm%2Fconfig.ron`. Each
+//! backup is named after the UTC time it was taken, e.g. `2026-10-17T12:00:00.123Z`.
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail};
+use xdg::BaseDirectories;
+
+/// How many backups are kept for every config file.
+pub const BACKUPS_KEPT: usize = 10;
+
+const BACKUP_DIR: &str = "backups";
+
+pub struct Backup {
+    pub path: PathBuf,
+    /// the UTC time the backup was taken, as written in its name
+    pub taken: String,
+}
+
+/// Replaces `path` with `contents` so that readers see either the old or the new file,
+/// never a partial one. The previous contents are kept as a backup.
+pub fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    let path = &resolve(path);
+    let existing = fs::read_to_string(path).ok();
+    if existing.as_deref() == Some(contents) {
+        return Ok(());
+    }
+    if existing.is_some() {
+        backup(path)?;
+    }
+    replace(path, contents)
+}
+
+/// Replaces `path` with `contents` like [`write_atomic`] but without a backup, for files
+/// leftwm does not read such as the output of `convert`.
+pub fn replace(path: &Path, contents: &str) -> Result<()> {
+    // a symlinked config stays a symlink, its target is replaced instead
+    let path = &resolve(path);
+    let file_name = file_name(path)?;
+    let tmp_path = path.with_file_name(format!(".{file_name}.{}.tmp", std::process::id()));
+    let write = || -> Result<()> {
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&tmp_path, metadata.permissions())?;
+        }
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    };
+    write().inspect_err(|_| {
+        let _ = fs::remove_file(&tmp_path);
+    })?;
+    // make the rename itself durable
+    if let Some(dir) = path.parent().and_then(|dir| File::open(dir).ok()) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Lists the backups of `config`, newest first.
+pub fn list(config: &Path) -> Result<Vec<Backup>> {
+    let dir = backup_dir(config)?;
+    let mut backups: Vec<Backup> = match fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let taken = entry.file_name().into_string().ok()?;
+                Some(Backup {
+                    path: entry.path(),
+                    taken,
+                })
+            })
+            .collect(),
+        Err(_) => vec![],
+    };
+    // the timestamps sort chronologically
+    backups.sort_by(|a, b| b.taken.cmp(&a.taken));
+    Ok(backups)
+}
+
+/// Rolls `config` back to the backup numbered `number` in [`list`], starting at 1.
+/// The current contents are backed up first, so a restore can be undone.
+/// Returns when the restored backup was taken.
+pub fn restore(config: &Path, number: usize) -> Result<String> {
+    let backups = list(config)?;
+    let Some(backup) = number.checked_sub(1).and_then(|i| backups.get(i)) else {
+        bail!(
+            "There is no backup number {number}, there are {} backups of {}",
+            backups.len(),
+            config.display()
+        );
+    };
+    let contents = fs::read_to_string(&backup.path)
+        .with_context(|| format!("Could not read {}", backup.path.display()))?;
+    write_atomic(config, &contents)?;
+    Ok(backup.taken.clone())
+}
+
+fn backup(path: &Path) -> Result<()> {
+    let dir = backup_dir(path)?;
+    fs::create_dir_all(&dir)?;
+    fs::copy(path, dir.join(timestamp(SystemTime::now())))
+        .with_context(|| format!("Could not back up {}", path.display()))?;
+    for old in list(path)?.iter().skip(BACKUPS_KEPT) {
+        fs::remove_file(&old.path)?;
+    }
+    Ok(())
+}
+
+/// The directory holding the backups of `config`, named after its canonical path so
+/// configs with the same file name in different directories keep separate backups.
+fn backup_dir(config: &Path) -> Result<PathBuf> {
+    let config = resolve(config);
+    let config = config
+        .to_str()
+        .with_context(|| format!("{} is not valid UTF-8", config.display()))?;
+    let name = config.replace('%', "%25").replace('/', "%2F");
+    BaseDirectories::with_prefix("leftwm")
+        .get_state_home()
+        .map(|state| state.join(BACKUP_DIR).join(name))
+        .context("Could not find the XDG state directory")
+}
+
+/// The canonical path of `path`, following symlinks. A file which does not exist yet
+/// is resolved through its directory.
+fn resolve(path: &Path) -> PathBuf {
+    if let Ok(path) = fs::canonicalize(path) {
+        return path;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(dir), Some(name)) => fs::canonicalize(if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        })
+        .map_or_else(|_| path.to_path_buf(), |dir| dir.join(name)),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn file_name(path: &Path) -> Result<&str> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .with_context(|| format!("{} has no file name", path.display()))
+}
+
+/// Formats `time` as an RFC 3339 UTC timestamp with milliseconds.
+fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (year, month, day) = civil_from_days(secs / 86_400);
+    let secs_of_day = secs % 86_400;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Converts days since 1970-01-01 to a (year, month, day) date in the Gregorian calendar.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    // shift the epoch to 0000-03-01, so leap days are at the end of a year
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::Once;
+    use std::time::Duration;
+
+    /// A config path in a directory of its own, with the backups in a state directory
+    /// shared by all tests of this run.
+    pub(crate) fn config_file(name: &str) -> PathBuf {
+        static STATE_HOME: Once = Once::new();
+        let root =
+            std::env::temp_dir().join(format!("leftwm-config-backup-{}", std::process::id()));
+        STATE_HOME.call_once(|| {
+            // left over from an earlier run with the same process id
+            let _ = fs::remove_dir_all(&root);
+            // SAFETY: set once before any backup is looked up, nothing else in the tests
+            // reads the variable
+            unsafe { std::env::set_var("XDG_STATE_HOME", root.join("state")) };
+        });
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("config.ron")
+    }
+
+    /// Writes `contents` like a save would, waiting so every backup gets its own timestamp.
+    fn save(path: &Path, contents: &str) {
+        std::thread::sleep(Duration::from_millis(2));
+        write_atomic(path, contents).unwrap();
+    }
+
+    fn backed_up(path: &Path) -> Vec<String> {
+        list(path)
+            .unwrap()
+            .iter()
+            .map(|b| fs::read_to_string(&b.path).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn write_atomic_backs_up_the_replaced_contents() {
+        let file = config_file("write");
+        save(&file, "1");
+        assert!(backed_up(&file).is_empty());
+        save(&file, "2");
+        save(&file, "2");
+        assert_eq!(fs::read_to_string(&file).unwrap(), "2");
+        assert_eq!(backed_up(&file), vec!["1"]);
+        // no temporary file is left behind
+        assert_eq!(fs::read_dir(file.parent().unwrap()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn backups_past_the_limit_are_removed() {
+        let file = config_file("rotate");
+        for i in 0..BACKUPS_KEPT + 3 {
+            save(&file, &i.to_string());
+        }
+        let expected: Vec<String> = (2..BACKUPS_KEPT + 2).rev().map(|i| i.to_string()).collect();
+        assert_eq!(backed_up(&file), expected);
+    }
+
+    #[test]
+    fn symlinked_configs_replace_their_target() {
+        let target = config_file("symlink");
+        let link = target.with_file_name("link.ron");
+        save(&target, "1");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        save(&link, "2");
+        assert!(
+            fs::symlink_metadata(&link)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(fs::read_to_string(&target).unwrap(), "2");
+        assert_eq!(backed_up(&link), vec!["1"]);
+        assert_eq!(backed_up(&target), vec!["1"]);
+    }
+
+    #[test]
+    fn restore_counts_from_the_newest_backup() {
+        let file = config_file("restore");
+        for contents in ["1", "2", "3"] {
+            save(&file, contents);
+        }
+        assert_eq!(backed_up(&file), vec!["2", "1"]);
+        let taken = list(&file).unwrap()[1].taken.clone();
+        std::thread::sleep(Duration::from_millis(2));
+        assert_eq!(restore(&file, 2).unwrap(), taken);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "1");
+        // the restored-over contents are backed up, so the restore can be undone
+        assert_eq!(backed_up(&file), vec!["3", "2", "1"]);
+        assert!(restore(&file, 0).is_err());
+        assert!(restore(&file, 4).is_err());
+    }
+
+    #[test]
+    fn civil_from_days_handles_leap_years() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(789), (1972, 2, 29));
+        assert_eq!(civil_from_days(790), (1972, 3, 1));
+        // 2000 is a leap year, 2100 isn't
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(11_017), (2000, 3, 1));
+        assert_eq!(civil_from_days(20_088), (2024, 12, 31));
+        assert_eq!(civil_from_days(47_540), (2100, 2, 28));
+        assert_eq!(civil_from_days(47_541), (2100, 3, 1));
+    }
+
+    #[test]
+    fn timestamp_is_utc_with_milliseconds() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_792_209_738_123);
+        assert_eq!(timestamp(time), "2026-10-17T04:02:18.123Z");
+        assert_eq!(timestamp(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+    }
+}
@@ -1,44 +1,293 @@
 use crate::config;
 use crate::config::Config;
-use crate::config::{all_ids_some, all_ids_unique, get_workspace_ids};
+use crate::config::command::{Argument, BaseCommand};
+use crate::config::detect::is_program_in_path;
+use crate::config::diagnostic::Diagnostic;
+use crate::config::get_workspace_ids;
+use crate::config::layout::{self, Layout};
+use crate::config::modifier::{Modifier, ModifierError};
+use crate::config::monitor::{Monitor, Rect};
+use crate::config::report::{self, Group, Report};
+use crate::config::values::{FocusBehaviour, Size};
+use crate::config::{location, migrate, overlay};
 use anyhow::Result;
-use anyhow::bail;
-use std::collections::HashSet;
-use std::{env, fs};
-
-pub fn check_config(path: Option<&str>, verbose: bool) -> Result<()> {
-    println!(
-        "\x1b[0;94m::\x1b[0m LeftWM Config version: {}",
-        std::env::var("CARGO_PKG_VERSON").unwrap_or("unknown".to_string())
-    );
-    println!(
-        "\x1b[0;94m::\x1b[0m LeftWM git hash: {}",
-        option_env!("GIT_HASH").unwrap_or(git_version::git_version!(fallback = "unknown"))
-    );
+use std::collections::{HashMap, HashSet};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{fs, thread};
+
+/// Runs every check and prints the findings grouped by what was checked.
+/// A config which can't be loaded is reported as a finding, not as an `Err`.
+pub fn check_config(
+    path: Option<&str>,
+    monitors: Option<&[Monitor]>,
+    verbose: bool,
+) -> Result<Report> {
+    report::print_header();
+    if let Some(path) = path {
+        println!("\x1b[1;35mNote: Using file {path} \x1b[0m");
+    }
     println!("\x1b[0;94m::\x1b[0m Loading configuration . . .");
-    match config::filehandler::load_from_file(path, verbose) {
-        Ok(config) => {
-            println!("\x1b[0;92m    -> Configuration loaded OK \x1b[0m");
+    let report = run_checks(path, monitors, verbose)?;
+    report.print();
+    Ok(report)
+}
+
+/// Runs every check, only the `verbose` output is printed.
+/// A config which can't be loaded is reported as a finding, not as an `Err`.
+pub fn run_checks(
+    path: Option<&str>,
+    monitors: Option<&[Monitor]>,
+    verbose: bool,
+) -> Result<Report> {
+    let file = match path {
+        Some(path) => PathBuf::from(path),
+        None => config::filehandler::get_config_file()?,
+    };
+    let mut report = Report {
+        config: file.clone(),
+        loading: vec![],
+        overlays: vec![],
+        groups: vec![],
+        environment: vec![],
+    };
+    let source = fs::read_to_string(&file);
+    let is_ron = file.extension().is_some_and(|e| e == "ron");
+    let migrated = source
+        .as_ref()
+        .map_err(|e| anyhow::anyhow!("Could not read {}: {e}", file.display()))
+        .and_then(|source| {
+            if is_ron {
+                migrate::migrate_ron(source)
+            } else {
+                migrate::migrate_toml(source)
+            }
+        });
+    // errors of the migration are in the file as written, those of deserializing in the
+    // migrated source
+    let loaded =
+        migrated.map_err(|e| (e, None)).and_then(
+            |migrated| match config::filehandler::deserialize(&migrated.source, is_ron) {
+                Ok(config) => Ok((config, migrated)),
+                Err(e) => Err((e, Some(migrated.source))),
+            },
+        );
+    match loaded {
+        Ok((config, migrated)) => {
+            if !migrated.applied.is_empty() {
+                report.loading.push(Diagnostic::warning(
+                    "LW002",
+                    "version",
+                    format!(
+                        "The config uses schema version {}, it was upgraded while loading. Please consider running `{}`.",
+                        migrated.from_version,
+                        config::filehandler::upgrade_command(is_ron)
+                    ),
+                ));
+            }
+            let merged = overlay::fragments(&file).and_then(|fragments| {
+                if fragments.is_empty() {
+                    Ok(None)
+                } else {
+                    overlay::merge(&config, &file).map(Some)
+                }
+            });
+            let (config, overlays) = match merged {
+                Ok(Some(merged)) => {
+                    for key in &merged.unknown {
+                        report.loading.push(Diagnostic::warning(
+                            "LW004",
+                            key.setting.clone(),
+                            format!(
+                                "{} sets a key leftwm-config doesn't know, it is kept as written.",
+                                key.fragment.display()
+                            ),
+                        ));
+                    }
+                    (merged.config, merged.sources)
+                }
+                Ok(None) => (config, vec![]),
+                Err(e) => {
+                    report.loading.push(Diagnostic::error(
+                        "LW003",
+                        "",
+                        format!(
+                            "The `config.d` fragments could not be merged, the config is checked without them. Reason: {e:#}"
+                        ),
+                    ));
+                    (config, vec![])
+                }
+            };
             if verbose {
                 dbg!(&config);
             }
-            config.check_mousekey(verbose);
-            config.check_workspace_ids(verbose);
-            config.check_keybinds(verbose);
+            for (name, ok, diagnostics) in [
+                (
+                    "modifiers",
+                    "Modkey and mousekey OK",
+                    config.check_mousekey(verbose),
+                ),
+                ("options", "All options OK", config.check_options(verbose)),
+                (
+                    "workspaces",
+                    "All workspaces OK",
+                    [
+                        config.check_workspace_ids(verbose),
+                        config.check_workspace_geometry(monitors, verbose),
+                    ]
+                    .concat(),
+                ),
+                (
+                    "window rules",
+                    "All window rules OK",
+                    config.check_window_rules(verbose),
+                ),
+                ("layouts", "All layouts OK", config.check_layouts(verbose)),
+                (
+                    "keybinds",
+                    "All keybinds OK",
+                    config.check_keybinds(verbose),
+                ),
+                (
+                    "references",
+                    "All references OK",
+                    config.check_references(verbose),
+                ),
+            ] {
+                report.groups.push(Group {
+                    name,
+                    ok,
+                    diagnostics,
+                });
+            }
+            location::locate(&mut report.loading, &file, &overlays);
+            for group in &mut report.groups {
+                location::locate(&mut group.diagnostics, &file, &overlays);
+            }
+            report.overlays = overlays;
         }
-        Err(e) => {
-            println!("Configuration failed. Reason: {e:?}");
-            bail!("Configuration failed. Reason: {e:?}");
+        Err((e, parsed)) => {
+            // with a location the frame is printed instead of the position in the message
+            let located = source.ok().and_then(|source| {
+                let parsed = parsed.as_deref().unwrap_or(&source);
+                location::of_error(&e, &file, parsed, &source)
+            });
+            let reason = located
+                .as_ref()
+                .map_or_else(|| format!("{e:#}"), |(_, message)| message.clone());
+            let mut failed = Diagnostic::error(
+                "LW001",
+                "",
+                format!("Configuration failed. Reason: {reason}"),
+            );
+            failed.location = located.map(|(location, _)| location);
+            report.loading.push(failed);
         }
     }
-    println!("\x1b[0;94m::\x1b[0m Checking environment . . .");
-    check_elogind(verbose)?;
+    report.environment = check_elogind(verbose);
+    Ok(report)
+}
 
-    Ok(())
+/// The finding for a modifier in `path` which leftwm can't use.
+fn modifier_diagnostic(error: &ModifierError, path: String) -> Diagnostic {
+    let code = match error {
+        ModifierError::Unknown(_) => "LW102",
+        ModifierError::NumLock(_) => "LW103",
+        ModifierError::Placeholder(_) => "LW104",
+    };
+    Diagnostic::error(code, path, error.to_string())
+}
+
+/// Names the first few of `areas`, largest first.
+fn list_areas(areas: &[Rect]) -> String {
+    const SHOWN: usize = 3;
+    let mut areas = areas.to_vec();
+    areas.sort_by_key(|a| std::cmp::Reverse(a.area()));
+    let mut names: Vec<String> = areas.iter().take(SHOWN).map(ToString::to_string).collect();
+    if areas.len() > SHOWN {
+        names.push(format!("{} smaller areas", areas.len() - SHOWN));
+    }
+    names.join(", ")
+}
+
+/// How often `--watch` looks at the config file.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Runs [`check_config`] again whenever the config or one of its overlays changes.
+/// The file is looked up by path every time, so editors which save by writing a new file
+/// and renaming it over the old one are noticed as well.
+pub fn watch_config(path: Option<&str>, monitors: Option<&[Monitor]>, verbose: bool) -> Result<()> {
+    let file = match path {
+        Some(path) => PathBuf::from(path),
+        None => config::filehandler::get_config_file()?,
+    };
+    let mut last = None;
+    loop {
+        let current = fingerprint(&file)?;
+        if last.as_ref() != Some(&current) {
+            // wait for the editor to finish writing before reading the file
+            thread::sleep(WATCH_INTERVAL);
+            if fingerprint(&file)? != current {
+                continue;
+            }
+            print!("\x1b[2J\x1b[H");
+            println!(
+                "\x1b[0;94m::\x1b[0m Watching {}, press Ctrl-C to stop",
+                file.display()
+            );
+            if current.first().is_some_and(|(_, state)| state.is_none()) {
+                println!(
+                    "\x1b[1;93mWARN: {} does not exist, waiting for it to be created\x1b[0m",
+                    file.display()
+                );
+            } else {
+                // the report has already been printed, the next change is checked either way
+                let _ = check_config(Some(&file.to_string_lossy()), monitors, verbose);
+            }
+            last = Some(current);
+        }
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+/// The modification time, size and inode of a file, `None` if it is missing.
+type FileState = Option<(SystemTime, u64, u64)>;
+
+/// The state of the config and each of its overlays.
+fn fingerprint(file: &Path) -> Result<Vec<(PathBuf, FileState)>> {
+    let files = std::iter::once(file.to_path_buf()).chain(overlay::fragments(file)?);
+    Ok(files
+        .map(|path| {
+            let state = fs::metadata(&path)
+                .ok()
+                .map(|m| (m.modified().unwrap_or(UNIX_EPOCH), m.len(), m.ino()));
+            (path, state)
+        })
+        .collect())
 }
 
 impl Config {
-    pub fn check_mousekey(&self, verbose: bool) {
+    /// Checks that modkey and mousekey only name modifiers leftwm knows.
+    pub fn check_mousekey(&self, verbose: bool) -> Vec<Diagnostic> {
+        let mut found = Vec::new();
+        if verbose {
+            println!("Checking modkey.");
+        }
+        if let Modifier::List(modifiers) = &self.modkey {
+            found.push(Diagnostic::error(
+                "LW101",
+                "modkey",
+                format!(
+                    "modkey must be a single modifier, found `{}`",
+                    modifiers.join("+")
+                ),
+            ));
+        }
+        for error in self.modkey.validate(false) {
+            found.push(modifier_diagnostic(&error, "modkey".to_string()));
+        }
         if verbose {
             println!("Checking if mousekey is set.");
         }
@@ -47,110 +296,554 @@ impl Config {
                 println!("Mousekey is set.");
             }
             if mousekey.is_empty() {
-                println!(
-                    "Your mousekey is set to nothing, this will cause windows to move/resize with just a mouse press."
-                );
-                return;
+                found.push(Diagnostic::warning(
+                    "LW105",
+                    "mousekey",
+                    "Your mousekey is set to nothing, this will cause windows to move/resize with just a mouse press.",
+                ));
             }
-            if verbose {
-                println!("Mousekey is okay.");
+            for error in mousekey.validate(false) {
+                found.push(modifier_diagnostic(&error, "mousekey".to_string()));
+            }
+        }
+        found
+    }
+
+    /// Checks the behaviour options which depend on each other or on the filesystem.
+    pub fn check_options(&self, verbose: bool) -> Vec<Diagnostic> {
+        let mut found = Vec::new();
+        if verbose {
+            println!("Checking options.");
+        }
+        if let Some(state_path) = &self.state_path {
+            if state_path.as_os_str().is_empty() {
+                found.push(Diagnostic::warning(
+                    "LW201",
+                    "state_path",
+                    "`state_path` is set but empty, leftwm will fall back to its default location",
+                ));
+            } else if state_path.is_relative() {
+                found.push(Diagnostic::warning(
+                    "LW202",
+                    "state_path",
+                    format!(
+                        "`state_path` {} is relative, it will be resolved against the directory leftwm is started from",
+                        state_path.display()
+                    ),
+                ));
+            } else if let Some(parent) = state_path.parent()
+                && !parent.is_dir()
+            {
+                found.push(Diagnostic::warning(
+                    "LW203",
+                    "state_path",
+                    format!(
+                        "The directory {} for `state_path` does not exist",
+                        parent.display()
+                    ),
+                ));
             }
         }
+        if !self.auto_derive_workspaces && self.workspaces.as_ref().is_none_or(Vec::is_empty) {
+            found.push(Diagnostic::warning(
+                "LW204",
+                "auto_derive_workspaces",
+                "`auto_derive_workspaces` is disabled and no workspaces are defined, no workspace will be created",
+            ));
+        }
+        if self.sloppy_mouse_follows_focus && self.focus_behaviour != FocusBehaviour::Sloppy {
+            found.push(Diagnostic::info(
+                "LW205",
+                "sloppy_mouse_follows_focus",
+                "`sloppy_mouse_follows_focus` only has an effect with the `Sloppy` focus behaviour.",
+            ));
+        }
+        found
     }
 
     /// Checks defined workspaces to ensure no ID collisions occur.
-    pub fn check_workspace_ids(&self, verbose: bool) {
-        if let Some(wss) = self.workspaces.as_ref() {
-            if verbose {
-                println!("Checking config for valid workspace definitions.");
-            }
-            let ids = get_workspace_ids(wss);
-            if ids.iter().any(std::option::Option::is_some) {
-                if all_ids_some(&ids) {
-                    if !all_ids_unique(&ids) {
-                        println!(
-                            "Your config.toml contains duplicate workspace IDs. Please assign unique IDs to workspaces. The default config will be used instead."
-                        );
+    pub fn check_workspace_ids(&self, verbose: bool) -> Vec<Diagnostic> {
+        let mut found = Vec::new();
+        let Some(wss) = self.workspaces.as_ref() else {
+            return found;
+        };
+        if verbose {
+            println!("Checking config for valid workspace definitions.");
+        }
+        let ids = get_workspace_ids(wss);
+        if !ids.iter().any(Option::is_some) {
+            return found;
+        }
+        for (i, id) in ids.iter().enumerate() {
+            match id {
+                None => found.push(Diagnostic::error(
+                    "LW302",
+                    format!("workspaces[{i}].id"),
+                    "Only some workspaces specify an ID. This can lead to ID collisions and is not allowed. The default config will be used instead.",
+                )),
+                Some(id) => {
+                    if let Some(first) = ids[..i].iter().position(|other| *other == Some(*id)) {
+                        found.push(Diagnostic::error(
+                            "LW301",
+                            format!("workspaces[{i}].id"),
+                            format!(
+                                "Workspace ID {id} is already used by workspaces[{first}]. Please assign unique IDs to workspaces. The default config will be used instead."
+                            ),
+                        ));
                     }
-                } else {
-                    println!(
-                        "Your config.toml specifies an ID for some but not all workspaces. This can lead to ID collisions and is not allowed. The default config will be used instead."
-                    );
                 }
             }
         }
+        found
+    }
+
+    /// Checks the size of each workspace, that workspaces neither overlap nor leave gaps
+    /// and, with a monitor layout, that each one is on a monitor.
+    pub fn check_workspace_geometry(
+        &self,
+        monitors: Option<&[Monitor]>,
+        verbose: bool,
+    ) -> Vec<Diagnostic> {
+        let mut found = Vec::new();
+        let Some(wss) = self.workspaces.as_ref() else {
+            return found;
+        };
+        if verbose {
+            println!("Checking workspace geometry.");
+        }
+        let mut areas: Vec<(usize, Rect)> = Vec::new();
+        for (i, ws) in wss.iter().enumerate() {
+            let area = Rect {
+                x: ws.x,
+                y: ws.y,
+                width: ws.width,
+                height: ws.height,
+            };
+            if ws.width <= 0 || ws.height <= 0 {
+                found.push(Diagnostic::error(
+                    "LW303",
+                    format!("workspaces[{i}]"),
+                    format!(
+                        "Workspace {i} has an invalid size {}x{}",
+                        ws.width, ws.height
+                    ),
+                ));
+                continue;
+            }
+            if let Some(Size::Pixel(max)) = ws.max_window_width
+                && max > ws.width
+            {
+                found.push(Diagnostic::warning(
+                    "LW309",
+                    format!("workspaces[{i}].max_window_width"),
+                    format!(
+                        "`max_window_width` is {max}px but workspace {i} is only {}px wide, it has no effect",
+                        ws.width
+                    ),
+                ));
+            }
+            for (j, other) in &areas {
+                if let Some(overlap) = area.intersection(other) {
+                    found.push(Diagnostic::warning(
+                        "LW304",
+                        format!("workspaces[{i}]"),
+                        format!(
+                            "Workspace {i} ({area}) overlaps workspace {j} ({other}) in {overlap}"
+                        ),
+                    ));
+                }
+            }
+            areas.push((i, area));
+        }
+        let rects: Vec<Rect> = areas.iter().map(|(_, area)| *area).collect();
+        match monitors {
+            Some(monitors) => {
+                for (i, area) in &areas {
+                    let on = monitors
+                        .iter()
+                        .filter(|m| m.area.intersection(area).is_some());
+                    if on.clone().count() == 0 {
+                        found.push(Diagnostic::error(
+                            "LW307",
+                            format!("workspaces[{i}]"),
+                            format!("Workspace {i} ({area}) is not on any monitor"),
+                        ));
+                    } else if !on.clone().any(|m| m.area.contains(area)) {
+                        let names: Vec<String> = on.map(ToString::to_string).collect();
+                        found.push(Diagnostic::warning(
+                            "LW308",
+                            format!("workspaces[{i}]"),
+                            format!(
+                                "Workspace {i} ({area}) does not fit on {}",
+                                names.join(" or ")
+                            ),
+                        ));
+                    }
+                }
+                for monitor in monitors {
+                    let uncovered = monitor.area.uncovered(&rects);
+                    if !uncovered.is_empty() {
+                        found.push(Diagnostic::warning(
+                            "LW306",
+                            "workspaces",
+                            format!(
+                                "No workspace covers {} of {monitor}",
+                                list_areas(&uncovered)
+                            ),
+                        ));
+                    }
+                }
+            }
+            // without monitors only gaps inside the area the workspaces span are found,
+            // monitors of different sizes leave those as well
+            None => {
+                let bounds = rects.iter().copied().reduce(|a, b| {
+                    let x = a.x.min(b.x);
+                    let y = a.y.min(b.y);
+                    Rect {
+                        x,
+                        y,
+                        width: a.right().max(b.right()) - x,
+                        height: a.bottom().max(b.bottom()) - y,
+                    }
+                });
+                if let Some(bounds) = bounds {
+                    let uncovered = bounds.uncovered(&rects);
+                    if !uncovered.is_empty() {
+                        found.push(Diagnostic::info(
+                            "LW305",
+                            "workspaces",
+                            format!(
+                                "No workspace covers {}, pass --monitors to check against your monitors",
+                                list_areas(&uncovered)
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// Checks window rules for settings which contradict each other or reference
+    /// workspaces that do not exist.
+    pub fn check_window_rules(&self, verbose: bool) -> Vec<Diagnostic> {
+        let mut found = Vec::new();
+        let workspace_ids = self
+            .workspaces
+            .as_ref()
+            .map(|wss| get_workspace_ids(wss))
+            .unwrap_or_default();
+        for (i, rule) in self.window_rules.iter().flatten().enumerate() {
+            if verbose {
+                println!("Window rule {i}: {rule:?}");
+            }
+            let path = |field: &str| format!("window_rules[{i}]{field}");
+            if rule.window_class.is_none() && rule.window_title.is_none() {
+                found.push(Diagnostic::warning(
+                    "LW401",
+                    path(""),
+                    "Neither `window_class` nor `window_title` is set, the rule will be ignored",
+                ));
+            }
+            if rule.spawn_fullscreen == Some(true) && rule.spawn_floating == Some(true) {
+                found.push(Diagnostic::warning(
+                    "LW402",
+                    path(".spawn_fullscreen"),
+                    "`spawn_fullscreen` and `spawn_floating` are both set, the window will be fullscreen",
+                ));
+            }
+            if rule.spawn_sticky == Some(true) && rule.spawn_on_tag.is_some() {
+                found.push(Diagnostic::warning(
+                    "LW403",
+                    path(".spawn_on_tag"),
+                    "`spawn_sticky` windows are visible on every tag, `spawn_on_tag` has no effect",
+                ));
+            }
+            if let Some(geometry) = rule.spawn_floating_geometry {
+                if geometry.width <= 0 || geometry.height <= 0 {
+                    found.push(Diagnostic::error(
+                        "LW404",
+                        path(".spawn_floating_geometry"),
+                        format!(
+                            "`spawn_floating_geometry` has an invalid size {}x{}",
+                            geometry.width, geometry.height
+                        ),
+                    ));
+                }
+                if rule.spawn_floating != Some(true) {
+                    found.push(Diagnostic::warning(
+                        "LW405",
+                        path(".spawn_floating_geometry"),
+                        "`spawn_floating_geometry` is only used when `spawn_floating` is set",
+                    ));
+                }
+            }
+            // workspaces without ids are numbered by leftwm, those can't be checked here
+            if let Some(id) = rule.spawn_on_workspace
+                && workspace_ids.iter().any(Option::is_some)
+                && !workspace_ids.contains(&Some(id))
+            {
+                found.push(Diagnostic::error(
+                    "LW406",
+                    path(".spawn_on_workspace"),
+                    format!(
+                        "`spawn_on_workspace` references workspace id {id} which is not defined"
+                    ),
+                ));
+            }
+        }
+        found
+    }
+
+    /// Checks the custom layout definitions and that every layout name used by `layouts` and
+    /// the workspaces refers to a built-in or custom layout. `SetLayout` values are checked
+    /// with the keybinds.
+    pub fn check_layouts(&self, verbose: bool) -> Vec<Diagnostic> {
+        let mut found = Vec::new();
+        let mut names = HashSet::new();
+        for (i, definition) in self.layout_definitions.iter().enumerate() {
+            if verbose {
+                println!("Layout definition {i}: {definition:?}");
+            }
+            let path = |field: &str| format!("layout_definitions[{i}]{field}");
+            if definition.name.is_empty() {
+                found.push(Diagnostic::error(
+                    "LW501",
+                    path(".name"),
+                    format!("Layout definition {i} has no name"),
+                ));
+            } else if !names.insert(&definition.name) {
+                found.push(Diagnostic::error(
+                    "LW502",
+                    path(".name"),
+                    format!(
+                        "Layout definition `{}` is defined more than once",
+                        definition.name
+                    ),
+                ));
+            } else if Layout::from_str(&definition.name).is_ok() {
+                found.push(Diagnostic::info(
+                    "LW503",
+                    path(".name"),
+                    format!(
+                        "Layout definition `{}` replaces the built-in layout.",
+                        definition.name
+                    ),
+                ));
+            }
+            if let Some(main) = &definition.columns.main {
+                if main.count == 0 {
+                    found.push(Diagnostic::error(
+                        "LW504",
+                        path(".columns.main.count"),
+                        format!(
+                            "Layout definition `{}` has a main column for 0 windows, remove `main` instead",
+                            definition.name
+                        ),
+                    ));
+                }
+                match main.size {
+                    Size::Ratio(r) if r <= 0.0 || r > 1.0 => found.push(Diagnostic::error(
+                        "LW505",
+                        path(".columns.main.size"),
+                        format!(
+                            "Layout definition `{}` has a main size ratio of {r}, expected a value between 0 and 1",
+                            definition.name
+                        ),
+                    )),
+                    Size::Pixel(p) if p <= 0 => found.push(Diagnostic::error(
+                        "LW506",
+                        path(".columns.main.size"),
+                        format!(
+                            "Layout definition `{}` has a main size of {p}px",
+                            definition.name
+                        ),
+                    )),
+                    _ => {}
+                }
+            }
+        }
+        let workspace_layouts = self
+            .workspaces
+            .iter()
+            .flatten()
+            .enumerate()
+            .flat_map(|(i, ws)| {
+                ws.layouts
+                    .iter()
+                    .flatten()
+                    .enumerate()
+                    .map(move |(j, name)| (format!("workspaces[{i}].layouts[{j}]"), name))
+            });
+        for (path, name) in self
+            .layouts
+            .iter()
+            .enumerate()
+            .map(|(j, name)| (format!("layouts[{j}]"), name))
+            .chain(workspace_layouts)
+        {
+            if !layout::is_defined(&name.0, &self.layout_definitions) {
+                let message = layout::unknown_layout_message(
+                    &name.0,
+                    &format!("`{path}`"),
+                    &self.layout_definitions,
+                );
+                found.push(Diagnostic::error("LW507", path, message));
+            }
+        }
+        found
     }
 
     /// Check all keybinds to ensure that required values are provided
     /// Checks to see if value is provided (if required)
     /// Checks to see if keys are valid against Xkeysym
     /// Ideally, we will pass this to the command handler with a dummy config
-    pub fn check_keybinds(&self, verbose: bool) {
-        let mut returns = Vec::new();
-        println!("\x1b[0;94m::\x1b[0m Checking keybinds . . .");
-        let mut bindings = HashSet::new();
-        for keybind in &self.keybind {
+    pub fn check_keybinds(&self, verbose: bool) -> Vec<Diagnostic> {
+        let mut found = Vec::new();
+        let mut bindings = HashMap::new();
+        for (i, keybind) in self.keybind.iter().enumerate() {
             if verbose {
                 println!("Keybind: {:?} {}", keybind, keybind.value.is_empty());
             }
+            let path = |field: &str| format!("keybind[{i}]{field}");
             if let Err(err) = keybind.try_convert_to_core_keybind(self) {
-                returns.push((Some(keybind.clone()), err.to_string()));
+                found.push(Diagnostic::error("LW601", path(".value"), err.to_string()));
             }
             if crate::utils::xkeysym_lookup::into_keysym(&keybind.key).is_none() {
-                returns.push((
-                    Some(keybind.clone()),
+                found.push(Diagnostic::error(
+                    "LW602",
+                    path(".key"),
                     format!("Key `{}` is not valid", keybind.key),
                 ));
             }
 
             let mut modkey = keybind.modifier.as_ref().unwrap_or(&"None".into()).clone();
-            for m in &modkey.clone() {
-                if m != "modkey"
-                    && m != "mousekey"
-                    && crate::utils::xkeysym_lookup::into_mod(&m) == 0
-                {
-                    returns.push((
-                        Some(keybind.clone()),
-                        format!("Modifier `{m}` is not valid"),
-                    ));
-                }
+            for error in modkey.validate(true) {
+                found.push(modifier_diagnostic(&error, path(".modifier")));
             }
 
             modkey.sort_unstable();
-            if let Some(conflict_key) = bindings.replace((modkey.clone(), &keybind.key)) {
-                returns.push((
-                    None,
+            if let Some(first) = bindings.insert((modkey.clone(), &keybind.key), i) {
+                found.push(Diagnostic::error(
+                    "LW603",
+                    path(""),
                     format!(
-                        "\x1b[0m\x1b[1mMultiple commands bound to key combination {} + {}:\
-                    \n\x1b[1;91m    -> {:?}\
-                    \n    -> {:?}\
-                    \n\x1b[0mHelp: change one of the keybindings to something else.\n",
-                        modkey, keybind.key, conflict_key, keybind.command,
+                        "{} + {} is already bound to {:?} by keybind[{first}], change one of the keybindings to something else",
+                        modkey, keybind.key, self.keybind[first].command,
                     ),
                 ));
             }
         }
-        if returns.is_empty() {
-            println!("\x1b[0;92m    -> All keybinds OK\x1b[0m");
-        } else {
-            for error in returns {
-                match error.0 {
-                    Some(binding) => {
-                        println!(
-                            "\x1b[1;91mERROR: {} for keybind {:?}\x1b[0m",
-                            error.1, binding
-                        );
-                    }
-                    None => {
-                        println!("\x1b[1;91mERROR: {} \x1b[0m", error.1);
+        found
+    }
+
+    /// Checks that the scratchpads, tags and layouts named by keybinds and window rules
+    /// exist. Values which are invalid on their own are reported by the keybind checks.
+    pub fn check_references(&self, verbose: bool) -> Vec<Diagnostic> {
+        let mut found = Vec::new();
+        if verbose {
+            println!("Checking references between sections.");
+        }
+        let tag_count = self.tags.as_ref().map(Vec::len);
+        let tag_range = |count: usize| match count {
+            0 => "no tags are defined".to_string(),
+            1 => "only tag 1 is defined".to_string(),
+            count => format!("only tags 1 to {count} are defined"),
+        };
+        // a workspace with its own layouts can switch to those as well
+        let cycled: Vec<&str> = self
+            .layouts
+            .iter()
+            .chain(
+                self.workspaces
+                    .iter()
+                    .flatten()
+                    .flat_map(|ws| ws.layouts.iter().flatten()),
+            )
+            .map(|name| name.0.as_str())
+            .collect();
+        for (i, keybind) in self.keybind.iter().enumerate() {
+            let path = format!("keybind[{i}].value");
+            let binding = format!(
+                "{} + {}",
+                keybind.modifier.as_ref().unwrap_or(&"None".into()),
+                keybind.key
+            );
+            match (&keybind.command, &keybind.value) {
+                (BaseCommand::ToggleScratchPad, Argument::Text(name))
+                    if !self
+                        .scratchpad
+                        .iter()
+                        .flatten()
+                        .any(|s| s.name == name.trim()) =>
+                {
+                    found.push(Diagnostic::error(
+                        "LW801",
+                        path,
+                        format!(
+                            "{binding} toggles the scratchpad `{}`, but no scratchpad has that name",
+                            name.trim()
+                        ),
+                    ));
+                }
+                (BaseCommand::GotoTag | BaseCommand::MoveToTag, Argument::Tag(tag)) => {
+                    if let Some(count) = tag_count
+                        && *tag > count
+                    {
+                        found.push(Diagnostic::error(
+                            "LW802",
+                            path,
+                            format!(
+                                "{binding} runs {:?} with tag {tag}, but {}",
+                                keybind.command,
+                                tag_range(count)
+                            ),
+                        ));
                     }
                 }
+                // layouts which are not defined at all are reported with the keybinds
+                (BaseCommand::SetLayout, Argument::Layout(name))
+                    if !cycled.is_empty()
+                        && layout::is_defined(name, &self.layout_definitions)
+                        && !cycled.contains(&name.as_str()) =>
+                {
+                    found.push(Diagnostic::warning(
+                        "LW804",
+                        path,
+                        format!(
+                            "{binding} sets the layout `{name}`, which is not listed in `layouts` or the layouts of any workspace, leftwm will not switch to it"
+                        ),
+                    ));
+                }
+                _ => {}
+            }
+        }
+        for (i, rule) in self.window_rules.iter().flatten().enumerate() {
+            if let (Some(tag), Some(count)) = (rule.spawn_on_tag, tag_count)
+                && (tag == 0 || tag > count)
+            {
+                let matching = match (&rule.window_class, &rule.window_title) {
+                    (Some(class), Some(title)) => format!("class `{class}` and title `{title}`"),
+                    (Some(class), None) => format!("class `{class}`"),
+                    (None, Some(title)) => format!("title `{title}`"),
+                    (None, None) => "no window".to_string(),
+                };
+                found.push(Diagnostic::error(
+                    "LW803",
+                    format!("window_rules[{i}].spawn_on_tag"),
+                    format!(
+                        "The rule for {matching} spawns windows on tag {tag}, but {}",
+                        tag_range(count)
+                    ),
+                ));
             }
         }
+        found
     }
 }
 
-fn check_elogind(verbose: bool) -> Result<()> {
+fn check_elogind(verbose: bool) -> Vec<Diagnostic> {
     // We assume that if it is in the path it's all good
     // We also cross-reference the ENV variable
     match (
@@ -161,51 +854,329 @@ fn check_elogind(verbose: bool) -> Result<()> {
             if verbose {
                 println!(":: XDG_RUNTIME_DIR: {val}, LOGINCTL OKAY");
             }
-
-            println!("\x1b[0;92m    -> Environment OK \x1b[0m");
-
-            Ok(())
+            vec![]
         }
         (Ok(val), false) => {
             if verbose {
                 println!(":: XDG_RUNTIME_DIR: {val}, LOGINCTL not installed");
             }
-
-            println!("\x1b[0;92m    -> Environment OK (has XDG_RUNTIME_DIR) \x1b[0m");
-
-            Ok(())
+            vec![]
         }
         (Err(e), false) => {
             if verbose {
                 println!(":: XDG_RUNTIME_DIR_ERROR: {e:?}, LOGINCTL BAD");
             }
-
-            bail!(
+            vec![Diagnostic::error(
+                "LW701",
+                "",
                 "Elogind not installed/operating and no alternative XDG_RUNTIME_DIR is set. \
-                See https://github.com/leftwm/leftwm/wiki/XDG_RUNTIME_DIR for more information."
-            );
+                See https://github.com/leftwm/leftwm/wiki/XDG_RUNTIME_DIR for more information.",
+            )]
         }
         (Err(e), true) => {
             if verbose {
                 println!(":: XDG_RUNTIME_DIR: {e:?}, LOGINCTL OKAY");
             }
-            println!(
-                "\x1b[1;93mWARN: Elogind/systemd installed but XDG_RUNTIME_DIR not set.\nThis may be because elogind isn't started. \x1b[0m",
-            );
-            Ok(())
+            vec![Diagnostic::warning(
+                "LW702",
+                "",
+                "Elogind/systemd installed but XDG_RUNTIME_DIR not set. This may be because elogind isn't started.",
+            )]
         }
     }
 }
 
-#[must_use]
-fn is_program_in_path(program: &str) -> bool {
-    if let Ok(path) = env::var("PATH") {
-        for p in path.split(':') {
-            let p_str = format!("{p}/{program}");
-            if fs::metadata(p_str).is_ok() {
-                return true;
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::diagnostic::Severity;
+
+    /// Checks `source` written to a file of its own.
+    fn check_source(name: &str, source: &str) -> Report {
+        let dir =
+            std::env::temp_dir().join(format!("leftwm-config-check-{}-{name}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("config.ron");
+        fs::write(&file, source).unwrap();
+        let report = run_checks(file.to_str(), None, false).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        report
+    }
+
+    fn codes(report: &Report) -> Vec<&'static str> {
+        report.config_diagnostics().map(|d| d.code).collect()
+    }
+
+    /// Reads `source` as a RON config with `implicit_some`.
+    fn config(source: &str) -> Config {
+        let source = format!("#![enable(implicit_some)]\n{source}");
+        config::filehandler::deserialize(&source, true).unwrap()
+    }
+
+    fn found(diagnostics: &[Diagnostic]) -> Vec<(&'static str, &str)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.code, d.path.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn run_checks_accepts_a_valid_config() {
+        let report = check_source(
+            "valid",
+            "#![enable(implicit_some)]\n(modkey: \"Mod4\", mousekey: [\"Mod4\"], keybind: [], version: 2)",
+        );
+        assert_eq!(codes(&report), Vec::<&str>::new());
+        assert_eq!(report.worst(), None);
+    }
+
+    #[test]
+    fn run_checks_warns_about_an_outdated_schema() {
+        let report = check_source(
+            "outdated",
+            "#![enable(implicit_some)]\n(modkey: \"Mod4\", mousekey: [\"Mod4\"], keybind: [], window_config_by_class: [])",
+        );
+        assert_eq!(codes(&report), vec!["LW002"]);
+        assert_eq!(report.worst(), Some(Severity::Warning));
+    }
+
+    #[test]
+    fn run_checks_reports_an_unknown_modkey() {
+        let report = check_source(
+            "modkey",
+            "#![enable(implicit_some)]\n(modkey: \"Hyper\", mousekey: [\"Mod4\"], keybind: [], version: 2)",
+        );
+        assert_eq!(codes(&report), vec!["LW102"]);
+        assert_eq!(report.worst(), Some(Severity::Error));
+    }
+
+    #[test]
+    fn run_checks_reports_a_broken_config_as_a_finding() {
+        let report = check_source("broken", "(modkey: ");
+        assert_eq!(codes(&report), vec!["LW001"]);
+        assert!(report.groups.is_empty());
+        assert_eq!(report.worst(), Some(Severity::Error));
+    }
+
+    #[test]
+    fn window_rules_are_checked_for_consistency() {
+        let config = config(
+            r#"(
+                workspaces: [(id: 1, x: 0, y: 0, width: 1920, height: 1080)],
+                window_rules: [
+                    (spawn_floating: true),
+                    (window_class: "mpv", spawn_fullscreen: true, spawn_floating: true),
+                    (window_class: "conky", spawn_sticky: true, spawn_on_tag: 2),
+                    (window_class: "krita", spawn_floating_geometry: (x: 0, y: 0, width: 0, height: 600)),
+                    (window_title: "htop", spawn_on_workspace: 2),
+                    (window_class: "st", spawn_on_workspace: 1, spawn_as_type: Dialog, spawn_floating: true, spawn_floating_geometry: (x: 10, y: 10, width: 800, height: 600)),
+                ],
+            )"#,
+        );
+        assert_eq!(
+            found(&config.check_window_rules(false)),
+            vec![
+                ("LW401", "window_rules[0]"),
+                ("LW402", "window_rules[1].spawn_fullscreen"),
+                ("LW403", "window_rules[2].spawn_on_tag"),
+                ("LW404", "window_rules[3].spawn_floating_geometry"),
+                ("LW405", "window_rules[3].spawn_floating_geometry"),
+                ("LW406", "window_rules[4].spawn_on_workspace"),
+            ]
+        );
+    }
+
+    #[test]
+    fn layout_definitions_are_checked() {
+        let config = config(
+            r#"(
+                layout_definitions: [
+                    (name: ""),
+                    (name: "Wide"),
+                    (name: "Wide"),
+                    (name: "Monocle"),
+                    (name: "Empty", columns: (main: (count: 0))),
+                    (name: "Ratio", columns: (main: (size: 1.5))),
+                    (name: "Pixels", columns: (main: (size: -10))),
+                ],
+            )"#,
+        );
+        assert_eq!(
+            found(&config.check_layouts(false)),
+            vec![
+                ("LW501", "layout_definitions[0].name"),
+                ("LW502", "layout_definitions[2].name"),
+                ("LW503", "layout_definitions[3].name"),
+                ("LW504", "layout_definitions[4].columns.main.count"),
+                ("LW505", "layout_definitions[5].columns.main.size"),
+                ("LW506", "layout_definitions[6].columns.main.size"),
+            ]
+        );
+    }
+
+    #[test]
+    fn set_layout_accepts_custom_layouts() {
+        let config = config(
+            r#"(
+                layouts: ["Monocle", "Wide"],
+                layout_definitions: [(name: "Wide")],
+                keybind: [
+                    (command: SetLayout, value: "Wide", modifier: ["modkey"], key: "w"),
+                    (command: SetLayout, value: "Narrow", modifier: ["modkey"], key: "n"),
+                ],
+            )"#,
+        );
+        assert_eq!(found(&config.check_layouts(false)), vec![]);
+        let keybinds = config.check_keybinds(false);
+        assert_eq!(
+            found(&keybinds)
+                .into_iter()
+                .filter(|(code, _)| *code == "LW601")
+                .collect::<Vec<_>>(),
+            vec![("LW601", "keybind[1].value")]
+        );
+    }
+
+    #[test]
+    fn unknown_layout_names_are_reported() {
+        let config = config(
+            r#"(
+                layouts: ["Monocle", "Fibonaci", "Wide"],
+                layout_definitions: [(name: "Wide")],
+                workspaces: [(x: 0, y: 0, width: 1920, height: 1080, layouts: ["Wide", "Mono"])],
+            )"#,
+        );
+        let diagnostics = config.check_layouts(false);
+        assert_eq!(
+            found(&diagnostics),
+            vec![
+                ("LW507", "layouts[1]"),
+                ("LW507", "workspaces[0].layouts[1]"),
+            ]
+        );
+        assert!(
+            diagnostics[0]
+                .message
+                .ends_with("did you mean `Fibonacci`?")
+        );
+    }
+
+    fn states(file: &Path) -> Vec<(PathBuf, bool)> {
+        fingerprint(file)
+            .unwrap()
+            .into_iter()
+            .map(|(path, state)| (path, state.is_some()))
+            .collect()
+    }
+
+    #[test]
+    fn fingerprint_notices_writes_renames_and_overlays() {
+        let dir = std::env::temp_dir().join(format!("leftwm-config-watch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("config.ron");
+        assert_eq!(states(&file), vec![(file.clone(), false)]);
+
+        fs::write(&file, "(modkey: \"Mod4\")").unwrap();
+        let written = fingerprint(&file).unwrap();
+        assert_eq!(states(&file), vec![(file.clone(), true)]);
+        assert_eq!(fingerprint(&file).unwrap(), written);
+
+        // an editor saving by renaming a new file of the same size over the config
+        let new = dir.join("config.ron.new");
+        fs::write(&new, "(modkey: \"Mod1\")").unwrap();
+        fs::rename(&new, &file).unwrap();
+        let renamed = fingerprint(&file).unwrap();
+        assert_ne!(renamed, written);
+
+        let fragment = dir.join(overlay::OVERLAY_DIR).join("10-keys.ron");
+        fs::create_dir_all(fragment.parent().unwrap()).unwrap();
+        fs::write(&fragment, "(keybind: [])").unwrap();
+        assert_eq!(
+            states(&file),
+            vec![(file.clone(), true), (fragment.clone(), true)]
+        );
+        assert_ne!(fingerprint(&file).unwrap(), renamed);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn references_to_missing_scratchpads_and_tags_are_reported() {
+        let config = config(
+            r#"(
+                tags: ["1", "2", "3"],
+                scratchpad: [(name: "term", value: "st")],
+                keybind: [
+                    (command: ToggleScratchPad, value: " term ", modifier: ["modkey"], key: "t"),
+                    (command: ToggleScratchPad, value: "music", modifier: ["modkey"], key: "m"),
+                    (command: GotoTag, value: "3", modifier: ["modkey"], key: "3"),
+                    (command: MoveToTag, value: "4", modifier: ["modkey", "Shift"], key: "4"),
+                ],
+                window_rules: [
+                    (window_class: "krita", spawn_on_tag: 3),
+                    (window_class: "mpv", spawn_on_tag: 0),
+                    (window_title: "Steam", spawn_on_tag: 9),
+                ],
+            )"#,
+        );
+        let diagnostics = config.check_references(false);
+        assert_eq!(
+            found(&diagnostics),
+            vec![
+                ("LW801", "keybind[1].value"),
+                ("LW802", "keybind[3].value"),
+                ("LW803", "window_rules[1].spawn_on_tag"),
+                ("LW803", "window_rules[2].spawn_on_tag"),
+            ]
+        );
+        assert!(
+            diagnostics[1]
+                .message
+                .ends_with("only tags 1 to 3 are defined")
+        );
+        assert!(
+            diagnostics[3]
+                .message
+                .starts_with("The rule for title `Steam`")
+        );
+    }
+
+    #[test]
+    fn tags_are_not_checked_without_a_tag_list() {
+        let config = config(
+            r#"(
+                tags: None,
+                keybind: [(command: GotoTag, value: "12", modifier: ["modkey"], key: "1")],
+                window_rules: [(window_class: "mpv", spawn_on_tag: 12)],
+            )"#,
+        );
+        assert_eq!(found(&config.check_references(false)), vec![]);
+    }
+
+    #[test]
+    fn layouts_which_are_never_cycled_to_are_reported() {
+        let config = config(
+            r#"(
+                layouts: ["Monocle"],
+                layout_definitions: [(name: "Wide"), (name: "Tall")],
+                workspaces: [(x: 0, y: 0, width: 1920, height: 1080, layouts: ["Wide"])],
+                keybind: [
+                    (command: SetLayout, value: "Monocle", modifier: ["modkey"], key: "m"),
+                    (command: SetLayout, value: "Wide", modifier: ["modkey"], key: "w"),
+                    (command: SetLayout, value: "Tall", modifier: ["modkey"], key: "t"),
+                    (command: SetLayout, value: "Narrow", modifier: ["modkey"], key: "n"),
+                ],
+            )"#,
+        );
+        assert_eq!(
+            found(&config.check_references(false)),
+            vec![("LW804", "keybind[2].value")]
+        );
+        let config = Config {
+            layouts: vec![],
+            workspaces: None,
+            ..config
+        };
+        assert_eq!(found(&config.check_references(false)), vec![]);
     }
-    false
 }
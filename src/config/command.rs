@@ -1,6 +1,6 @@
-use crate::config::Layout;
 use crate::config::values::WindowHandle;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
 
 #[allow(clippy::module_name_repetitions)]
 #[derive(Debug, Serialize, Default, Deserialize, Clone, PartialEq, Eq, Hash)]
@@ -49,52 +49,127 @@ pub enum BaseCommand {
 }
 
 impl BaseCommand {
-    pub fn needs_value(&self) -> bool {
+    /// The kind of value this command takes as its argument.
+    pub fn argument(&self) -> ArgumentKind {
         match self {
-            BaseCommand::Execute => true,
-            BaseCommand::CloseWindow => false,
-            BaseCommand::SwapTags => false,
-            BaseCommand::SoftReload => false,
-            BaseCommand::HardReload => false,
-            BaseCommand::ToggleScratchPad => true,
-            BaseCommand::ToggleFullScreen => false,
-            BaseCommand::ToggleSticky => false,
-            BaseCommand::GotoTag => true,
-            BaseCommand::ReturnToLastTag => false,
-            BaseCommand::FloatingToTile => false,
-            BaseCommand::TileToFloating => false,
-            BaseCommand::ToggleFloating => false,
-            BaseCommand::MoveWindowUp => false,
-            BaseCommand::MoveWindowDown => false,
-            BaseCommand::MoveWindowTop => false,
-            BaseCommand::FocusNextTag => false,
-            BaseCommand::FocusPreviousTag => false,
-            BaseCommand::FocusWindow => false,
-            BaseCommand::FocusWindowUp => false,
-            BaseCommand::FocusWindowDown => false,
-            BaseCommand::FocusWindowTop => false,
-            BaseCommand::FocusWorkspaceNext => false,
-            BaseCommand::FocusWorkspacePrevious => false,
-            BaseCommand::MoveToTag => true,
-            BaseCommand::MoveToLastWorkspace => false,
-            BaseCommand::MoveWindowToNextWorkspace => false,
-            BaseCommand::MoveWindowToPreviousWorkspace => false,
-            BaseCommand::MouseMoveWindow => false,
-            BaseCommand::NextLayout => false,
-            BaseCommand::PreviousLayout => false,
-            BaseCommand::SetLayout => true,
-            BaseCommand::RotateTag => false,
-            BaseCommand::IncreaseMainWidth => true,
-            BaseCommand::DecreaseMainWidth => true,
-            BaseCommand::SetMarginMultiplier => true,
-            //
-            BaseCommand::UnloadTheme => true,
-            BaseCommand::LoadTheme => true,
-            BaseCommand::CloseAllOtherWindows => false,
+            BaseCommand::Execute | BaseCommand::ToggleScratchPad | BaseCommand::LoadTheme => {
+                ArgumentKind::Text
+            }
+            BaseCommand::FocusWindow => ArgumentKind::OptionalText,
+            BaseCommand::GotoTag | BaseCommand::MoveToTag => ArgumentKind::Tag,
+            BaseCommand::MoveWindowTop => ArgumentKind::Bool { default: true },
+            BaseCommand::FocusWindowTop => ArgumentKind::Bool { default: false },
+            BaseCommand::IncreaseMainWidth | BaseCommand::DecreaseMainWidth => ArgumentKind::Step,
+            BaseCommand::SetMarginMultiplier => ArgumentKind::Float,
+            BaseCommand::SetLayout => ArgumentKind::Layout,
+            _ => ArgumentKind::None,
         }
     }
 }
 
+/// The type of the value a [`BaseCommand`] is bound with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgumentKind {
+    /// the value is ignored
+    None,
+    /// a non-empty string
+    Text,
+    /// a string which may be empty
+    OptionalText,
+    /// a tag number, starting at 1
+    Tag,
+    /// `true` or `false`, an empty value means `default`
+    Bool { default: bool },
+    /// a percentage from -128 to 127
+    Step,
+    /// a decimal number
+    Float,
+    /// the name of a built-in or defined layout
+    Layout,
+}
+
+impl ArgumentKind {
+    /// A short description of the expected value, used in error messages.
+    pub fn expected(self) -> &'static str {
+        match self {
+            ArgumentKind::None => "no value",
+            ArgumentKind::Text | ArgumentKind::OptionalText => "a string",
+            ArgumentKind::Tag => "a tag number starting at 1",
+            ArgumentKind::Bool { .. } => "`true` or `false`",
+            ArgumentKind::Step => "a whole number from -128 to 127",
+            ArgumentKind::Float => "a decimal number",
+            ArgumentKind::Layout => "a layout name",
+        }
+    }
+}
+
+/// The value a keybind is bound with, parsed for the [`ArgumentKind`] of its command.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Argument {
+    /// no value, or an empty one
+    #[default]
+    None,
+    Text(String),
+    Tag(TagId),
+    Bool(bool),
+    Step(i8),
+    Float(f32),
+    Layout(String),
+    /// a value which does not fit the command, kept as written so it can be reported
+    Invalid(String),
+}
+
+impl Argument {
+    /// Parses `value`, as written in the config, as the argument of a command taking `kind`.
+    pub fn parse(kind: ArgumentKind, value: &str) -> Self {
+        if value.is_empty() {
+            return Self::None;
+        }
+        let parsed = match kind {
+            ArgumentKind::None | ArgumentKind::Text | ArgumentKind::OptionalText => {
+                Some(Self::Text(value.to_owned()))
+            }
+            ArgumentKind::Layout => Some(Self::Layout(value.to_owned())),
+            ArgumentKind::Tag => value
+                .trim()
+                .parse()
+                .ok()
+                .filter(|tag| *tag > 0)
+                .map(Self::Tag),
+            ArgumentKind::Bool { .. } => value.trim().parse().ok().map(Self::Bool),
+            ArgumentKind::Step => value.trim().parse().ok().map(Self::Step),
+            ArgumentKind::Float => value.trim().parse().ok().map(Self::Float),
+        };
+        parsed.unwrap_or_else(|| Self::Invalid(value.to_owned()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::None
+    }
+}
+
+/// The string form leftwm reads, e.g. `1` for a tag.
+impl fmt::Display for Argument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Argument::None => Ok(()),
+            Argument::Text(text) | Argument::Layout(text) | Argument::Invalid(text) => {
+                f.write_str(text)
+            }
+            Argument::Tag(tag) => write!(f, "{tag}"),
+            Argument::Bool(flag) => write!(f, "{flag}"),
+            Argument::Step(step) => write!(f, "{step}"),
+            Argument::Float(float) => write!(f, "{float}"),
+        }
+    }
+}
+
+impl Serialize for Argument {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 pub type TagId = usize;
 
 #[allow(dead_code)]
@@ -140,7 +215,8 @@ pub enum CoreCommand {
     MouseMoveWindow,
     NextLayout,
     PreviousLayout,
-    SetLayout(Layout),
+    /// name of a built-in layout or of a `layout_definitions` entry
+    SetLayout(String),
     RotateTag,
     IncreaseMainWidth(i8),
     DecreaseMainWidth(i8),
@@ -149,3 +225,69 @@ pub enum CoreCommand {
     CloseAllOtherWindows,
     Other(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_typed_arguments() {
+        assert_eq!(Argument::parse(ArgumentKind::Tag, "3"), Argument::Tag(3));
+        assert_eq!(Argument::parse(ArgumentKind::Tag, " 3 "), Argument::Tag(3));
+        assert_eq!(
+            Argument::parse(ArgumentKind::Bool { default: true }, "false"),
+            Argument::Bool(false)
+        );
+        assert_eq!(
+            Argument::parse(ArgumentKind::Step, "-5"),
+            Argument::Step(-5)
+        );
+        assert_eq!(
+            Argument::parse(ArgumentKind::Float, "0.5"),
+            Argument::Float(0.5)
+        );
+        assert_eq!(
+            Argument::parse(ArgumentKind::Layout, "Monocle"),
+            Argument::Layout("Monocle".to_string())
+        );
+        assert_eq!(
+            Argument::parse(ArgumentKind::Text, "alacritty -e htop"),
+            Argument::Text("alacritty -e htop".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_empty_and_invalid_arguments() {
+        assert_eq!(Argument::parse(ArgumentKind::Tag, ""), Argument::None);
+        assert_eq!(Argument::parse(ArgumentKind::Text, ""), Argument::None);
+        for (kind, value) in [
+            (ArgumentKind::Tag, "0"),
+            (ArgumentKind::Tag, "x"),
+            (ArgumentKind::Bool { default: false }, "yes"),
+            (ArgumentKind::Step, "500"),
+            (ArgumentKind::Float, "half"),
+        ] {
+            assert_eq!(
+                Argument::parse(kind, value),
+                Argument::Invalid(value.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn arguments_are_written_as_strings() {
+        for (kind, value) in [
+            (ArgumentKind::Tag, "3"),
+            (ArgumentKind::Bool { default: true }, "true"),
+            (ArgumentKind::Step, "-5"),
+            (ArgumentKind::Float, "0.5"),
+            (ArgumentKind::Text, "rofi -show run"),
+            (ArgumentKind::Tag, "x"),
+            (ArgumentKind::Tag, ""),
+        ] {
+            let argument = Argument::parse(kind, value);
+            assert_eq!(argument.to_string(), value);
+            assert_eq!(ron::to_string(&argument).unwrap(), format!("{value:?}"));
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Carries the comments of a TOML config over to the RON config it is migrated to.
+//!
+//! Comments are attached to the setting which follows them, comments inside an entry of a
+//! `[[keybind]]` style list are attached to that entry. Trailing comments become a line of
+//! their own, comments which can't be placed end up at the bottom of the file.
+use crate::config::document::{self, Edit};
+use crate::config::migrate;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Anchor {
+    /// a top level setting
+    Field(String),
+    /// the entry with this index in a list of tables
+    Item(String, usize),
+}
+
+/// Inserts the comments of `toml` into `ron`, a serialization of the same config.
+pub fn carry_over(toml: &str, ron: &str) -> Result<String, document::ParseError> {
+    let (anchored, unplaced) = collect(toml);
+    let root = document::parse(ron)?;
+    let mut leftover = Vec::new();
+    let mut edits: Vec<Edit> = Vec::new();
+    for (anchor, comments) in anchored {
+        let start = match &anchor {
+            Anchor::Field(name) => root.field(name).map(|f| f.name_span.start),
+            Anchor::Item(name, index) => root
+                .field(name)
+                .and_then(|f| f.value.unwrap_some().items().get(*index))
+                .map(|item| item.span.start),
+        };
+        let Some(start) = start else {
+            leftover.extend(comments);
+            continue;
+        };
+        let line_start = ron[..start].rfind('\n').map_or(0, |i| i + 1);
+        let indent = &ron[line_start..start];
+        let text = comments
+            .iter()
+            .map(|comment| format!("{indent}//{comment}\n"))
+            .collect();
+        edits.push((line_start..line_start, text));
+    }
+    let mut result = document::apply_edits(ron, edits);
+    leftover.extend(unplaced);
+    if !leftover.is_empty() && !result.ends_with('\n') {
+        result.push('\n');
+    }
+    for comment in leftover {
+        result.push_str(&format!("//{comment}\n"));
+    }
+    Ok(result)
+}
+
+/// Groups the comments of `toml` by the setting they belong to, in source order.
+/// Comments after the last setting are returned separately.
+fn collect(toml: &str) -> (Vec<(Anchor, Vec<String>)>, Vec<String>) {
+    let mut anchored: Vec<(Anchor, Vec<String>)> = Vec::new();
+    let mut pending = Vec::new();
+    let mut table: Option<Anchor> = None;
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    let mut depth = 0usize;
+    // the setting whose value is being read, comments inside multi line values belong to it
+    let mut current: Option<Anchor> = None;
+
+    for line in toml.lines() {
+        let (code, comment) = split_comment(line);
+        let code = code.trim();
+        if let Some(comment) = comment {
+            pending.push(comment.to_string());
+        }
+        if code.is_empty() {
+            continue;
+        }
+        let anchor = if depth > 0 {
+            current.clone()
+        } else if let Some(name) = code.strip_prefix("[[").and_then(|c| c.strip_suffix("]]")) {
+            Some(table_anchor(name.trim(), true, &table, &mut counts))
+        } else if let Some(name) = code.strip_prefix('[').and_then(|c| c.strip_suffix(']')) {
+            Some(table_anchor(name.trim(), false, &table, &mut counts))
+        } else if table.is_some() {
+            table.clone()
+        } else {
+            code.split('=')
+                .next()
+                .map(|key| Anchor::Field(field_name(key.trim()).to_string()))
+        };
+        if let Some(anchor) = anchor {
+            if code.starts_with('[') {
+                table = Some(anchor.clone());
+            }
+            if !pending.is_empty() {
+                match anchored.last_mut() {
+                    Some((last, comments)) if *last == anchor => comments.append(&mut pending),
+                    _ => anchored.push((anchor.clone(), std::mem::take(&mut pending))),
+                }
+            }
+            current = Some(anchor);
+        }
+        // only brackets of values open multi line values, table headers are balanced
+        if !code.starts_with('[') || depth > 0 {
+            depth = bracket_depth(code, depth);
+        }
+    }
+    (anchored, pending)
+}
+
+/// The anchor for a `[name]` or `[[name]]` header. Headers of nested tables such as
+/// `[layout_definitions.columns]` belong to the entry of the outer list being written.
+fn table_anchor(
+    name: &str,
+    is_list: bool,
+    current: &Option<Anchor>,
+    counts: &mut Vec<(String, usize)>,
+) -> Anchor {
+    let (outer, nested) = match name.split_once('.') {
+        Some((outer, _)) => (field_name(outer.trim()), true),
+        None => (field_name(name), false),
+    };
+    if nested {
+        return match current {
+            Some(Anchor::Item(name, index)) if name == outer => Anchor::Item(name.clone(), *index),
+            _ => Anchor::Field(outer.to_string()),
+        };
+    }
+    if !is_list {
+        return Anchor::Field(outer.to_string());
+    }
+    let index = match counts.iter_mut().find(|(name, _)| name == outer) {
+        Some((_, count)) => {
+            *count += 1;
+            *count
+        }
+        None => {
+            counts.push((outer.to_string(), 0));
+            0
+        }
+    };
+    Anchor::Item(outer.to_string(), index)
+}
+
+/// The current name of a setting which may have been renamed by a migration.
+fn field_name(key: &str) -> &str {
+    migrate::current_field_name(key.trim_matches('"'))
+}
+
+/// Splits a line into its code and the text of its comment, ignoring `#` inside strings.
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return (&line[..i], Some(&line[i + 1..])),
+            _ => {}
+        }
+        escaped = false;
+    }
+    (line, None)
+}
+
+/// The nesting of `[` and `{` after `code`, starting from `depth`.
+fn bracket_depth(code: &str, mut depth: usize) -> usize {
+    let mut quote = None;
+    let mut escaped = false;
+    for c in code.chars() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '[' | '{') => depth += 1,
+            (None, ']' | '}') => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        escaped = false;
+    }
+    depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RON: &str = "(\n    modkey: \"Mod4\",\n    keybind: [\n        (command: Execute, key: \"Return\"),\n        (command: CloseWindow, key: \"q\"),\n    ],\n    window_rules: [\n        (window_class: \"mpv\"),\n    ],\n)";
+
+    #[test]
+    fn comments_are_placed_above_their_setting() {
+        let toml = "# the main modifier\nmodkey = \"Mod4\" # super\n";
+        assert_eq!(
+            carry_over(toml, RON).unwrap(),
+            RON.replace(
+                "    modkey",
+                "    // the main modifier\n    // super\n    modkey"
+            )
+        );
+    }
+
+    #[test]
+    fn comments_in_list_entries_stay_with_the_entry() {
+        let toml = "[[keybind]]\ncommand = \"Execute\"\nkey = \"Return\"\n\n[[keybind]]\n# close it\ncommand = \"CloseWindow\"\nkey = \"q\"\n";
+        assert_eq!(
+            carry_over(toml, RON).unwrap(),
+            RON.replace(
+                "        (command: CloseWindow",
+                "        // close it\n        (command: CloseWindow"
+            )
+        );
+    }
+
+    #[test]
+    fn comments_follow_renamed_settings() {
+        let toml = "[[window_config_by_class]]\n# mpv floats\nwm_class = \"mpv\"\n";
+        assert_eq!(
+            carry_over(toml, RON).unwrap(),
+            RON.replace(
+                "        (window_class",
+                "        // mpv floats\n        (window_class"
+            )
+        );
+    }
+
+    #[test]
+    fn unplaced_comments_go_to_the_bottom() {
+        assert_eq!(
+            carry_over("# gone\nremoved = 1\n# the end\n", "(y: 1)").unwrap(),
+            "(y: 1)\n// gone\n// the end\n"
+        );
+    }
+
+    #[test]
+    fn without_comments_nothing_changes() {
+        assert_eq!(carry_over("modkey = \"Mod4\"\n", RON).unwrap(), RON);
+    }
+}
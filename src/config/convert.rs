@@ -0,0 +1,173 @@
+//! Converts configs between RON, TOML and JSON, for tooling which cannot read RON.
+//!
+//! RON and TOML sources are upgraded to the current schema on the way, JSON is expected to
+//! already be current. Keys leftwm-config does not know about only survive in RON output.
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, bail};
+
+use crate::config::{Config, filehandler, migrate};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ron,
+    Toml,
+    Json,
+}
+
+impl Format {
+    /// The format of a file, by its extension.
+    pub fn of(path: &Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "ron" => Ok(Self::Ron),
+            "toml" => Ok(Self::Toml),
+            "json" => Ok(Self::Json),
+            _ => bail!("Unknown format `{s}`, expected ron, toml or json"),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ron => "ron",
+            Self::Toml => "toml",
+            Self::Json => "json",
+        })
+    }
+}
+
+/// Reads a config written in `format`, along with the schema migrations which were applied.
+pub fn read(source: &str, format: Format) -> Result<(Config, Vec<String>)> {
+    match format {
+        Format::Ron | Format::Toml => {
+            let (config, migrated) = filehandler::parse(source, format == Format::Ron)?;
+            Ok((config, migrated.applied))
+        }
+        Format::Json => {
+            let config: Config = serde_json::from_str(source)?;
+            if config.version != migrate::CURRENT_VERSION {
+                bail!(
+                    "JSON configs must use schema version {}, this one uses {}",
+                    migrate::CURRENT_VERSION,
+                    config.version
+                );
+            }
+            Ok((config, vec![]))
+        }
+    }
+}
+
+pub fn write(config: &Config, format: Format) -> Result<String> {
+    Ok(match format {
+        Format::Ron => filehandler::with_header(config)?,
+        Format::Toml => toml::to_string_pretty(config)?,
+        Format::Json => serde_json::to_string_pretty(config)? + "\n",
+    })
+}
+
+/// Reads `output` back as `format` and lists everything in which it differs from `config`.
+/// An empty list means the conversion lost nothing.
+pub fn verify(config: &Config, output: &str, format: Format) -> Result<Vec<String>> {
+    let (converted, _) = read(output, format)
+        .with_context(|| format!("The converted config could not be read back as {format}"))?;
+    let mut lost = filehandler::changed_settings(config, &converted)?;
+    if lost.is_empty()
+        && filehandler::to_ron(config, true)? != filehandler::to_ron(&converted, true)?
+    {
+        lost.push(format!(
+            "keys leftwm-config does not know about can not be written as {format}"
+        ));
+    }
+    Ok(lost)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMATS: [Format; 3] = [Format::Ron, Format::Toml, Format::Json];
+
+    const RON: &str = r#"#![enable(implicit_some)]
+(
+    version: 2,
+    modkey: "Mod4",
+    mousekey: ["Mod4", "Shift"],
+    tags: ["1", "2", "web"],
+    keybind: [
+        (command: Execute, value: "st", modifier: ["modkey"], key: "Return"),
+        (command: FocusNextTag, modifier: ["modkey", "Control"], key: "Right"),
+    ],
+    window_rules: [
+        (window_class: "krita", spawn_floating: true),
+    ],
+)
+"#;
+
+    fn config() -> Config {
+        read(RON, Format::Ron).unwrap().0
+    }
+
+    #[test]
+    fn format_is_read_from_names_and_extensions() {
+        assert_eq!("TOML".parse::<Format>().unwrap(), Format::Toml);
+        assert!("yaml".parse::<Format>().is_err());
+        assert_eq!(Format::of(Path::new("config.json")), Some(Format::Json));
+        assert_eq!(Format::of(Path::new("config")), None);
+        for format in FORMATS {
+            assert_eq!(format.to_string().parse::<Format>().unwrap(), format);
+        }
+    }
+
+    #[test]
+    fn every_format_converts_to_every_other_without_loss() {
+        let config = config();
+        for from in FORMATS {
+            let (source, _) = read(&write(&config, from).unwrap(), from).unwrap();
+            for to in FORMATS {
+                let output = write(&source, to).unwrap();
+                assert_eq!(verify(&config, &output, to).unwrap(), Vec::<String>::new());
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_keys_are_only_kept_in_ron() {
+        let source = RON.replace("modkey: \"Mod4\",", "modkey: \"Mod4\",\n    future: 1,");
+        let (config, _) = read(&source, Format::Ron).unwrap();
+        assert!(write(&config, Format::Ron).unwrap().contains("future: 1"));
+        for to in [Format::Toml, Format::Json] {
+            let output = write(&config, to).unwrap();
+            assert_eq!(verify(&config, &output, to).unwrap().len(), 1);
+        }
+    }
+
+    #[test]
+    fn old_schemas_are_upgraded_except_in_json() {
+        let old = RON
+            .replace("    version: 2,\n", "")
+            .replace(r#"["modkey", "Control"]"#, r#""modkey+Control""#);
+        let (config, applied) = read(&old, Format::Ron).unwrap();
+        assert_eq!(applied.len(), 1);
+        assert_eq!(config.version, migrate::CURRENT_VERSION);
+        assert_eq!(
+            verify(&config, RON, Format::Ron).unwrap(),
+            Vec::<String>::new()
+        );
+
+        let json = write(&config, Format::Json)
+            .unwrap()
+            .replace("\"version\": 2", "\"version\": 1");
+        assert!(read(&json, Format::Json).is_err());
+    }
+}
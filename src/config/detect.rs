@@ -0,0 +1,170 @@
+//! Finds the terminals, launchers, screen lockers and session tools which are installed,
+//! so new configs bind programs which exist on this machine.
+use std::os::unix::fs::PermissionsExt;
+use std::{env, fs};
+
+/// A program a keybind can start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Program {
+    /// the executable looked up in `$PATH`
+    pub name: &'static str,
+    /// what the keybind runs
+    pub command: &'static str,
+}
+
+const fn program(name: &'static str, command: &'static str) -> Program {
+    Program { name, command }
+}
+
+// order from least common to most common.
+// the thinking is if a machine has an uncommon terminal installed, it is intentional
+pub const TERMINALS: &[Program] = &[
+    program("alacritty", "alacritty"),
+    program("termite", "termite"),
+    program("kitty", "kitty"),
+    program("urxvt", "urxvt"),
+    program("rxvt", "rxvt"),
+    program("st", "st"),
+    program("roxterm", "roxterm"),
+    program("eterm", "eterm"),
+    program("xterm", "xterm"),
+    program("terminator", "terminator"),
+    program("terminology", "terminology"),
+    program("gnome-terminal", "gnome-terminal"),
+    program("xfce4-terminal", "xfce4-terminal"),
+    program("konsole", "konsole"),
+    program("uxterm", "uxterm"),
+    // at the bottom because of odd behaviour. guake wants F12 and should really be
+    // started using autostart instead of LeftWM keybind.
+    program("guake", "guake"),
+];
+
+pub const LAUNCHERS: &[Program] = &[
+    program("rofi", "rofi -show drun"),
+    program("dmenu_run", "dmenu_run"),
+    program("fuzzel", "fuzzel"),
+];
+
+pub const LOCKERS: &[Program] = &[
+    program("i3lock", "i3lock"),
+    program("betterlockscreen", "betterlockscreen -l"),
+    program("slock", "slock"),
+];
+
+/// Tools which end or manage the session, with loginctl leftwm is exited by ending the session.
+pub const SESSION_TOOLS: &[Program] = &[
+    program("loginctl", "loginctl kill-session $XDG_SESSION_ID"),
+    program("xss-lock", "xss-lock"),
+];
+
+/// Used when none of the known programs is installed, so the keybinds still exist.
+pub const FALLBACK_TERMINAL: &str = "termite";
+pub const FALLBACK_LAUNCHER: &str = "dmenu_run";
+pub const FALLBACK_LOCKER: &str = "slock";
+const FALLBACK_EXIT: &str = "pkill leftwm";
+
+/// The known programs which are installed, each list in order of preference. The default
+/// has none installed, so every choice is the fallback.
+#[derive(Default)]
+pub struct Environment {
+    pub terminals: Vec<Program>,
+    pub launchers: Vec<Program>,
+    pub lockers: Vec<Program>,
+    pub session_tools: Vec<Program>,
+}
+
+impl Environment {
+    #[must_use]
+    pub fn detect() -> Self {
+        Self {
+            terminals: installed(TERMINALS),
+            launchers: installed(LAUNCHERS),
+            lockers: installed(LOCKERS),
+            session_tools: installed(SESSION_TOOLS),
+        }
+    }
+
+    #[must_use]
+    pub fn terminal(&self) -> &'static str {
+        self.terminals
+            .first()
+            .map_or(FALLBACK_TERMINAL, |p| p.command)
+    }
+
+    #[must_use]
+    pub fn launcher(&self) -> &'static str {
+        self.launchers
+            .first()
+            .map_or(FALLBACK_LAUNCHER, |p| p.command)
+    }
+
+    #[must_use]
+    pub fn locker(&self) -> &'static str {
+        self.lockers.first().map_or(FALLBACK_LOCKER, |p| p.command)
+    }
+
+    /// The command which ends the session.
+    #[must_use]
+    pub fn exit_command(&self) -> &'static str {
+        self.session_tools
+            .iter()
+            .find(|p| p.name == "loginctl")
+            .map_or(FALLBACK_EXIT, |p| p.command)
+    }
+
+    /// Prints what was found and what a new config would use.
+    pub fn report(&self) {
+        let found = |programs: &[Program]| {
+            if programs.is_empty() {
+                "none found".to_string()
+            } else {
+                programs
+                    .iter()
+                    .map(|p| p.name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        };
+        println!("\x1b[0;94m::\x1b[0m Installed programs:");
+        println!("    terminals:     {}", found(&self.terminals));
+        println!("    launchers:     {}", found(&self.launchers));
+        println!("    lockers:       {}", found(&self.lockers));
+        println!("    session tools: {}", found(&self.session_tools));
+        println!("\x1b[0;94m::\x1b[0m A new config would use:");
+        println!("    terminal: {}", self.terminal());
+        println!("    launcher: {}", self.launcher());
+        println!("    locker:   {}", self.locker());
+        println!("    exit:     {}", self.exit_command());
+        for (kind, programs, fallback) in [
+            ("terminal", &self.terminals, FALLBACK_TERMINAL),
+            ("launcher", &self.launchers, FALLBACK_LAUNCHER),
+            ("locker", &self.lockers, FALLBACK_LOCKER),
+        ] {
+            if programs.is_empty() {
+                println!(
+                    "\x1b[1;93mWARN: No known {kind} is installed, `{fallback}` is bound but won't start until it is installed\x1b[0m"
+                );
+            }
+        }
+    }
+}
+
+fn installed(programs: &[Program]) -> Vec<Program> {
+    programs
+        .iter()
+        .filter(|p| is_program_in_path(p.name))
+        .copied()
+        .collect()
+}
+
+/// Whether an executable file called `program` is in one of the `$PATH` directories.
+#[must_use]
+pub fn is_program_in_path(program: &str) -> bool {
+    let Ok(path) = env::var("PATH") else {
+        return false;
+    };
+    path.split(':').any(|dir| {
+        fs::metadata(format!("{dir}/{program}"))
+            .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+    })
+}
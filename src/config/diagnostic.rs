@@ -0,0 +1,157 @@
+//! Findings of `check`, each with a severity, a stable code and the setting it is about.
+//!
+//! Codes never change meaning once released, so scripts can match on them:
+//!
+//! | codes     | checks                                   |
+//! |-----------|------------------------------------------|
+//! | `LW0xx`   | reading the config file                  |
+//! | `LW1xx`   | `modkey`, `mousekey` and other modifiers |
+//! | `LW2xx`   | behaviour options                        |
+//! | `LW3xx`   | workspaces                               |
+//! | `LW4xx`   | window rules                             |
+//! | `LW5xx`   | layouts                                  |
+//! | `LW6xx`   | keybinds                                 |
+//! | `LW7xx`   | the environment leftwm runs in           |
+//! | `LW8xx`   | references between sections              |
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::config::location::Location;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// The exit status of `check` when it could not run, e.g. because the config directory
+    /// or the monitor layout could not be read.
+    pub const CHECK_FAILED: i32 = 3;
+
+    /// The exit status of `check` when this is the worst finding: 0 without warnings,
+    /// 1 with warnings and 2 with errors. See also [`Self::CHECK_FAILED`].
+    #[must_use]
+    pub fn exit_code(worst: Option<Self>) -> i32 {
+        match worst {
+            None | Some(Self::Info) => 0,
+            Some(Self::Warning) => 1,
+            Some(Self::Error) => 2,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Info => "INFO",
+            Self::Warning => "WARN",
+            Self::Error => "ERROR",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// e.g. `LW602`
+    pub code: &'static str,
+    /// the setting, e.g. `keybind[12].key`, empty for findings outside the config
+    pub path: String,
+    pub message: String,
+    /// where in the config file the setting is written, see [`crate::config::location`]
+    pub location: Option<Location>,
+}
+
+impl Diagnostic {
+    pub fn error(code: &'static str, path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, code, path, message)
+    }
+
+    pub fn warning(
+        code: &'static str,
+        path: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self::new(Severity::Warning, code, path, message)
+    }
+
+    pub fn info(code: &'static str, path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Info, code, path, message)
+    }
+
+    fn new(
+        severity: Severity,
+        code: &'static str,
+        path: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity,
+            code,
+            path: path.into(),
+            message: message.into(),
+            location: None,
+        }
+    }
+
+    /// Prints the diagnostic in the colour of its severity, followed by the line of the
+    /// config it is about.
+    pub fn print(&self) {
+        let colour = match self.severity {
+            Severity::Info => "\x1b[0;94m",
+            Severity::Warning => "\x1b[1;93m",
+            Severity::Error => "\x1b[1;91m",
+        };
+        println!("{colour}{self}\x1b[0m");
+        if let Some(location) = &self.location {
+            location.print_frame(colour);
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]", self.severity, self.code)?;
+        if !self.path.is_empty() {
+            write!(f, " {}", self.path)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The most severe finding in `diagnostics`.
+#[must_use]
+pub fn worst(diagnostics: &[Diagnostic]) -> Option<Severity> {
+    diagnostics.iter().map(|d| d.severity).max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_code_follows_the_worst_severity() {
+        assert_eq!(Severity::exit_code(None), 0);
+        assert_eq!(Severity::exit_code(Some(Severity::Info)), 0);
+        assert_eq!(Severity::exit_code(Some(Severity::Warning)), 1);
+        assert_eq!(Severity::exit_code(Some(Severity::Error)), 2);
+        assert_ne!(Severity::CHECK_FAILED, 1);
+        assert_ne!(Severity::CHECK_FAILED, 2);
+    }
+
+    #[test]
+    fn worst_is_the_most_severe_finding() {
+        assert_eq!(worst(&[]), None);
+        let findings = [
+            Diagnostic::info("LW000", "", ""),
+            Diagnostic::error("LW000", "", ""),
+            Diagnostic::warning("LW000", "", ""),
+        ];
+        assert_eq!(worst(&findings), Some(Severity::Error));
+        assert_eq!(worst(&findings[..1]), Some(Severity::Info));
+    }
+}
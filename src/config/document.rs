@@ -0,0 +1,915 @@
+//! A small RON reader which keeps the byte span of every value it parses.
+//!
+//! `ron` itself only gives us typed values, so anything the `Config` structs don't model
+//! (unknown keys, comments, formatting) is lost once a file is deserialized. This parser
+//! is used next to the real deserializer to find where each value lives in the source.
+use std::ops::Range;
+
+use thiserror::Error;
+
+pub type Span = Range<usize>;
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum NodeKind {
+    /// Strings, chars, numbers and bare identifiers such as `true`, `None` or `Tag`
+    Scalar,
+    /// `Name(field: value, ..)`, also used for an empty `()`
+    Struct {
+        // not every consumer cares about struct names, the tree stays complete anyway
+        #[allow(dead_code)]
+        name: Option<String>,
+        fields: Vec<Field>,
+    },
+    /// `Name(value, ..)`, e.g. `Some(3)` or `Pixel(800)`
+    Tuple {
+        name: Option<String>,
+        items: Vec<Node>,
+    },
+    Seq(Vec<Node>),
+    Map(#[allow(dead_code)] Vec<(Node, Node)>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub name: String,
+    pub name_span: Span,
+    pub value: Node,
+}
+
+#[derive(Debug, Error)]
+#[error("{message} at line {line}, column {column}")]
+pub struct ParseError {
+    pub message: String,
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Node {
+    #[must_use]
+    pub fn fields(&self) -> &[Field] {
+        match &self.kind {
+            NodeKind::Struct { fields, .. } => fields,
+            _ => &[],
+        }
+    }
+
+    #[must_use]
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields().iter().find(|f| f.name == name)
+    }
+
+    #[must_use]
+    pub fn items(&self) -> &[Node] {
+        match &self.kind {
+            NodeKind::Seq(items) | NodeKind::Tuple { items, .. } => items,
+            _ => &[],
+        }
+    }
+
+    #[must_use]
+    pub fn is_struct(&self) -> bool {
+        matches!(self.kind, NodeKind::Struct { .. })
+    }
+
+    /// Looks through an explicit `Some(..)`, configs written without `implicit_some` use them.
+    #[must_use]
+    pub fn unwrap_some(&self) -> &Node {
+        match &self.kind {
+            NodeKind::Tuple {
+                name: Some(name),
+                items,
+            } if name == "Some" && items.len() == 1 => &items[0],
+            _ => self,
+        }
+    }
+
+    #[must_use]
+    pub fn text<'s>(&self, source: &'s str) -> &'s str {
+        &source[self.span.clone()]
+    }
+}
+
+/// Parses a complete RON document, including an optional `#![enable(..)]` header.
+///
+/// # Errors
+///
+/// Returns a `ParseError` pointing at the first token which isn't valid RON.
+pub fn parse(source: &str) -> Result<Node, ParseError> {
+    let mut parser = Parser { source, pos: 0 };
+    parser.skip_trivia()?;
+    while parser.source[parser.pos..].starts_with("#!") {
+        parser.pos += 2;
+        parser.expect('[')?;
+        parser.skip_balanced('[', ']')?;
+        parser.skip_trivia()?;
+    }
+    let root = parser.value()?;
+    parser.skip_trivia()?;
+    if parser.pos < source.len() {
+        return Err(parser.error("unexpected trailing characters"));
+    }
+    Ok(root)
+}
+
+/// Converts a byte offset into a 1-based line and column.
+#[must_use]
+pub fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before.chars().count(), |i| before[i + 1..].chars().count())
+        + 1;
+    (line, column)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.source[self.pos..].chars().next()
+    }
+
+    fn peek_at(&self, n: usize) -> Option<char> {
+        self.source[self.pos..].chars().nth(n)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        let (line, column) = line_col(self.source, self.pos);
+        ParseError {
+            message: message.to_string(),
+            offset: self.pos,
+            line,
+            column,
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{c}`")))
+        }
+    }
+
+    fn skip_trivia(&mut self) -> Result<(), ParseError> {
+        loop {
+            let rest = &self.source[self.pos..];
+            if rest.starts_with("//") {
+                self.pos += rest.find('\n').unwrap_or(rest.len());
+            } else if rest.starts_with("/*") {
+                self.skip_block_comment()?;
+            } else if let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+                self.pos += c.len_utf8();
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    // RON block comments nest
+    fn skip_block_comment(&mut self) -> Result<(), ParseError> {
+        let mut depth = 0;
+        loop {
+            let rest = &self.source[self.pos..];
+            if rest.starts_with("/*") {
+                depth += 1;
+                self.pos += 2;
+            } else if rest.starts_with("*/") {
+                depth -= 1;
+                self.pos += 2;
+                if depth == 0 {
+                    return Ok(());
+                }
+            } else if self.bump().is_none() {
+                return Err(self.error("unterminated block comment"));
+            }
+        }
+    }
+
+    fn skip_balanced(&mut self, open: char, close: char) -> Result<(), ParseError> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.bump() {
+                Some(c) if c == open => depth += 1,
+                Some(c) if c == close => depth -= 1,
+                Some(_) => {}
+                None => return Err(self.error(&format!("expected `{close}`"))),
+            }
+        }
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Node, ParseError> {
+        let start = self.pos;
+        match self.peek() {
+            Some('(') => self.parens(start, None),
+            Some('[') => self.seq(start),
+            Some('{') => self.map(start),
+            Some('"') => {
+                self.string()?;
+                Ok(self.scalar(start))
+            }
+            Some('\'') => {
+                self.char_literal()?;
+                Ok(self.scalar(start))
+            }
+            Some('r' | 'b') if self.is_string_prefix() => {
+                while self.peek() != Some('"') && self.peek() != Some('#') {
+                    self.bump();
+                }
+                if self.source[start..self.pos].contains('r') {
+                    self.raw_string()?;
+                } else {
+                    self.string()?;
+                }
+                Ok(self.scalar(start))
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                self.number();
+                Ok(self.scalar(start))
+            }
+            Some(c) if is_ident_start(c) => {
+                let name = self.ident()?;
+                let after_ident = self.pos;
+                self.skip_trivia()?;
+                if self.peek() == Some('(') {
+                    self.parens(start, Some(name))
+                } else {
+                    self.pos = after_ident;
+                    Ok(self.scalar(start))
+                }
+            }
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of file")),
+        }
+    }
+
+    fn scalar(&self, start: usize) -> Node {
+        Node {
+            kind: NodeKind::Scalar,
+            span: start..self.pos,
+        }
+    }
+
+    fn is_string_prefix(&self) -> bool {
+        matches!(
+            (self.peek(), self.peek_at(1), self.peek_at(2)),
+            (Some('r'), Some('"'), _)
+                | (Some('r'), Some('#'), Some('"' | '#'))
+                | (Some('b'), Some('"'), _)
+                | (Some('b'), Some('r'), Some('"' | '#'))
+        )
+    }
+
+    fn ident(&mut self) -> Result<String, ParseError> {
+        if self.source[self.pos..].starts_with("r#") {
+            self.pos += 2;
+        }
+        let start = self.pos;
+        match self.peek() {
+            Some(c) if is_ident_start(c) => {
+                self.bump();
+            }
+            _ => return Err(self.error("expected an identifier")),
+        }
+        while self.peek().is_some_and(is_ident_char) {
+            self.bump();
+        }
+        Ok(self.source[start..self.pos].to_string())
+    }
+
+    fn number(&mut self) {
+        self.bump();
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || "_.+-".contains(c))
+        {
+            self.bump();
+        }
+    }
+
+    fn string(&mut self) -> Result<(), ParseError> {
+        self.expect('"')?;
+        loop {
+            match self.bump() {
+                Some('\\') => {
+                    self.bump();
+                }
+                Some('"') => return Ok(()),
+                Some(_) => {}
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn raw_string(&mut self) -> Result<(), ParseError> {
+        let mut hashes = 0;
+        while self.peek() == Some('#') {
+            hashes += 1;
+            self.bump();
+        }
+        self.expect('"')?;
+        let terminator = format!("\"{}", "#".repeat(hashes));
+        match self.source[self.pos..].find(&terminator) {
+            Some(i) => {
+                self.pos += i + terminator.len();
+                Ok(())
+            }
+            None => Err(self.error("unterminated raw string")),
+        }
+    }
+
+    fn char_literal(&mut self) -> Result<(), ParseError> {
+        self.expect('\'')?;
+        loop {
+            match self.bump() {
+                Some('\\') => {
+                    self.bump();
+                }
+                Some('\'') => return Ok(()),
+                Some(_) => {}
+                None => return Err(self.error("unterminated char")),
+            }
+        }
+    }
+
+    fn is_field_start(&mut self) -> Result<bool, ParseError> {
+        let start = self.pos;
+        let is_field = if self.peek().is_some_and(is_ident_start) {
+            self.ident()?;
+            self.skip_trivia()?;
+            self.peek() == Some(':') && self.peek_at(1) != Some(':')
+        } else {
+            false
+        };
+        self.pos = start;
+        Ok(is_field)
+    }
+
+    fn parens(&mut self, start: usize, name: Option<String>) -> Result<Node, ParseError> {
+        self.expect('(')?;
+        self.skip_trivia()?;
+        if self.peek() == Some(')') || self.is_field_start()? {
+            let mut fields = vec![];
+            while self.peek() != Some(')') {
+                let name_start = self.pos;
+                let field_name = self.ident()?;
+                let name_span = name_start..self.pos;
+                self.skip_trivia()?;
+                self.expect(':')?;
+                self.skip_trivia()?;
+                let value = self.value()?;
+                fields.push(Field {
+                    name: field_name,
+                    name_span,
+                    value,
+                });
+                if !self.separator(')')? {
+                    break;
+                }
+            }
+            self.expect(')')?;
+            Ok(Node {
+                kind: NodeKind::Struct { name, fields },
+                span: start..self.pos,
+            })
+        } else {
+            let mut items = vec![];
+            while self.peek() != Some(')') {
+                items.push(self.value()?);
+                if !self.separator(')')? {
+                    break;
+                }
+            }
+            self.expect(')')?;
+            Ok(Node {
+                kind: NodeKind::Tuple { name, items },
+                span: start..self.pos,
+            })
+        }
+    }
+
+    fn seq(&mut self, start: usize) -> Result<Node, ParseError> {
+        self.expect('[')?;
+        self.skip_trivia()?;
+        let mut items = vec![];
+        while self.peek() != Some(']') {
+            items.push(self.value()?);
+            if !self.separator(']')? {
+                break;
+            }
+        }
+        self.expect(']')?;
+        Ok(Node {
+            kind: NodeKind::Seq(items),
+            span: start..self.pos,
+        })
+    }
+
+    fn map(&mut self, start: usize) -> Result<Node, ParseError> {
+        self.expect('{')?;
+        self.skip_trivia()?;
+        let mut entries = vec![];
+        while self.peek() != Some('}') {
+            let key = self.value()?;
+            self.skip_trivia()?;
+            self.expect(':')?;
+            self.skip_trivia()?;
+            let value = self.value()?;
+            entries.push((key, value));
+            if !self.separator('}')? {
+                break;
+            }
+        }
+        self.expect('}')?;
+        Ok(Node {
+            kind: NodeKind::Map(entries),
+            span: start..self.pos,
+        })
+    }
+
+    /// Consumes the `,` after an element, returns false if the closing delimiter follows instead.
+    fn separator(&mut self, close: char) -> Result<bool, ParseError> {
+        self.skip_trivia()?;
+        match self.peek() {
+            Some(',') => {
+                self.bump();
+                self.skip_trivia()?;
+                Ok(true)
+            }
+            Some(c) if c == close => Ok(false),
+            _ => Err(self.error(&format!("expected `,` or `{close}`"))),
+        }
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// A replacement of `span` in some source text, an empty span is an insertion.
+pub type Edit = (Span, String);
+
+/// Builds the edit which adds `entries` (each `name: value`) to the end of a struct,
+/// following the struct's existing layout. A comment after the last field stays on its line.
+#[must_use]
+pub fn field_insertion(node: &Node, source: &str, entries: &[String]) -> Option<Edit> {
+    if entries.is_empty() || !node.is_struct() {
+        return None;
+    }
+    match (node.fields().first(), node.fields().last()) {
+        (Some(first), Some(last)) => {
+            let before_first = &source[node.span.start..first.name_span.start];
+            let end = last.value.span.end;
+            let Some(i) = before_first.rfind('\n') else {
+                let text = entries.iter().map(|entry| format!(", {entry}")).collect();
+                return Some((end..end, text));
+            };
+            let indent = &before_first[i + 1..];
+            let line_end = source[end..].find('\n').map_or(source.len(), |n| end + n);
+            let rest = source[end..line_end].trim_end();
+            let (comma, comment) = match rest.trim_start().strip_prefix(',') {
+                Some(comment) => (",", comment),
+                None => ("", rest),
+            };
+            if comment.trim().is_empty() || comment.trim_start().starts_with("//") {
+                let text: Vec<String> = entries
+                    .iter()
+                    .map(|entry| format!("\n{indent}{entry}"))
+                    .collect();
+                Some((
+                    end..line_end,
+                    format!(",{comment}{}{comma}", text.join(",")),
+                ))
+            } else {
+                // something else follows on the line, e.g. the closing parenthesis
+                let text = entries
+                    .iter()
+                    .map(|entry| format!(",\n{indent}{entry}"))
+                    .collect();
+                Some((end..end, text))
+            }
+        }
+        _ => {
+            let end = node.span.end - 1;
+            Some((end..end, entries.join(", ")))
+        }
+    }
+}
+
+/// Applies non-overlapping edits to `source`, spans refer to the unmodified source.
+#[must_use]
+pub fn apply_edits(source: &str, mut edits: Vec<Edit>) -> String {
+    edits.sort_by_key(|(span, _)| std::cmp::Reverse((span.start, span.end)));
+    let mut result = source.to_string();
+    for (span, text) in edits {
+        result.replace_range(span, &text);
+    }
+    result
+}
+
+/// Lists the extensions enabled by `#![enable(..)]` attributes at the top of a document.
+#[must_use]
+pub fn extensions(source: &str) -> Vec<String> {
+    let mut parser = Parser { source, pos: 0 };
+    let mut extensions = vec![];
+    while parser.skip_trivia().is_ok() && parser.source[parser.pos..].starts_with("#!") {
+        parser.pos += 2;
+        let start = parser.pos;
+        if parser.expect('[').is_err() || parser.skip_balanced('[', ']').is_err() {
+            break;
+        }
+        let attribute = &source[start..parser.pos];
+        if let Some(list) = attribute
+            .find('(')
+            .zip(attribute.rfind(')'))
+            .filter(|_| attribute.contains("enable"))
+            .map(|(open, close)| &attribute[open + 1..close])
+        {
+            extensions.extend(list.split(',').map(|e| e.trim().to_string()));
+        }
+    }
+    extensions
+}
+
+/// Applies the differences between `old` and `new`, two serializations of the same schema,
+/// to `doc`, the hand-written document `old` was produced from.
+///
+/// Values are only rewritten where `old` and `new` disagree. Struct fields are matched by
+/// name and sequence items by the longest run of unchanged items, so comments, blank lines
+/// and key order around untouched values are kept as they are.
+///
+/// # Errors
+///
+/// Returns a `ParseError` if any of the three documents isn't valid RON.
+pub fn update(doc: &str, old: &str, new: &str) -> Result<String, ParseError> {
+    let doc_root = parse(doc)?;
+    let old_root = parse(old)?;
+    let new_root = parse(new)?;
+    let mut differ = Differ {
+        doc,
+        old,
+        new,
+        edits: vec![],
+    };
+    differ.node(&doc_root, &old_root, &new_root);
+    Ok(apply_edits(doc, differ.edits))
+}
+
+struct Differ<'a> {
+    doc: &'a str,
+    old: &'a str,
+    new: &'a str,
+    edits: Vec<Edit>,
+}
+
+impl Differ<'_> {
+    fn node(&mut self, doc: &Node, old: &Node, new: &Node) {
+        if old.text(self.old) == new.text(self.new) {
+            return;
+        }
+        // `Some(..)` may be written out even with `implicit_some` enabled
+        let doc = if std::ptr::eq(new.unwrap_some(), new) {
+            doc.unwrap_some()
+        } else {
+            doc
+        };
+        match (&doc.kind, &old.kind, &new.kind) {
+            (NodeKind::Struct { .. }, NodeKind::Struct { .. }, NodeKind::Struct { .. }) => {
+                self.fields(doc, old, new);
+            }
+            (NodeKind::Seq(doc_items), NodeKind::Seq(old_items), NodeKind::Seq(new_items))
+                if doc_items.len() == old_items.len() =>
+            {
+                self.items(doc, new, doc_items, old_items, new_items);
+            }
+            _ => self.replace(doc, new),
+        }
+    }
+
+    fn fields(&mut self, doc: &Node, old: &Node, new: &Node) {
+        let indent = match doc.fields().first() {
+            Some(first) => indent_of(self.doc, first.name_span.start).to_string(),
+            None => format!("{}    ", indent_of(self.doc, doc.span.start)),
+        };
+        let mut missing = vec![];
+        for new_field in new.fields() {
+            let old_field = old.field(&new_field.name);
+            match (doc.field(&new_field.name), old_field) {
+                (Some(doc_field), Some(old_field)) => {
+                    self.node(&doc_field.value, &old_field.value, &new_field.value);
+                }
+                (Some(doc_field), None) => self.replace(&doc_field.value, &new_field.value),
+                // left out of the document and still at its default
+                (None, Some(old_field))
+                    if old_field.value.text(self.old) == new_field.value.text(self.new) => {}
+                (None, _) => missing.push(format!(
+                    "{}: {}",
+                    new_field.name,
+                    self.new_text(&new_field.value, new_field.name_span.start, &indent)
+                )),
+            }
+        }
+        self.edits.extend(field_insertion(doc, self.doc, &missing));
+    }
+
+    fn items(
+        &mut self,
+        doc: &Node,
+        new: &Node,
+        doc_items: &[Node],
+        old_items: &[Node],
+        new_items: &[Node],
+    ) {
+        if doc_items.is_empty() || new_items.is_empty() {
+            self.replace(doc, new);
+            return;
+        }
+        let indent = indent_of(self.doc, doc_items[0].span.start).to_string();
+        let separator = if self.doc[doc.span.start..doc_items[0].span.start].contains('\n') {
+            format!(",\n{indent}")
+        } else {
+            ", ".to_string()
+        };
+
+        // walk the gaps between the items which are unchanged, pairing up what changed
+        let mut old_start = 0;
+        let mut new_start = 0;
+        let anchors = self.unchanged_items(old_items, new_items);
+        for (old_end, new_end) in anchors
+            .into_iter()
+            .chain([(old_items.len(), new_items.len())])
+        {
+            let paired = (old_end - old_start).min(new_end - new_start);
+            for i in 0..paired {
+                self.node(
+                    &doc_items[old_start + i],
+                    &old_items[old_start + i],
+                    &new_items[new_start + i],
+                );
+            }
+
+            let removed = old_start + paired..old_end;
+            if !removed.is_empty() {
+                let start = self.item_start(doc, doc_items, removed.start);
+                let span = if removed.end < doc_items.len() {
+                    // comments in front of the next item stay with it
+                    start..self.item_start(doc, doc_items, removed.end)
+                } else {
+                    // there is always an item left in front, new_items isn't empty
+                    let end = doc_items[removed.start - 1].span.end;
+                    if self.doc[end..doc_items[removed.start].span.start].contains('\n') {
+                        start..self.line_end(doc_items[removed.end - 1].span.end)
+                    } else {
+                        end..doc_items[removed.end - 1].span.end
+                    }
+                };
+                self.edits.push((span, String::new()));
+            }
+
+            let added: Vec<String> = new_items[new_start + paired..new_end]
+                .iter()
+                .map(|item| self.new_text(item, item.span.start, &indent))
+                .collect();
+            if !added.is_empty() {
+                let at = old_start + paired;
+                if at > 0 {
+                    let end = doc_items[at - 1].span.end;
+                    let text = added.iter().map(|t| format!("{separator}{t}")).collect();
+                    self.edits.push((end..end, text));
+                } else {
+                    let start = doc_items[0].span.start;
+                    let text = added.iter().map(|t| format!("{t}{separator}")).collect();
+                    self.edits.push((start..start, text));
+                }
+            }
+
+            old_start = old_end + 1;
+            new_start = new_end + 1;
+        }
+    }
+
+    /// Where the line of `items[i]` starts, including the comment lines above it. An item
+    /// which shares its line with the one in front starts at the item itself.
+    fn item_start(&self, doc: &Node, items: &[Node], i: usize) -> usize {
+        let start = items[i].span.start;
+        let from = match i {
+            0 => doc.span.start + 1,
+            _ => items[i - 1].span.end,
+        };
+        self.doc[from..start]
+            .find('\n')
+            .map_or(start, |n| from + n + 1)
+    }
+
+    /// The start of the line after `end` if only a comma and a comment follow `end` on its
+    /// line, `end` otherwise.
+    fn line_end(&self, end: usize) -> usize {
+        let Some(n) = self.doc[end..].find('\n') else {
+            return end;
+        };
+        let rest = self.doc[end..end + n].trim_start();
+        let rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+        if rest.is_empty() || rest.starts_with("//") {
+            end + n + 1
+        } else {
+            end
+        }
+    }
+
+    /// Index pairs of the longest run of items `old` and `new` have in common.
+    fn unchanged_items(&self, old: &[Node], new: &[Node]) -> Vec<(usize, usize)> {
+        let same = |i: usize, j: usize| old[i].text(self.old) == new[j].text(self.new);
+        let mut lengths = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+        for i in (0..old.len()).rev() {
+            for j in (0..new.len()).rev() {
+                lengths[i][j] = if same(i, j) {
+                    lengths[i + 1][j + 1] + 1
+                } else {
+                    lengths[i + 1][j].max(lengths[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        let mut pairs = vec![];
+        while i < old.len() && j < new.len() {
+            if same(i, j) {
+                pairs.push((i, j));
+                i += 1;
+                j += 1;
+            } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        pairs
+    }
+
+    fn replace(&mut self, doc: &Node, new: &Node) {
+        let text = self.new_text(new, new.span.start, indent_of(self.doc, doc.span.start));
+        self.edits.push((doc.span.clone(), text));
+    }
+
+    /// The text of `new`, with following lines moved from the indentation of the line at
+    /// `line_offset` in the new document to `indent`.
+    fn new_text(&self, new: &Node, line_offset: usize, indent: &str) -> String {
+        let from = indent_of(self.new, line_offset);
+        let mut lines = new.text(self.new).split('\n');
+        let mut text = lines.next().unwrap_or_default().to_string();
+        for line in lines {
+            text.push('\n');
+            match line.strip_prefix(from) {
+                Some(rest) => {
+                    text.push_str(indent);
+                    text.push_str(rest);
+                }
+                None => text.push_str(line),
+            }
+        }
+        text
+    }
+}
+
+/// The leading whitespace of the line containing `offset`.
+fn indent_of(source: &str, offset: usize) -> &str {
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = &source[line_start..];
+    &line[..line.len() - line.trim_start().len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_keeps_comments_and_unknown_fields() {
+        let doc = "(\n    // the main modifier\n    modkey: \"Mod4\",\n    future_key: 3, // not modelled\n    gaps: 5,\n)\n";
+        let old = "(modkey: \"Mod4\", gaps: 5)";
+        let new = "(modkey: \"Mod1\", gaps: 5)";
+        assert_eq!(
+            update(doc, old, new).unwrap(),
+            "(\n    // the main modifier\n    modkey: \"Mod1\",\n    future_key: 3, // not modelled\n    gaps: 5,\n)\n"
+        );
+    }
+
+    #[test]
+    fn update_without_changes_returns_the_document() {
+        let doc = "(\n    tags: [\"1\", \"2\"], // two tags\n)\n";
+        let old = "(tags: [\"1\", \"2\"])";
+        assert_eq!(update(doc, old, old).unwrap(), doc);
+    }
+
+    #[test]
+    fn update_inserts_list_items() {
+        let doc = "(\n    tags: [\n        \"1\",\n        // second\n        \"2\",\n    ],\n)\n";
+        let old = "(tags: [\"1\", \"2\"])";
+        let new = "(tags: [\"1\", \"2\", \"3\"])";
+        assert_eq!(
+            update(doc, old, new).unwrap(),
+            "(\n    tags: [\n        \"1\",\n        // second\n        \"2\",\n        \"3\",\n    ],\n)\n"
+        );
+        let new = "(tags: [\"0\", \"1\", \"2\"])";
+        assert_eq!(
+            update(doc, old, new).unwrap(),
+            "(\n    tags: [\n        \"0\",\n        \"1\",\n        // second\n        \"2\",\n    ],\n)\n"
+        );
+    }
+
+    #[test]
+    fn update_removes_list_items() {
+        let doc = "(\n    tags: [\n        \"1\",\n        // second\n        \"2\",\n        \"3\",\n    ],\n)\n";
+        let old = "(tags: [\"1\", \"2\", \"3\"])";
+        let new = "(tags: [\"1\", \"3\"])";
+        assert_eq!(
+            update(doc, old, new).unwrap(),
+            "(\n    tags: [\n        \"1\",\n        \"3\",\n    ],\n)\n"
+        );
+        let new = "(tags: [\"1\", \"2\"])";
+        assert_eq!(
+            update(doc, old, new).unwrap(),
+            "(\n    tags: [\n        \"1\",\n        // second\n        \"2\",\n    ],\n)\n"
+        );
+    }
+
+    #[test]
+    fn update_removes_the_comments_of_removed_items() {
+        let doc = "(\n    tags: [\n        // first\n        \"1\",\n        \"2\", // two\n        // third\n        \"3\", // three\n    ],\n)\n";
+        let old = "(tags: [\"1\", \"2\", \"3\"])";
+        let new = "(tags: [\"2\", \"3\"])";
+        assert_eq!(
+            update(doc, old, new).unwrap(),
+            "(\n    tags: [\n        \"2\", // two\n        // third\n        \"3\", // three\n    ],\n)\n"
+        );
+        let new = "(tags: [\"1\", \"2\"])";
+        assert_eq!(
+            update(doc, old, new).unwrap(),
+            "(\n    tags: [\n        // first\n        \"1\",\n        \"2\", // two\n    ],\n)\n"
+        );
+        let doc = "(tags: [\"1\", \"2\", \"3\"])";
+        assert_eq!(update(doc, old, new).unwrap(), "(tags: [\"1\", \"2\"])");
+    }
+
+    #[test]
+    fn field_insertion_keeps_a_trailing_comment_with_its_field() {
+        let doc = "(\n    modkey: \"Mod4\", // the main modifier\n)\n";
+        let root = parse(doc).unwrap();
+        let edit = field_insertion(&root, doc, &["gaps: 8".to_string()]);
+        assert_eq!(
+            apply_edits(doc, edit.into_iter().collect()),
+            "(\n    modkey: \"Mod4\", // the main modifier\n    gaps: 8,\n)\n"
+        );
+        let doc = "(\n    modkey: \"Mod4\" // no comma\n)\n";
+        let root = parse(doc).unwrap();
+        let edit = field_insertion(&root, doc, &["gaps: 8".to_string(), "a: 1".to_string()]);
+        assert_eq!(
+            apply_edits(doc, edit.into_iter().collect()),
+            "(\n    modkey: \"Mod4\", // no comma\n    gaps: 8,\n    a: 1\n)\n"
+        );
+        let doc = "(modkey: \"Mod4\")";
+        let root = parse(doc).unwrap();
+        let edit = field_insertion(&root, doc, &["gaps: 8".to_string()]);
+        assert_eq!(
+            apply_edits(doc, edit.into_iter().collect()),
+            "(modkey: \"Mod4\", gaps: 8)"
+        );
+    }
+
+    #[test]
+    fn update_adds_fields_missing_from_the_document() {
+        let doc = "(\n    modkey: \"Mod4\",\n)\n";
+        let old = "(modkey: \"Mod4\", gaps: 5)";
+        let new = "(modkey: \"Mod4\", gaps: 8)";
+        assert_eq!(
+            update(doc, old, new).unwrap(),
+            "(\n    modkey: \"Mod4\",\n    gaps: 8,\n)\n"
+        );
+    }
+
+    #[test]
+    fn update_reports_invalid_documents() {
+        assert!(update("(modkey: ", "()", "()").is_err());
+    }
+}
@@ -1,12 +1,17 @@
 use std::fs;
-use std::fs::File;
-use std::io::Write;
+use std::io::IsTerminal;
+use std::mem;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-use anyhow::Result;
+use anyhow::{Result, bail, ensure};
 use xdg::BaseDirectories;
 
-use crate::config::Config;
+use crate::config::detect::Environment;
+use crate::config::{Config, backup, document, migrate, profile, unknown, wizard};
+
+/// The config file chosen with `--config` or `LEFTWM_CONFIG`, see [`set_config_path`].
+static CONFIG_PATH: OnceLock<PathBuf> = OnceLock::new();
 
 #[must_use]
 pub fn load() -> Config {
@@ -23,26 +28,10 @@ pub fn load() -> Config {
 /// If a path is specified and does not exist, returns `LeftError`.
 pub fn load_from_file(fspath: Option<&str>, verbose: bool) -> Result<Config> {
     let config_filename = if let Some(fspath) = fspath {
-        println!("\x1b[1;35mNote: Using file {fspath} \x1b[0m");
+        eprintln!("\x1b[1;35mNote: Using file {fspath} \x1b[0m");
         PathBuf::from(fspath)
     } else {
-        let ron_file = BaseDirectories::with_prefix("leftwm")
-            .place_config_file(crate::CONFIG_NAME.to_string() + ".ron")?;
-        let toml_file = BaseDirectories::with_prefix("leftwm")
-            .place_config_file(crate::CONFIG_NAME.to_string() + ".toml")?;
-        if Path::new(&ron_file).exists() {
-            ron_file
-        } else if Path::new(&toml_file).exists() {
-            println!(
-                "\x1b[1;93mWARN: TOML as config format is about to be deprecated.
-      Please consider migrating to RON manually or by using `leftwm-check -m`.\x1b[0m"
-            );
-            toml_file
-        } else {
-            let config = Config::default();
-            write_to_file(&ron_file, &config)?;
-            return Ok(config);
-        }
+        get_config_file()?
     };
     if verbose {
         dbg!(&config_filename);
@@ -51,48 +40,302 @@ pub fn load_from_file(fspath: Option<&str>, verbose: bool) -> Result<Config> {
     if verbose {
         dbg!(&contents);
     }
-    if config_filename.as_path().extension() == Some(std::ffi::OsStr::new("ron")) {
-        let config = ron::from_str(&contents)?;
+    let is_ron = config_filename.as_path().extension() == Some(std::ffi::OsStr::new("ron"));
+    let (config, migrated) = parse(&contents, is_ron)?;
+    if !migrated.applied.is_empty() {
+        eprintln!(
+            "\x1b[1;93mWARN: The config uses schema version {}, it was upgraded while loading.
+      Please consider running `{}`.\x1b[0m",
+            migrated.from_version,
+            upgrade_command(is_ron)
+        );
+    }
+    Ok(config)
+}
+
+/// Upgrades a config source to the current schema and deserializes it.
+pub fn parse(contents: &str, is_ron: bool) -> Result<(Config, migrate::Migrated)> {
+    let migrated = if is_ron {
+        migrate::migrate_ron(contents)?
+    } else {
+        migrate::migrate_toml(contents)?
+    };
+    let config = deserialize(&migrated.source, is_ron)?;
+    Ok((config, migrated))
+}
+
+/// Deserializes a config source which is already at the current schema.
+pub fn deserialize(contents: &str, is_ron: bool) -> Result<Config> {
+    if is_ron {
+        let mut config = ron::from_str(contents)?;
+        unknown::capture_ron(&mut config, contents)?;
         Ok(config)
     } else {
-        let config = toml::from_str(&contents)?;
+        let mut config = toml::from_str(contents)?;
+        unknown::capture_toml(&mut config, contents)?;
         Ok(config)
     }
 }
 
+/// Describes the difference between two versions of a config, one line per changed setting.
+pub fn summarize_changes(before: &str, after: &str, is_ron: bool) -> Result<Vec<String>> {
+    let (added, removed) = line_changes(before, after);
+    let mut summary = vec![format!("{added} lines added, {removed} lines removed")];
+    summary.extend(changed_settings(
+        &parse(before, is_ron)?.0,
+        &parse(after, is_ron)?.0,
+    )?);
+    if summary.len() == 1 {
+        summary.push("no setting leftwm-config knows about changed".to_string());
+    }
+    Ok(summary)
+}
+
+/// The number of lines added and removed between `before` and `after`, the lines which are
+/// not part of their longest common subsequence.
+fn line_changes(before: &str, after: &str) -> (usize, usize) {
+    let before: Vec<&str> = before.lines().collect();
+    let after: Vec<&str> = after.lines().collect();
+    // lines which didn't change at the start and end are common to both
+    let prefix = before
+        .iter()
+        .zip(&after)
+        .take_while(|(b, a)| b == a)
+        .count();
+    let suffix = before[prefix..]
+        .iter()
+        .rev()
+        .zip(after[prefix..].iter().rev())
+        .take_while(|(b, a)| b == a)
+        .count();
+    let before = &before[prefix..before.len() - suffix];
+    let after = &after[prefix..after.len() - suffix];
+    // lengths of the longest common subsequence of `before` and a prefix of `after`,
+    // one row at a time
+    let mut previous = vec![0; after.len() + 1];
+    let mut current = vec![0; after.len() + 1];
+    for b in before {
+        for (j, a) in after.iter().enumerate() {
+            current[j + 1] = if b == a {
+                previous[j] + 1
+            } else {
+                current[j].max(previous[j + 1])
+            };
+        }
+        mem::swap(&mut previous, &mut current);
+    }
+    let common = previous[after.len()];
+    (after.len() - common, before.len() - common)
+}
+
+/// Describes every setting which differs between `old` and `new`, one line per setting.
+pub fn changed_settings(old: &Config, new: &Config) -> Result<Vec<String>> {
+    let (serde_json::Value::Object(old), serde_json::Value::Object(new)) =
+        (serde_json::to_value(old)?, serde_json::to_value(new)?)
+    else {
+        bail!("A config did not serialize to an object")
+    };
+    let short = |value: &serde_json::Value| {
+        let text = value.to_string();
+        if text.chars().count() > 40 {
+            format!("{}...", text.chars().take(37).collect::<String>())
+        } else {
+            text
+        }
+    };
+    let null = serde_json::Value::Null;
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    let mut changes = Vec::new();
+    for key in keys {
+        let (old, new) = (old.get(key).unwrap_or(&null), new.get(key).unwrap_or(&null));
+        if old == new {
+            continue;
+        }
+        changes.push(match (old, new) {
+            (serde_json::Value::Array(old), serde_json::Value::Array(new)) => {
+                let changed = old.iter().zip(new).filter(|(o, n)| o != n).count();
+                format!(
+                    "{key}: {} -> {} entries, {changed} changed in place",
+                    old.len(),
+                    new.len()
+                )
+            }
+            _ => format!("{key}: {} -> {}", short(old), short(new)),
+        });
+    }
+    Ok(changes)
+}
+
+/// The command which brings an outdated config up to the current schema. TOML configs are
+/// converted to RON, which keeps their comments, rewriting them as TOML would drop those.
+pub fn upgrade_command(is_ron: bool) -> &'static str {
+    if is_ron {
+        "leftwm-config migrate --to-latest"
+    } else {
+        "leftwm-config migrate"
+    }
+}
+
+/// Applies all pending schema migrations to `file` in place and reports each step.
+/// TOML files are not rewritten, see [`upgrade_command`].
+pub fn migrate_to_latest(file: &Path) -> Result<()> {
+    let contents = fs::read_to_string(file)?;
+    let is_ron = file.extension() == Some(std::ffi::OsStr::new("ron"));
+    let migrated = if is_ron {
+        migrate::migrate_ron(&contents)?
+    } else {
+        migrate::migrate_toml(&contents)?
+    };
+    if migrated.applied.is_empty() {
+        println!(
+            "\x1b[0;92m    -> Already at schema version {}\x1b[0m",
+            migrate::CURRENT_VERSION
+        );
+        return Ok(());
+    }
+    if !is_ron {
+        bail!(
+            "{} uses schema version {}, rewriting it as TOML would drop its comments. Run `{}` to convert it to RON instead.",
+            file.display(),
+            migrated.from_version,
+            upgrade_command(false)
+        );
+    }
+    for step in &migrated.applied {
+        println!("\x1b[0;94m::\x1b[0m Applied: {step}");
+    }
+    backup::write_atomic(file, &migrated.source)?;
+    println!(
+        "\x1b[0;92m    -> Migrated from schema version {} to {}\x1b[0m",
+        migrated.from_version,
+        migrate::CURRENT_VERSION
+    );
+    Ok(())
+}
+
+/// Makes every mode use `path` instead of the config file in the XDG config directory.
+pub fn set_config_path(path: PathBuf) {
+    let _ = CONFIG_PATH.set(path);
+}
+
+/// The default RON and TOML config files in the XDG config directory.
+pub fn default_config_files() -> Result<(PathBuf, PathBuf)> {
+    let dirs = BaseDirectories::with_prefix("leftwm");
+    Ok((
+        dirs.place_config_file(crate::CONFIG_NAME.to_string() + ".ron")?,
+        dirs.place_config_file(crate::CONFIG_NAME.to_string() + ".toml")?,
+    ))
+}
+
+/// The file the config is read from. Without an explicit path this is the RON or TOML file
+/// in the XDG config directory, a default config is written if neither exists.
+/// With a profile selected this is the profile's file.
 pub fn get_config_file() -> Result<PathBuf> {
-    let ron_file = BaseDirectories::with_prefix("leftwm")
-        .place_config_file(crate::CONFIG_NAME.to_string() + ".ron")?;
-    let toml_file = BaseDirectories::with_prefix("leftwm")
-        .place_config_file(crate::CONFIG_NAME.to_string() + ".toml")?;
+    if let Some(name) = profile::selected() {
+        let path = profile::path(name)?;
+        ensure!(
+            path.exists(),
+            "There is no profile `{name}`, create it with `leftwm-config profile create {name}`"
+        );
+        return Ok(path);
+    }
+    if let Some(path) = CONFIG_PATH.get() {
+        ensure!(
+            path.exists(),
+            "The config file {} does not exist, create it with `leftwm-config --new`",
+            path.display()
+        );
+        return Ok(path.clone());
+    }
+    let (ron_file, toml_file) = default_config_files()?;
     if Path::new(&ron_file).exists() {
         Ok(ron_file)
     } else if Path::new(&toml_file).exists() {
-        println!(
+        eprintln!(
             "\x1b[1;93mWARN: TOML as config format is about to be deprecated.
       Please consider migrating to RON manually or by using `leftwm-config --migrate`.\x1b[0m"
         );
         Ok(toml_file)
     } else {
-        let config = Config::default();
+        let config = Config::for_environment(&Environment::detect());
         write_to_file(&ron_file, &config)?;
         Ok(ron_file)
     }
 }
 
+/// The file RON configs are written to, the selected profile or [`main_ron_file`].
+pub fn ron_config_file() -> Result<PathBuf> {
+    match profile::selected() {
+        Some(name) => profile::path(name),
+        None => main_ron_file(),
+    }
+}
+
+/// The RON file leftwm reads, for a TOML file this is the `.ron` file next to it.
+pub fn main_ron_file() -> Result<PathBuf> {
+    match CONFIG_PATH.get() {
+        Some(path) if path.extension() == Some(std::ffi::OsStr::new("toml")) => {
+            Ok(path.with_extension("ron"))
+        }
+        Some(path) => Ok(path.clone()),
+        None => Ok(default_config_files()?.0),
+    }
+}
+
 pub fn save_to_file(config: &Config) -> Result<()> {
-    write_to_file(
-        &BaseDirectories::with_prefix("leftwm")
-            .place_config_file(crate::CONFIG_NAME.to_string() + ".ron")?,
-        config,
-    )
+    let file = ron_config_file()?;
+    write_to_file(&file, config)?;
+    profile::apply_if_active(&file)
 }
 
+/// Writes `config` to `ron_file`. When the file already holds a RON config only the values
+/// which changed are rewritten, the comments and layout of everything else are kept.
+/// The file is replaced atomically and its previous version is kept as a backup.
 pub fn write_to_file(ron_file: &PathBuf, config: &Config) -> Result<(), anyhow::Error> {
-    let ron_pretty_conf = ron::ser::PrettyConfig::new()
-        .depth_limit(2)
-        .extensions(ron::extensions::Extensions::IMPLICIT_SOME);
+    let text = match fs::read_to_string(ron_file) {
+        Ok(existing) => match update_existing(&existing, config) {
+            Ok(text) => text,
+            Err(e) => {
+                // write_atomic backs the file up before it is replaced
+                eprintln!(
+                    "\x1b[1;93mWARN: Could not update {} in place, it is rewritten without its comments and formatting. The previous version is kept as a backup, see `leftwm-config restore`. Reason: {e:#}\x1b[0m",
+                    ron_file.display()
+                );
+                with_header(config)?
+            }
+        },
+        Err(_) => with_header(config)?,
+    };
+    backup::write_atomic(ron_file, &text)
+}
+
+fn update_existing(existing: &str, config: &Config) -> Result<String> {
+    // diff against the upgraded document, so old key names are not kept as unknown keys
+    let existing = &migrate::migrate_ron(existing)?.source;
+    let implicit_some = document::extensions(existing)
+        .iter()
+        .any(|extension| extension == "implicit_some");
+    let mut old_config = ron::from_str(existing)?;
+    unknown::capture_ron(&mut old_config, existing)?;
+    let old = to_ron(&old_config, implicit_some)?;
+    let new = to_ron(config, implicit_some)?;
+    Ok(document::update(existing, &old, &new)?)
+}
+
+pub fn to_ron(config: &Config, implicit_some: bool) -> Result<String> {
+    let mut ron_pretty_conf = ron::ser::PrettyConfig::new().depth_limit(2);
+    if implicit_some {
+        ron_pretty_conf = ron_pretty_conf.extensions(ron::extensions::Extensions::IMPLICIT_SOME);
+    }
     let ron = ron::ser::to_string_pretty(&config, ron_pretty_conf)?;
+    unknown::restore(config, &ron)
+}
+
+/// Serializes `config` as a new RON file with the leftwm banner on top.
+pub fn with_header(config: &Config) -> Result<String> {
+    let ron = to_ron(config, true)?;
     let comment_header = String::from(
         r"//  _        ___                                      ___ _
 // | |      / __)_                                   / __|_)
@@ -104,15 +347,11 @@ pub fn write_to_file(ron_file: &PathBuf, config: &Config) -> Result<(), anyhow::
 // For info about configuration please visit https://github.com/leftwm/leftwm/wiki
 ",
     );
-    let ron_with_header = comment_header + &ron;
-    let mut file = File::create(ron_file)?;
-    file.write_all(ron_with_header.as_bytes())?;
-    Ok(())
+    Ok(comment_header + &ron)
 }
 
 pub fn generate_new_config() -> Result<()> {
-    let file = BaseDirectories::with_prefix("leftwm")
-        .place_config_file(crate::CONFIG_NAME.to_string() + ".ron")?;
+    let file = ron_config_file()?;
 
     if file.exists() {
         println!(
@@ -122,16 +361,60 @@ pub fn generate_new_config() -> Result<()> {
         let _ = std::io::stdin()
             .read_line(&mut line)
             .expect("Failed to read line");
-        if line.contains('y') || line.contains('Y') {
-            let config = Config::default();
-            let ron_pretty_conf = ron::ser::PrettyConfig::new()
-                .depth_limit(2)
-                .extensions(ron::extensions::Extensions::IMPLICIT_SOME);
-            let text = ron::ser::to_string_pretty(&config, ron_pretty_conf)?;
-            let mut file = File::create(&file)?;
-            file.write_all(text.as_bytes())?;
+        if !(line.contains('y') || line.contains('Y')) {
+            return Ok(());
         }
     }
+    // the questions need someone to answer them, e.g. not a script piping into --new
+    let config = if std::io::stdin().is_terminal() {
+        wizard::run()?
+    } else {
+        Config::for_environment(&Environment::detect())
+    };
+    let ron_pretty_conf = ron::ser::PrettyConfig::new()
+        .depth_limit(2)
+        .extensions(ron::extensions::Extensions::IMPLICIT_SOME);
+    let text = ron::ser::to_string_pretty(&config, ron_pretty_conf)?;
+    backup::write_atomic(&file, &text)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_changes_counts_a_line_diff() {
+        assert_eq!(line_changes("a\nb\nc\n", "a\nb\nc\n"), (0, 0));
+        assert_eq!(line_changes("a\nb\nc\n", "a\nx\nc\n"), (1, 1));
+        assert_eq!(line_changes("", "a\nb\n"), (2, 0));
+        assert_eq!(line_changes("a\nb\n", ""), (0, 2));
+        // moved lines are one removal and one addition
+        assert_eq!(line_changes("a\nb\nc\n", "b\nc\na\n"), (1, 1));
+    }
+
+    #[test]
+    fn line_changes_counts_duplicates() {
+        // a line which is already there elsewhere is still added
+        assert_eq!(line_changes("),\n", "),\n),\n"), (1, 0));
+        assert_eq!(line_changes("x\n),\nx\n", "x\n"), (0, 2));
+        assert_eq!(
+            line_changes("(\n    a: 1,\n),\n(\n    a: 1,\n),\n", "(\n    a: 1,\n),\n"),
+            (0, 3)
+        );
+    }
+
+    #[test]
+    fn summarize_changes_lists_the_changed_settings() {
+        let before = "(\n    modkey: \"Mod4\",\n    version: 2,\n)\n";
+        let after = "(\n    modkey: \"Mod1\",\n    version: 2,\n)\n";
+        assert_eq!(
+            summarize_changes(before, after, true).unwrap(),
+            vec![
+                "1 lines added, 1 lines removed".to_string(),
+                "modkey: \"Mod4\" -> \"Mod1\"".to_string(),
+            ]
+        );
+    }
+}
@@ -1,19 +1,43 @@
 use crate::config::Config;
-use crate::config::command::{BaseCommand, CoreCommand};
-use crate::config::layout::Layout;
+use crate::config::command::{Argument, ArgumentKind, BaseCommand, CoreCommand};
+use crate::config::layout;
 use crate::config::modifier::Modifier;
-use anyhow::ensure;
-use anyhow::{Context, Result};
-use serde::{Deserialize, Serialize};
-use std::str::FromStr;
+use crate::config::unknown::UnknownFields;
+use serde::{Deserialize, Deserializer, Serialize};
+use thiserror::Error;
 
 #[derive(Serialize, Default, Deserialize, Debug, Clone)]
+#[serde(from = "KeybindRepr")]
 pub struct Keybind {
     pub command: BaseCommand,
-    #[serde(default)]
-    pub value: String,
+    /// parsed for `command`, change the command with [`Keybind::set_command`]
+    pub value: Argument,
     pub modifier: Option<Modifier>,
     pub key: String,
+    #[serde(skip)]
+    pub unknown_fields: UnknownFields,
+}
+
+/// A keybind as written, the value can only be parsed once the command is known.
+#[derive(Deserialize)]
+struct KeybindRepr {
+    command: BaseCommand,
+    #[serde(default, deserialize_with = "deserialize_value")]
+    value: String,
+    modifier: Option<Modifier>,
+    key: String,
+}
+
+impl From<KeybindRepr> for Keybind {
+    fn from(repr: KeybindRepr) -> Self {
+        Self {
+            value: Argument::parse(repr.command.argument(), &repr.value),
+            command: repr.command,
+            modifier: repr.modifier,
+            key: repr.key,
+            unknown_fields: UnknownFields::default(),
+        }
+    }
 }
 
 // TODO lift this allow by actually using those items
@@ -24,28 +48,131 @@ pub struct CoreKeybind {
     pub key: String,
 }
 
-macro_rules! ensure_non_empty {
-    ($value:expr) => {{
-        ensure!(!$value.is_empty(), "value must not be empty");
-        $value
-    }};
+/// Why the value of a keybind does not fit its command.
+#[derive(Debug, Error)]
+pub enum ArgumentError {
+    #[error("{command:?} needs {} as its value", .kind.expected())]
+    Missing {
+        command: BaseCommand,
+        kind: ArgumentKind,
+    },
+    #[error("{command:?} needs {} as its value, found `{found}`", .kind.expected())]
+    Invalid {
+        command: BaseCommand,
+        kind: ArgumentKind,
+        found: String,
+    },
+    #[error("{0}")]
+    UnknownLayout(String),
+}
+
+/// Accepts typed values (`1`, `true`, `0.5`) as well as the legacy strings leftwm reads.
+fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ValueRepr {
+        String(String),
+        Int(i64),
+        Float(f64),
+        Bool(bool),
+    }
+
+    Ok(match ValueRepr::deserialize(deserializer)? {
+        ValueRepr::String(s) => s,
+        ValueRepr::Int(i) => i.to_string(),
+        ValueRepr::Float(f) => f.to_string(),
+        ValueRepr::Bool(b) => b.to_string(),
+    })
 }
 
 impl Keybind {
-    pub fn try_convert_to_core_keybind(&self, config: &Config) -> Result<CoreKeybind> {
+    /// Sets the command and parses the value again for the argument it takes.
+    pub fn set_command(&mut self, command: BaseCommand) {
+        self.value = Argument::parse(command.argument(), &self.value.to_string());
+        self.command = command;
+    }
+
+    fn missing(&self) -> ArgumentError {
+        ArgumentError::Missing {
+            command: self.command.clone(),
+            kind: self.command.argument(),
+        }
+    }
+
+    /// Why the value is not the argument the command takes.
+    fn mismatch(&self) -> ArgumentError {
+        if self.value.is_empty() {
+            return self.missing();
+        }
+        ArgumentError::Invalid {
+            command: self.command.clone(),
+            kind: self.command.argument(),
+            found: self.value.to_string(),
+        }
+    }
+
+    fn text(&self) -> Result<String, ArgumentError> {
+        match &self.value {
+            Argument::Text(text) | Argument::Layout(text) => Ok(text.clone()),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn tag(&self) -> Result<usize, ArgumentError> {
+        match self.value {
+            Argument::Tag(tag) => Ok(tag),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn step(&self) -> Result<i8, ArgumentError> {
+        match self.value {
+            Argument::Step(step) => Ok(step),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn float(&self) -> Result<f32, ArgumentError> {
+        match self.value {
+            Argument::Float(float) => Ok(float),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn flag(&self) -> Result<bool, ArgumentError> {
+        match (&self.value, self.command.argument()) {
+            (Argument::Bool(flag), _) => Ok(*flag),
+            (Argument::None, ArgumentKind::Bool { default }) => Ok(default),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    /// Parses `value` for the command of this keybind, the error says why leftwm would not
+    /// accept the keybind with it.
+    pub fn check_value(&self, config: &Config, value: &str) -> Result<Argument, ArgumentError> {
+        let candidate = Self {
+            value: Argument::parse(self.command.argument(), value),
+            ..self.clone()
+        };
+        candidate.try_convert_to_core_keybind(config)?;
+        Ok(candidate.value)
+    }
+
+    pub fn try_convert_to_core_keybind(
+        &self,
+        config: &Config,
+    ) -> Result<CoreKeybind, ArgumentError> {
         let command = match &self.command {
-            BaseCommand::Execute => CoreCommand::Execute(ensure_non_empty!(self.value.clone())),
+            BaseCommand::Execute => CoreCommand::Execute(self.text()?),
             BaseCommand::CloseWindow => CoreCommand::CloseWindow,
             BaseCommand::SwapTags => CoreCommand::SwapScreens,
             BaseCommand::SoftReload => CoreCommand::SoftReload,
             BaseCommand::HardReload => CoreCommand::HardReload,
-            BaseCommand::ToggleScratchPad => {
-                CoreCommand::ToggleScratchPad(ensure_non_empty!(self.value.clone()))
-            }
+            BaseCommand::ToggleScratchPad => CoreCommand::ToggleScratchPad(self.text()?),
             BaseCommand::ToggleFullScreen => CoreCommand::ToggleFullScreen,
             BaseCommand::ToggleSticky => CoreCommand::ToggleSticky,
             BaseCommand::GotoTag => CoreCommand::GoToTag {
-                tag: usize::from_str(&self.value).context("invalid index value for GotoTag")?,
+                tag: self.tag()?,
                 swap: !config.disable_current_tag_swap,
             },
             BaseCommand::ReturnToLastTag => CoreCommand::ReturnToLastTag,
@@ -54,33 +181,18 @@ impl Keybind {
             BaseCommand::ToggleFloating => CoreCommand::ToggleFloating,
             BaseCommand::MoveWindowUp => CoreCommand::MoveWindowUp,
             BaseCommand::MoveWindowDown => CoreCommand::MoveWindowDown,
-            BaseCommand::MoveWindowTop => CoreCommand::MoveWindowTop {
-                swap: if self.value.is_empty() {
-                    true
-                } else {
-                    bool::from_str(&self.value)
-                        .context("invalid boolean value for MoveWindowTop")?
-                },
-            },
+            BaseCommand::MoveWindowTop => CoreCommand::MoveWindowTop { swap: self.flag()? },
             BaseCommand::FocusNextTag => CoreCommand::FocusNextTag,
             BaseCommand::FocusPreviousTag => CoreCommand::FocusPreviousTag,
-            BaseCommand::FocusWindow => CoreCommand::FocusWindow(self.value.clone()),
+            BaseCommand::FocusWindow => CoreCommand::FocusWindow(self.value.to_string()),
             BaseCommand::FocusWindowUp => CoreCommand::FocusWindowUp,
             BaseCommand::FocusWindowDown => CoreCommand::FocusWindowDown,
-            BaseCommand::FocusWindowTop => CoreCommand::FocusWindowTop {
-                swap: if self.value.is_empty() {
-                    false
-                } else {
-                    bool::from_str(&self.value)
-                        .context("invalid boolean value for FocusWindowTop")?
-                },
-            },
+            BaseCommand::FocusWindowTop => CoreCommand::FocusWindowTop { swap: self.flag()? },
             BaseCommand::FocusWorkspaceNext => CoreCommand::FocusWorkspaceNext,
             BaseCommand::FocusWorkspacePrevious => CoreCommand::FocusWorkspacePrevious,
             BaseCommand::MoveToTag => CoreCommand::SendWindowToTag {
                 window: None,
-                tag: usize::from_str(&self.value)
-                    .context("invalid index value for SendWindowToTag")?,
+                tag: self.tag()?,
             },
             BaseCommand::MoveToLastWorkspace => CoreCommand::MoveWindowToLastWorkspace,
             BaseCommand::MoveWindowToNextWorkspace => CoreCommand::MoveWindowToNextWorkspace,
@@ -90,26 +202,25 @@ impl Keybind {
             BaseCommand::MouseMoveWindow => CoreCommand::MouseMoveWindow,
             BaseCommand::NextLayout => CoreCommand::NextLayout,
             BaseCommand::PreviousLayout => CoreCommand::PreviousLayout,
-            BaseCommand::SetLayout => CoreCommand::SetLayout(
-                Layout::from_str(&self.value)
-                    .context("could not parse layout for command SetLayout")?,
-            ),
+            BaseCommand::SetLayout => {
+                let name = self.text()?;
+                if !layout::is_defined(&name, &config.layout_definitions) {
+                    return Err(ArgumentError::UnknownLayout(
+                        layout::unknown_layout_message(
+                            &name,
+                            "the value of command SetLayout",
+                            &config.layout_definitions,
+                        ),
+                    ));
+                }
+                CoreCommand::SetLayout(name)
+            }
             BaseCommand::RotateTag => CoreCommand::RotateTag,
-            BaseCommand::IncreaseMainWidth => CoreCommand::IncreaseMainWidth(
-                i8::from_str(&self.value).context("invalid width value for IncreaseMainWidth")?,
-            ),
-            BaseCommand::DecreaseMainWidth => CoreCommand::DecreaseMainWidth(
-                i8::from_str(&self.value).context("invalid width value for DecreaseMainWidth")?,
-            ),
-            BaseCommand::SetMarginMultiplier => CoreCommand::SetMarginMultiplier(
-                f32::from_str(&self.value)
-                    .context("invalid margin multiplier for SetMarginMultiplier")?,
-            ),
+            BaseCommand::IncreaseMainWidth => CoreCommand::IncreaseMainWidth(self.step()?),
+            BaseCommand::DecreaseMainWidth => CoreCommand::DecreaseMainWidth(self.step()?),
+            BaseCommand::SetMarginMultiplier => CoreCommand::SetMarginMultiplier(self.float()?),
             BaseCommand::UnloadTheme => CoreCommand::Other("UnloadTheme".into()),
-            BaseCommand::LoadTheme => CoreCommand::Other(format!(
-                "LoadTheme {}",
-                ensure_non_empty!(self.value.clone())
-            )),
+            BaseCommand::LoadTheme => CoreCommand::Other(format!("LoadTheme {}", self.text()?)),
             BaseCommand::CloseAllOtherWindows => CoreCommand::CloseAllOtherWindows,
         };
 
@@ -125,3 +236,92 @@ impl Keybind {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keybind(source: &str) -> Keybind {
+        ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(source)
+            .unwrap()
+    }
+
+    #[test]
+    fn legacy_values_are_parsed_for_the_command() {
+        let tag = "(command: GotoTag, value: \"2\", modifier: [\"modkey\"], key: \"2\")";
+        assert_eq!(keybind(tag).value, Argument::Tag(2));
+        let int = "(command: GotoTag, value: 2, modifier: [\"modkey\"], key: \"2\")";
+        assert_eq!(keybind(int).value, Argument::Tag(2));
+        let float = "(command: SetMarginMultiplier, value: 1.5, key: \"m\")";
+        assert_eq!(keybind(float).value, Argument::Float(1.5));
+        let flag = "(command: MoveWindowTop, value: false, key: \"Return\")";
+        assert_eq!(keybind(flag).value, Argument::Bool(false));
+        let missing = "(command: MoveWindowTop, key: \"Return\")";
+        assert_eq!(keybind(missing).value, Argument::None);
+        let invalid = "(command: GotoTag, value: \"one\", key: \"1\")";
+        assert_eq!(keybind(invalid).value, Argument::Invalid("one".to_string()));
+    }
+
+    #[test]
+    fn legacy_toml_values_are_parsed_for_the_command() {
+        let parsed: Keybind =
+            toml::from_str("command = \"IncreaseMainWidth\"\nvalue = 5\nkey = \"l\"\n").unwrap();
+        assert_eq!(parsed.value, Argument::Step(5));
+    }
+
+    #[test]
+    fn values_are_written_back_as_strings() {
+        let written = ron::to_string(&keybind(
+            "(command: GotoTag, value: 2, modifier: [\"modkey\"], key: \"2\")",
+        ))
+        .unwrap();
+        assert!(written.contains("value:\"2\""), "{written}");
+        assert_eq!(keybind(&written).value, Argument::Tag(2));
+    }
+
+    #[test]
+    fn set_command_parses_the_value_again() {
+        let mut bind = keybind("(command: Execute, value: \"3\", key: \"3\")");
+        assert_eq!(bind.value, Argument::Text("3".to_string()));
+        bind.set_command(BaseCommand::MoveToTag);
+        assert_eq!(bind.value, Argument::Tag(3));
+    }
+
+    #[test]
+    fn argument_errors_name_the_expected_value() {
+        let config = Config::default();
+        let error = |source: &str| {
+            keybind(source)
+                .try_convert_to_core_keybind(&config)
+                .err()
+                .map(|e| e.to_string())
+        };
+        assert_eq!(
+            error("(command: GotoTag, value: \"0\", key: \"1\")").as_deref(),
+            Some("GotoTag needs a tag number starting at 1 as its value, found `0`")
+        );
+        assert_eq!(
+            error("(command: Execute, key: \"1\")").as_deref(),
+            Some("Execute needs a string as its value")
+        );
+        assert_eq!(error("(command: MoveWindowTop, key: \"1\")"), None);
+    }
+
+    #[test]
+    fn check_value_parses_or_explains() {
+        let config = Config::default();
+        let bind = keybind("(command: GotoTag, value: \"1\", key: \"1\")");
+        assert_eq!(bind.check_value(&config, "4").unwrap(), Argument::Tag(4));
+        assert_eq!(
+            bind.check_value(&config, "four").unwrap_err().to_string(),
+            "GotoTag needs a tag number starting at 1 as its value, found `four`"
+        );
+        let layout = keybind("(command: SetLayout, value: \"Monocle\", key: \"m\")");
+        assert!(matches!(
+            layout.check_value(&config, "NoSuchLayout"),
+            Err(ArgumentError::UnknownLayout(_))
+        ));
+    }
+}
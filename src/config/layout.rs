@@ -2,6 +2,9 @@ use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use thiserror::Error;
 
+use crate::config::unknown::UnknownFields;
+use crate::config::values::Size;
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
 pub enum Layout {
     #[default]
@@ -21,8 +24,6 @@ pub enum Layout {
     LeftWiderRightStack,
 }
 
-// TODO fix this by matching the layout handling to leftwm
-#[allow(dead_code)]
 pub const LAYOUTS: &[Layout] = &[
     Layout::MainAndVertStack,
     Layout::MainAndHorizontalStack,
@@ -67,3 +68,393 @@ impl FromStr for Layout {
         }
     }
 }
+
+/// The name of a built-in layout or of a `layout_definitions` entry, as used by
+/// `Config.layouts` and `Workspace.layouts`.
+///
+/// RON accepts both `"Monocle"` and the bare identifier `Monocle`, which is how workspace
+/// layouts used to be written. It is always written back as a string.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(from = "LayoutNameRepr", into = "String")]
+pub struct LayoutName(pub String);
+
+// serde buffers untagged enums as `Content`, for which RON hands out bare
+// identifiers as strings.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LayoutNameRepr {
+    Name(String),
+}
+
+impl From<LayoutNameRepr> for LayoutName {
+    fn from(repr: LayoutNameRepr) -> Self {
+        let LayoutNameRepr::Name(name) = repr;
+        Self(name)
+    }
+}
+
+impl From<LayoutName> for String {
+    fn from(name: LayoutName) -> Self {
+        name.0
+    }
+}
+
+impl From<Layout> for LayoutName {
+    fn from(layout: Layout) -> Self {
+        Self(format!("{layout:?}"))
+    }
+}
+
+impl std::fmt::Display for LayoutName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Whether `name` is a built-in layout or the name of one of `definitions`.
+pub fn is_defined(name: &str, definitions: &[LayoutDefinition]) -> bool {
+    Layout::from_str(name).is_ok() || definitions.iter().any(|d| d.name == name)
+}
+
+/// Names the list an unknown layout was found in and suggests a close layout name.
+pub fn unknown_layout_message(
+    name: &str,
+    location: &str,
+    definitions: &[LayoutDefinition],
+) -> String {
+    let mut message = format!(
+        "Layout `{name}` in {location} is neither built-in nor defined in `layout_definitions`"
+    );
+    if let Some(suggestion) = suggestion(name, definitions) {
+        message.push_str(&format!(", did you mean `{suggestion}`?"));
+    }
+    message
+}
+
+/// The known layout name closest to a misspelled `name`, if any is close enough.
+pub fn suggestion(name: &str, definitions: &[LayoutDefinition]) -> Option<String> {
+    LAYOUTS
+        .iter()
+        .map(|l| format!("{l:?}"))
+        .chain(definitions.iter().map(|d| d.name.clone()))
+        .map(|candidate| {
+            (
+                edit_distance(&name.to_lowercase(), &candidate.to_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|(distance, _)| *distance <= 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+/// A user defined layout from `layout_definitions`, built from a main column, a stack
+/// and an optional second stack which can each be flipped, rotated and split.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct LayoutDefinition {
+    pub name: String,
+    pub flip: Flip,
+    pub rotate: Rotation,
+    pub reserve: Reserve,
+    pub columns: Columns,
+    #[serde(skip)]
+    pub unknown_fields: UnknownFields,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Columns {
+    pub flip: Flip,
+    pub rotate: Rotation,
+    pub main: Option<Main>,
+    pub stack: Stack,
+    pub second_stack: Option<SecondStack>,
+}
+
+impl Default for Columns {
+    fn default() -> Self {
+        Self {
+            flip: Flip::default(),
+            rotate: Rotation::default(),
+            main: Some(Main::default()),
+            stack: Stack::default(),
+            second_stack: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Main {
+    /// number of windows in the main column
+    pub count: usize,
+    pub size: Size,
+    pub flip: Flip,
+    pub rotate: Rotation,
+    pub split: Option<SplitAxis>,
+}
+
+impl Default for Main {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            size: Size::Ratio(0.5),
+            flip: Flip::default(),
+            rotate: Rotation::default(),
+            split: Some(SplitAxis::Vertical),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Stack {
+    pub flip: Flip,
+    pub rotate: Rotation,
+    pub split: Option<SplitAxis>,
+}
+
+impl Default for Stack {
+    fn default() -> Self {
+        Self {
+            flip: Flip::default(),
+            rotate: Rotation::default(),
+            split: Some(SplitAxis::Horizontal),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SecondStack {
+    pub flip: Flip,
+    pub rotate: Rotation,
+    pub split: SplitAxis,
+}
+
+impl Default for SecondStack {
+    fn default() -> Self {
+        Self {
+            flip: Flip::default(),
+            rotate: Rotation::default(),
+            split: SplitAxis::Horizontal,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Flip {
+    #[default]
+    None,
+    Horizontal,
+    Vertical,
+    Both,
+}
+
+impl Flip {
+    pub const ALL: [Self; 4] = [Self::None, Self::Horizontal, Self::Vertical, Self::Both];
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    #[default]
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Rotation {
+    pub const ALL: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
+}
+
+/// Whether the space of empty columns is kept free.
+// the variant names are the ones leftwm reads
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Reserve {
+    #[default]
+    None,
+    Reserve,
+    ReserveAndCenter,
+}
+
+impl Reserve {
+    pub const ALL: [Self; 3] = [Self::None, Self::Reserve, Self::ReserveAndCenter];
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SplitAxis {
+    Horizontal,
+    Vertical,
+    Grid,
+    Fibonacci,
+    Dwindle,
+}
+
+impl SplitAxis {
+    pub const ALL: [Self; 5] = [
+        Self::Horizontal,
+        Self::Vertical,
+        Self::Grid,
+        Self::Fibonacci,
+        Self::Dwindle,
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::filehandler;
+
+    const DEFINITIONS: &str = r#"#![enable(implicit_some)]
+(layout_definitions: [
+    (
+        name: "Wide",
+        flip: Horizontal,
+        rotate: East,
+        reserve: ReserveAndCenter,
+        columns: (
+            flip: Vertical,
+            rotate: South,
+            main: (count: 2, size: 0.6, flip: Both, rotate: West, split: Grid),
+            stack: (split: Dwindle),
+            second_stack: (split: Fibonacci),
+        ),
+    ),
+    (name: "Stackless", columns: (main: (size: 900))),
+])"#;
+
+    #[test]
+    fn layout_definitions_round_trip() {
+        let config = filehandler::deserialize(DEFINITIONS, true).unwrap();
+        let [wide, stackless] = config.layout_definitions.as_slice() else {
+            panic!("expected two definitions");
+        };
+        assert_eq!(wide.flip, Flip::Horizontal);
+        assert_eq!(wide.rotate, Rotation::East);
+        assert_eq!(wide.reserve, Reserve::ReserveAndCenter);
+        assert_eq!(wide.columns.rotate, Rotation::South);
+        let main = wide.columns.main.as_ref().unwrap();
+        assert_eq!(main.count, 2);
+        assert_eq!(main.size, Size::Ratio(0.6));
+        assert_eq!(main.split, Some(SplitAxis::Grid));
+        assert_eq!(wide.columns.stack.split, Some(SplitAxis::Dwindle));
+        assert_eq!(
+            wide.columns.second_stack.as_ref().map(|s| s.split),
+            Some(SplitAxis::Fibonacci)
+        );
+        // left out settings take their defaults
+        let main = stackless.columns.main.as_ref().unwrap();
+        assert_eq!(main.count, 1);
+        assert_eq!(main.size, Size::Pixel(900));
+        assert_eq!(stackless.columns.stack, Stack::default());
+        assert_eq!(stackless.columns.second_stack, None);
+
+        let written = filehandler::to_ron(&config, true).unwrap();
+        let read = filehandler::deserialize(&written, true).unwrap();
+        assert_eq!(read.layout_definitions, config.layout_definitions);
+    }
+
+    #[test]
+    fn layout_definitions_read_from_toml() {
+        let toml = r#"
+[[layout_definitions]]
+name = "Wide"
+flip = "Horizontal"
+
+[layout_definitions.columns.main]
+count = 2
+size = 0.6
+"#;
+        let config = filehandler::deserialize(toml, false).unwrap();
+        let wide = &config.layout_definitions[0];
+        assert_eq!(wide.flip, Flip::Horizontal);
+        assert_eq!(wide.columns.main.as_ref().unwrap().size, Size::Ratio(0.6));
+    }
+
+    #[test]
+    fn custom_layouts_are_defined() {
+        let config = filehandler::deserialize(DEFINITIONS, true).unwrap();
+        assert!(is_defined("Monocle", &config.layout_definitions));
+        assert!(is_defined("Wide", &config.layout_definitions));
+        assert!(!is_defined("Narrow", &config.layout_definitions));
+        assert!(!is_defined("monocle", &[]));
+    }
+
+    #[test]
+    fn layout_names_read_strings_and_identifiers() {
+        let ron = "#![enable(implicit_some)]\n(layouts: [\"Monocle\", Fibonacci], workspaces: [(x: 0, y: 0, width: 1920, height: 1080, layouts: [CenterMain, \"Wide\"])])";
+        let config = filehandler::deserialize(ron, true).unwrap();
+        assert_eq!(
+            config.layouts,
+            vec![
+                LayoutName("Monocle".to_string()),
+                LayoutName("Fibonacci".to_string())
+            ]
+        );
+        let workspace_layouts = config.workspaces.as_ref().unwrap()[0].layouts.clone();
+        assert_eq!(
+            workspace_layouts,
+            Some(vec![
+                LayoutName::from(Layout::CenterMain),
+                LayoutName("Wide".to_string())
+            ])
+        );
+        // a typo is read like any other name, `check` reports it
+        let toml = "layouts = [\"Monocle\", \"Fibonaci\"]\n";
+        let config = filehandler::deserialize(toml, false).unwrap();
+        assert_eq!(config.layouts[1], LayoutName("Fibonaci".to_string()));
+    }
+
+    #[test]
+    fn layout_names_are_written_as_strings() {
+        let names = vec![
+            LayoutName::from(Layout::Monocle),
+            LayoutName("Wide".to_string()),
+        ];
+        assert_eq!(ron::to_string(&names).unwrap(), "[\"Monocle\",\"Wide\"]");
+    }
+
+    #[test]
+    fn suggestion_finds_close_names() {
+        let definitions = [LayoutDefinition {
+            name: "Wide".to_string(),
+            ..LayoutDefinition::default()
+        }];
+        assert_eq!(
+            suggestion("Fibonaci", &definitions).as_deref(),
+            Some("Fibonacci")
+        );
+        assert_eq!(
+            suggestion("monocle", &definitions).as_deref(),
+            Some("Monocle")
+        );
+        assert_eq!(suggestion("Wid", &definitions).as_deref(), Some("Wide"));
+        assert_eq!(suggestion("Something", &definitions), None);
+        assert_eq!(
+            unknown_layout_message("Fibonaci", "`layouts[1]`", &definitions),
+            "Layout `Fibonaci` in `layouts[1]` is neither built-in nor defined in `layout_definitions`, did you mean `Fibonacci`?"
+        );
+    }
+}
@@ -0,0 +1,503 @@
+//! Maps diagnostics back to the line and column of the setting they are about.
+//!
+//! Config paths such as `keybind[12].key` are resolved against the RON or TOML source, a
+//! path which is only partly written (e.g. a default value) points at the closest parent.
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::config::diagnostic::Diagnostic;
+use crate::config::overlay::Source;
+use crate::config::{document, migrate};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Location {
+    pub file: PathBuf,
+    /// 1-based
+    pub line: usize,
+    /// 1-based, in characters
+    pub column: usize,
+    /// the line the span starts on
+    #[serde(skip)]
+    pub line_text: String,
+    /// how many characters of `line_text` the span covers, at least 1
+    pub length: usize,
+}
+
+impl Location {
+    /// The location of the byte range `span` of `source`, which was read from `file`.
+    #[must_use]
+    pub fn new(file: &Path, source: &str, span: Range<usize>) -> Self {
+        let start = span.start.min(source.len());
+        let (line, column) = document::line_col(source, start);
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        let end = span.end.clamp(start, line_end);
+        Self {
+            file: file.to_path_buf(),
+            line,
+            column,
+            line_text: source[line_start..line_end].replace('\t', " "),
+            length: source[start..end].chars().count().max(1),
+        }
+    }
+
+    /// Prints the line with the span underlined, `colour` is used for the underline.
+    pub fn print_frame(&self, colour: &str) {
+        let width = self.line.to_string().len();
+        println!(
+            "{:width$}\x1b[0;94m-->\x1b[0m {}:{}:{}",
+            "",
+            self.file.display(),
+            self.line,
+            self.column
+        );
+        println!("{:width$} \x1b[0;94m|\x1b[0m", "");
+        println!("\x1b[0;94m{} |\x1b[0m {}", self.line, self.line_text);
+        println!(
+            "{:width$} \x1b[0;94m|\x1b[0m {}{colour}{}\x1b[0m",
+            "",
+            " ".repeat(self.column - 1),
+            "^".repeat(self.length)
+        );
+    }
+}
+
+/// Sets the location of every diagnostic with a config path. Settings which were taken from
+/// a `config.d` fragment, as listed in `overlays`, are looked up in that fragment.
+pub fn locate(diagnostics: &mut [Diagnostic], file: &Path, overlays: &[Source]) {
+    let mut sources: HashMap<PathBuf, Option<String>> = HashMap::new();
+    for diagnostic in diagnostics.iter_mut().filter(|d| !d.path.is_empty()) {
+        let (file, path) = match overlay_for(&diagnostic.path, overlays) {
+            Some((fragment, path)) => (fragment, path),
+            None => (file, diagnostic.path.clone()),
+        };
+        let source = sources
+            .entry(file.to_path_buf())
+            .or_insert_with(|| fs::read_to_string(file).ok());
+        let Some(source) = source else {
+            continue;
+        };
+        let span = find(source, &path, is_toml(file));
+        diagnostic.location = span.map(|span| Location::new(file, source, span));
+    }
+}
+
+/// The location of the error which stopped a config from loading and its message without
+/// the position, if the error has a location. Positions in `parsed`, the source after
+/// migration, are mapped back to `original`, the source as it is in `file`.
+#[must_use]
+pub fn of_error(
+    error: &anyhow::Error,
+    file: &Path,
+    parsed: &str,
+    original: &str,
+) -> Option<(Location, String)> {
+    let (span, message) = error.chain().find_map(|cause| {
+        if let Some(e) = cause.downcast_ref::<document::ParseError>() {
+            Some((e.offset..e.offset + 1, e.message.clone()))
+        } else if let Some(e) = cause.downcast_ref::<ron::error::SpannedError>() {
+            let start = offset(parsed, e.span.start.line, e.span.start.col);
+            let end = offset(parsed, e.span.end.line, e.span.end.col);
+            Some((start..end.max(start + 1), e.code.to_string()))
+        } else if let Some(e) = cause.downcast_ref::<toml::de::Error>() {
+            e.span().map(|span| (span, e.message().to_string()))
+        } else {
+            None
+        }
+    })?;
+    let span = if parsed == original {
+        span
+    } else {
+        // a migration moved things around, the setting is looked up by its path instead
+        let is_toml = is_toml(file);
+        let path = if is_toml {
+            path_at_toml(parsed, span.start)
+        } else {
+            path_at_ron(parsed, span.start)
+        }?;
+        find(original, &path, is_toml)?
+    };
+    Some((Location::new(file, original, span), message))
+}
+
+fn is_toml(file: &Path) -> bool {
+    file.extension().is_some_and(|e| e == "toml")
+}
+
+fn find(source: &str, path: &str, is_toml: bool) -> Option<Range<usize>> {
+    if is_toml {
+        find_toml(source, path)
+    } else {
+        find_ron(source, path)
+    }
+}
+
+/// The fragment a setting was taken from and the path to look up in it. Keybinds and window
+/// rules are numbered differently in the fragment, their path uses the index of the entry
+/// there.
+fn overlay_for<'a>(path: &str, overlays: &'a [Source]) -> Option<(&'a Path, String)> {
+    let top = path.split('.').next()?;
+    let name = top.split('[').next()?;
+    let source = overlays.iter().rev().find(|s| {
+        let setting = s.setting.split(' ').next();
+        setting == Some(top) || setting == Some(name)
+    })?;
+    let path = match source.entry {
+        Some(entry) => format!("{name}[{entry}]{}", &path[top.len()..]),
+        None => path.to_string(),
+    };
+    Some((&source.fragment, path))
+}
+
+enum Step<'p> {
+    Field(&'p str),
+    Index(usize),
+}
+
+/// Splits `keybind[12].key` into `keybind`, `12` and `key`.
+fn steps(path: &str) -> Vec<Step<'_>> {
+    let mut steps = Vec::new();
+    for segment in path.split('.') {
+        let mut parts = segment.split('[');
+        if let Some(name) = parts.next().filter(|n| !n.is_empty()) {
+            steps.push(Step::Field(name));
+        }
+        for index in parts {
+            if let Ok(index) = index.trim_end_matches(']').parse() {
+                steps.push(Step::Index(index));
+            }
+        }
+    }
+    steps
+}
+
+fn find_ron(source: &str, path: &str) -> Option<Range<usize>> {
+    let root = document::parse(source).ok()?;
+    let mut node = &root;
+    let mut span = None;
+    for step in steps(path) {
+        let next = match step {
+            Step::Field(name) => {
+                let node = node.unwrap_some();
+                node.field(name)
+                    .or_else(|| migrate::former_names(name).find_map(|old| node.field(old)))
+                    .map(|f| &f.value)
+            }
+            Step::Index(index) => node.unwrap_some().items().get(index),
+        };
+        let Some(next) = next else {
+            break;
+        };
+        node = next;
+        span = Some(node.span.clone());
+    }
+    span
+}
+
+fn find_toml(source: &str, path: &str) -> Option<Range<usize>> {
+    let root = toml::de::DeTable::parse(source).ok()?;
+    let mut value: Option<&toml::Spanned<toml::de::DeValue>> = None;
+    let mut span = None;
+    for step in steps(path) {
+        let next = match (step, value.map(toml::Spanned::get_ref)) {
+            (Step::Field(name), None) => field(root.get_ref(), name),
+            (Step::Field(name), Some(toml::de::DeValue::Table(table))) => field(table, name),
+            (Step::Index(index), Some(toml::de::DeValue::Array(array))) => array.get(index),
+            _ => None,
+        };
+        let Some(next) = next else {
+            break;
+        };
+        span = Some(next.span());
+        value = Some(next);
+    }
+    span
+}
+
+fn field<'t, 'i>(
+    table: &'t toml::de::DeTable<'i>,
+    name: &str,
+) -> Option<&'t toml::Spanned<toml::de::DeValue<'i>>> {
+    let find = |name: &str| {
+        table
+            .iter()
+            .find(|(key, _)| key.get_ref().as_ref() == name)
+            .map(|(_, value)| value)
+    };
+    find(name).or_else(|| migrate::former_names(name).find_map(find))
+}
+
+/// The path of the innermost setting of a RON source which contains `offset`.
+fn path_at_ron(source: &str, offset: usize) -> Option<String> {
+    let root = document::parse(source).ok()?;
+    let mut node = &root;
+    let mut path = String::new();
+    loop {
+        let inner = node.unwrap_some();
+        if let Some(field) = inner
+            .fields()
+            .iter()
+            .find(|f| f.name_span.start <= offset && offset < f.value.span.end)
+        {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(&field.name);
+            node = &field.value;
+        } else if let Some((index, item)) = inner
+            .items()
+            .iter()
+            .enumerate()
+            .find(|(_, item)| item.span.contains(&offset))
+        {
+            path.push_str(&format!("[{index}]"));
+            node = item;
+        } else {
+            break;
+        }
+    }
+    (!path.is_empty()).then_some(path)
+}
+
+/// The path of the innermost setting of a TOML source which contains `offset`.
+fn path_at_toml(source: &str, offset: usize) -> Option<String> {
+    let root = toml::de::DeTable::parse(source).ok()?;
+    let mut value: Option<&toml::Spanned<toml::de::DeValue>> = None;
+    let mut path = String::new();
+    loop {
+        let table = match value.map(toml::Spanned::get_ref) {
+            None => root.get_ref(),
+            Some(toml::de::DeValue::Table(table)) => table,
+            Some(toml::de::DeValue::Array(array)) => {
+                let Some((index, item)) = array
+                    .iter()
+                    .enumerate()
+                    .find(|(_, item)| item.span().start <= offset && offset < toml_end(item))
+                else {
+                    break;
+                };
+                path.push_str(&format!("[{index}]"));
+                value = Some(item);
+                continue;
+            }
+            _ => break,
+        };
+        let Some((key, next)) = table
+            .iter()
+            .find(|(key, next)| key.span().start <= offset && offset < toml_end(next))
+        else {
+            break;
+        };
+        if !path.is_empty() {
+            path.push('.');
+        }
+        path.push_str(key.get_ref());
+        value = Some(next);
+    }
+    (!path.is_empty()).then_some(path)
+}
+
+/// The byte offset of a 1-based line and column.
+fn offset(source: &str, line: usize, column: usize) -> usize {
+    let line_start: usize = source
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    source[line_start..]
+        .char_indices()
+        .nth(column.saturating_sub(1))
+        .map_or(source.len(), |(i, _)| line_start + i)
+}
+
+/// Where a TOML value ends. The span of a `[[list]]` entry only covers its header, so the
+/// end of its last setting is used for those.
+fn toml_end(value: &toml::Spanned<toml::de::DeValue>) -> usize {
+    let children = match value.get_ref() {
+        toml::de::DeValue::Table(table) => table.values().map(toml_end).max(),
+        toml::de::DeValue::Array(array) => array.iter().map(toml_end).max(),
+        _ => None,
+    };
+    children.map_or(value.span().end, |end| end.max(value.span().end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlay_for_points_at_the_entry_in_the_fragment() {
+        let overlays = [
+            Source {
+                setting: "keybind[12] modkey+Return".to_string(),
+                fragment: PathBuf::from("config.d/10-host.ron"),
+                entry: Some(1),
+            },
+            Source {
+                setting: "workspaces".to_string(),
+                fragment: PathBuf::from("config.d/20-monitors.ron"),
+                entry: None,
+            },
+        ];
+        assert_eq!(
+            overlay_for("keybind[12].key", &overlays),
+            Some((
+                Path::new("config.d/10-host.ron"),
+                "keybind[1].key".to_string()
+            ))
+        );
+        assert_eq!(overlay_for("keybind[3].key", &overlays), None);
+        assert_eq!(
+            overlay_for("workspaces[2].x", &overlays),
+            Some((
+                Path::new("config.d/20-monitors.ron"),
+                "workspaces[2].x".to_string()
+            ))
+        );
+        assert_eq!(overlay_for("modkey", &overlays), None);
+    }
+
+    const RON: &str = r#"(
+    modkey: "Mod4",
+    keybind: [
+        (command: Execute, value: "st", modifier: ["modkey"], key: "Return"),
+        (command: CloseWindow, modifier: ["modkey", "Shift"], key: "q"),
+    ],
+)
+"#;
+
+    const TOML: &str = r#"modkey = "Mod4"
+
+[[keybind]]
+command = "Execute"
+value = "st"
+key = "Return"
+
+[[keybind]]
+command = "CloseWindow"
+key = "q"
+"#;
+
+    /// Writes `files` to a directory of their own and locates a diagnostic for each path.
+    fn located(
+        name: &str,
+        files: &[(&str, &str)],
+        overlays: &[Source],
+        paths: &[&str],
+    ) -> Vec<Option<(String, usize, usize, usize)>> {
+        let dir = std::env::temp_dir().join(format!(
+            "leftwm-config-location-{}-{name}",
+            std::process::id()
+        ));
+        for (file, source) in files {
+            let file = dir.join(file);
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(file, source).unwrap();
+        }
+        let mut diagnostics: Vec<Diagnostic> = paths
+            .iter()
+            .map(|path| Diagnostic::error("LW000", *path, "message"))
+            .collect();
+        let overlays: Vec<Source> = overlays
+            .iter()
+            .map(|s| Source {
+                setting: s.setting.clone(),
+                fragment: dir.join(&s.fragment),
+                entry: s.entry,
+            })
+            .collect();
+        locate(&mut diagnostics, &dir.join(files[0].0), &overlays);
+        fs::remove_dir_all(&dir).unwrap();
+        diagnostics
+            .into_iter()
+            .map(|d| {
+                d.location.map(|l| {
+                    let file = l.file.strip_prefix(&dir).unwrap().display().to_string();
+                    (file, l.line, l.column, l.length)
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn locate_finds_settings_in_ron() {
+        assert_eq!(
+            located(
+                "ron",
+                &[("config.ron", RON)],
+                &[],
+                &["modkey", "keybind[1].key", "keybind[1].modifier[1]", ""]
+            ),
+            vec![
+                Some(("config.ron".to_string(), 2, 13, 6)),
+                Some(("config.ron".to_string(), 5, 68, 3)),
+                Some(("config.ron".to_string(), 5, 53, 7)),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn locate_finds_settings_in_toml() {
+        assert_eq!(
+            located(
+                "toml",
+                &[("config.toml", TOML)],
+                &[],
+                &["modkey", "keybind[1].key"]
+            ),
+            vec![
+                Some(("config.toml".to_string(), 1, 10, 6)),
+                Some(("config.toml".to_string(), 10, 7, 3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn locate_falls_back_to_the_closest_written_parent() {
+        // keybind[1] has no value written, so the entry itself is pointed at
+        let found = located("parent", &[("config.ron", RON)], &[], &["keybind[1].value"]);
+        let (_, line, column, length) = found[0].clone().unwrap();
+        assert_eq!((line, column), (5, 9));
+        assert_eq!(length, RON.lines().nth(4).unwrap().trim().len() - 1);
+    }
+
+    #[test]
+    fn locate_looks_up_overlaid_settings_in_their_fragment() {
+        let fragment = "(\n    keybind: [\n        (command: Execute, value: \"rofi\", key: \"p\"),\n    ],\n)\n";
+        let overlays = [Source {
+            setting: "keybind[2] modkey+p".to_string(),
+            fragment: PathBuf::from("config.d/10-keys.ron"),
+            entry: Some(0),
+        }];
+        assert_eq!(
+            located(
+                "overlay",
+                &[("config.ron", RON), ("config.d/10-keys.ron", fragment)],
+                &overlays,
+                &["keybind[2].value", "keybind[0].key"]
+            ),
+            vec![
+                Some(("config.d/10-keys.ron".to_string(), 3, 35, 6)),
+                Some(("config.ron".to_string(), 4, 68, 8)),
+            ]
+        );
+    }
+
+    #[test]
+    fn columns_count_characters() {
+        let source = "(\n\ttags: [\"ü\", \"ß\"],\n)\n";
+        let location = Location::new(Path::new("config.ron"), source, 16..20);
+        assert_eq!(
+            (location.line, location.column, location.length),
+            (2, 14, 3)
+        );
+        assert_eq!(location.line_text, " tags: [\"ü\", \"ß\"],");
+    }
+}
@@ -0,0 +1,338 @@
+//! Upgrades config files written for older versions of the schema.
+//!
+//! The schema version is stored in the `version` key, files without it are version 0.
+//! Every step moves a config up one version and works on both RON and TOML sources.
+//! RON is edited in place so comments and formatting survive.
+use anyhow::{Context, Result, bail};
+
+use crate::config::document::{self, Edit, Node};
+
+/// The version written by this build of leftwm-config.
+pub const CURRENT_VERSION: u32 = 2;
+
+struct Migration {
+    /// version of the config after this step
+    version: u32,
+    description: &'static str,
+    ron: fn(&Node, &str) -> Result<Vec<Edit>>,
+    toml: fn(&mut toml::Table),
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "rename `window_config_by_class` to `window_rules` and `wm_class` to `window_class`",
+        ron: rename_window_rules_ron,
+        toml: rename_window_rules_toml,
+    },
+    Migration {
+        version: 2,
+        description: "split modifiers written as `\"modkey+Shift\"` into lists",
+        ron: split_modifiers_ron,
+        toml: split_modifiers_toml,
+    },
+];
+
+/// Top level settings which were renamed, as (old, new).
+const RENAMED_FIELDS: &[(&str, &str)] = &[("window_config_by_class", "window_rules")];
+
+/// Settings of window rules which were renamed, as (old, new).
+const RENAMED_RULE_FIELDS: &[(&str, &str)] = &[("wm_class", "window_class")];
+
+/// The outcome of migrating a config source.
+pub struct Migrated {
+    pub source: String,
+    pub from_version: u32,
+    /// descriptions of the steps which changed the source, in order
+    pub applied: Vec<String>,
+}
+
+/// Brings a RON config source up to [`CURRENT_VERSION`].
+pub fn migrate_ron(source: &str) -> Result<Migrated> {
+    let mut source = source.to_string();
+    let root = document::parse(&source)?;
+    let from_version = match root.field("version") {
+        Some(field) => field
+            .value
+            .text(&source)
+            .parse()
+            .context("`version` must be a number")?,
+        None => 0,
+    };
+    check_known(from_version)?;
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.version > from_version) {
+        let root = document::parse(&source)?;
+        let edits = (migration.ron)(&root, &source)?;
+        if !edits.is_empty() {
+            source = document::apply_edits(&source, edits);
+            applied.push(migration.description.to_string());
+        }
+    }
+    if from_version < CURRENT_VERSION {
+        let root = document::parse(&source)?;
+        let version = CURRENT_VERSION.to_string();
+        let edit = match root.field("version") {
+            Some(field) => Some((field.value.span.clone(), version)),
+            None => document::field_insertion(&root, &source, &[format!("version: {version}")]),
+        };
+        source = document::apply_edits(&source, edit.into_iter().collect());
+    }
+    Ok(Migrated {
+        source,
+        from_version,
+        applied,
+    })
+}
+
+/// Brings a TOML config source up to [`CURRENT_VERSION`]. Comments are not kept, so this
+/// is only used to read the config, TOML files are never rewritten with the result.
+pub fn migrate_toml(source: &str) -> Result<Migrated> {
+    let mut table: toml::Table = toml::from_str(source)?;
+    let from_version = match table.get("version") {
+        Some(toml::Value::Integer(version)) => {
+            u32::try_from(*version).context("`version` must not be negative")?
+        }
+        Some(_) => bail!("`version` must be a number"),
+        None => 0,
+    };
+    check_known(from_version)?;
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.version > from_version) {
+        let before = table.clone();
+        (migration.toml)(&mut table);
+        if table != before {
+            applied.push(migration.description.to_string());
+        }
+    }
+    // rewriting drops the comments, a source which needed no changes is kept as it is
+    if applied.is_empty() {
+        return Ok(Migrated {
+            source: source.to_string(),
+            from_version,
+            applied,
+        });
+    }
+    table.insert(
+        "version".to_string(),
+        toml::Value::Integer(CURRENT_VERSION.into()),
+    );
+    Ok(Migrated {
+        source: toml::to_string_pretty(&table)?,
+        from_version,
+        applied,
+    })
+}
+
+fn check_known(version: u32) -> Result<()> {
+    if version > CURRENT_VERSION {
+        bail!(
+            "The config has schema version {version}, this leftwm-config only knows versions up to {CURRENT_VERSION}"
+        );
+    }
+    Ok(())
+}
+
+fn rename_window_rules_ron(root: &Node, _source: &str) -> Result<Vec<Edit>> {
+    let mut edits = Vec::new();
+    if let Some(field) = root.field("window_config_by_class") {
+        if root.field("window_rules").is_some() {
+            bail!("Both `window_config_by_class` and `window_rules` are set");
+        }
+        edits.push((field.name_span.clone(), "window_rules".to_string()));
+    }
+    let rules = root
+        .field("window_config_by_class")
+        .or_else(|| root.field("window_rules"));
+    for rule in rules
+        .map(|f| f.value.unwrap_some().items())
+        .unwrap_or_default()
+    {
+        for (old, new) in RENAMED_RULE_FIELDS {
+            if let Some(field) = rule.field(old) {
+                edits.push((field.name_span.clone(), (*new).to_string()));
+            }
+        }
+    }
+    Ok(edits)
+}
+
+fn rename_window_rules_toml(table: &mut toml::Table) {
+    if let Some(rules) = table.remove("window_config_by_class") {
+        table.entry("window_rules").or_insert(rules);
+    }
+    if let Some(toml::Value::Array(rules)) = table.get_mut("window_rules") {
+        for rule in rules.iter_mut().filter_map(toml::Value::as_table_mut) {
+            for (old, new) in RENAMED_RULE_FIELDS {
+                if let Some(value) = rule.remove(*old) {
+                    rule.entry(*new).or_insert(value);
+                }
+            }
+        }
+    }
+}
+
+/// The name a top level setting has in the current schema.
+pub fn current_field_name(name: &str) -> &str {
+    RENAMED_FIELDS
+        .iter()
+        .find(|(old, _)| *old == name)
+        .map_or(name, |(_, new)| *new)
+}
+
+/// The names a setting had in older schema versions, to find it in files which were not
+/// migrated yet.
+pub fn former_names(name: &str) -> impl Iterator<Item = &'static str> + '_ {
+    RENAMED_FIELDS
+        .iter()
+        .chain(RENAMED_RULE_FIELDS)
+        .filter(move |(_, new)| *new == name)
+        .map(|(old, _)| *old)
+}
+
+fn split_modifier(modifier: &str) -> Option<Vec<String>> {
+    modifier.contains('+').then(|| {
+        modifier
+            .split('+')
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .map(str::to_string)
+            .collect()
+    })
+}
+
+fn split_modifiers_ron(root: &Node, source: &str) -> Result<Vec<Edit>> {
+    let mut edits = Vec::new();
+    let keybind_modifiers = root
+        .field("keybind")
+        .map(|f| f.value.items())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|keybind| keybind.field("modifier"));
+    for field in root.field("mousekey").into_iter().chain(keybind_modifiers) {
+        let value = field.value.unwrap_some();
+        let Ok(modifier) = ron::from_str::<String>(value.text(source)) else {
+            continue;
+        };
+        if let Some(list) = split_modifier(&modifier) {
+            let items = list
+                .iter()
+                .map(ron::to_string)
+                .collect::<Result<Vec<_>, _>>()?;
+            edits.push((value.span.clone(), format!("[{}]", items.join(", "))));
+        }
+    }
+    Ok(edits)
+}
+
+fn split_modifiers_toml(table: &mut toml::Table) {
+    let split = |value: &mut toml::Value| {
+        if let Some(list) = value.as_str().and_then(split_modifier) {
+            *value = toml::Value::Array(list.into_iter().map(toml::Value::String).collect());
+        }
+    };
+    if let Some(mousekey) = table.get_mut("mousekey") {
+        split(mousekey);
+    }
+    if let Some(toml::Value::Array(keybinds)) = table.get_mut("keybind") {
+        for keybind in keybinds.iter_mut().filter_map(toml::Value::as_table_mut) {
+            if let Some(modifier) = keybind.get_mut("modifier") {
+                split(modifier);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_ron_without_changes_only_sets_the_version() {
+        let migrated = migrate_ron("(\n    modkey: \"Mod4\",\n)\n").unwrap();
+        assert_eq!(migrated.from_version, 0);
+        assert!(migrated.applied.is_empty());
+        assert_eq!(
+            migrated.source,
+            "(\n    modkey: \"Mod4\",\n    version: 2,\n)\n"
+        );
+    }
+
+    #[test]
+    fn migrate_ron_at_the_current_version_is_unchanged() {
+        let source = "(\n    version: 2,\n    // kept\n    modkey: \"Mod4\",\n)\n";
+        let migrated = migrate_ron(source).unwrap();
+        assert_eq!(migrated.from_version, CURRENT_VERSION);
+        assert!(migrated.applied.is_empty());
+        assert_eq!(migrated.source, source);
+    }
+
+    #[test]
+    fn migrate_ron_applies_the_steps_which_change_the_source() {
+        let source = "(\n    // rules\n    window_config_by_class: [(wm_class: \"mpv\", spawn_floating: true)],\n    keybind: [(command: GotoTag, value: \"1\", modifier: \"modkey+Shift\", key: \"1\")],\n)\n";
+        let migrated = migrate_ron(source).unwrap();
+        assert_eq!(migrated.from_version, 0);
+        assert_eq!(
+            migrated.applied,
+            MIGRATIONS
+                .iter()
+                .map(|m| m.description.to_string())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            migrated.source,
+            "(\n    // rules\n    window_rules: [(window_class: \"mpv\", spawn_floating: true)],\n    keybind: [(command: GotoTag, value: \"1\", modifier: [\"modkey\", \"Shift\"], key: \"1\")],\n    version: 2,\n)\n"
+        );
+    }
+
+    #[test]
+    fn migrate_ron_skips_steps_below_the_version() {
+        let source = "(version: 1, window_config_by_class: [], keybind: [(command: GotoTag, value: \"1\", modifier: \"modkey+Shift\", key: \"1\")])";
+        let migrated = migrate_ron(source).unwrap();
+        assert_eq!(migrated.from_version, 1);
+        assert_eq!(
+            migrated.applied,
+            vec![MIGRATIONS[1].description.to_string()]
+        );
+        assert!(migrated.source.contains("window_config_by_class: []"));
+        assert!(migrated.source.contains("version: 2"));
+    }
+
+    #[test]
+    fn migrate_ron_rejects_unknown_versions() {
+        assert!(migrate_ron("(version: 99)").is_err());
+    }
+
+    #[test]
+    fn migrate_toml_without_changes_keeps_the_source() {
+        let source = "# my config\nmodkey = \"Mod4\"\n";
+        let migrated = migrate_toml(source).unwrap();
+        assert_eq!(migrated.from_version, 0);
+        assert!(migrated.applied.is_empty());
+        assert_eq!(migrated.source, source);
+    }
+
+    #[test]
+    fn migrate_toml_applies_the_steps_which_change_the_source() {
+        let source = "modkey = \"Mod4\"\n\n[[window_config_by_class]]\nwm_class = \"mpv\"\n\n[[keybind]]\ncommand = \"ToggleScratchPad\"\nvalue = \"term\"\nmodifier = \"modkey+Shift\"\nkey = \"t\"\n";
+        let migrated = migrate_toml(source).unwrap();
+        assert_eq!(
+            migrated.applied,
+            vec![
+                MIGRATIONS[0].description.to_string(),
+                MIGRATIONS[1].description.to_string()
+            ]
+        );
+        let table: toml::Table = toml::from_str(&migrated.source).unwrap();
+        assert_eq!(table["version"].as_integer(), Some(2));
+        assert!(table.get("window_config_by_class").is_none());
+        assert_eq!(
+            table["window_rules"][0]["window_class"].as_str(),
+            Some("mpv")
+        );
+        assert_eq!(
+            table["keybind"][0]["modifier"],
+            toml::Value::Array(vec!["modkey".into(), "Shift".into()])
+        );
+    }
+}
@@ -1,23 +1,39 @@
-use std::{env, fs};
+use std::path::PathBuf;
 
-pub use check::check_config;
-use layout::Layout;
+pub use check::{check_config, run_checks, watch_config};
+use layout::{LayoutDefinition, LayoutName};
 use serde::{Deserialize, Serialize};
 
-use crate::config::command::BaseCommand;
+use crate::config::command::{Argument, BaseCommand};
+use crate::config::detect::Environment;
 use crate::config::keybind::Keybind;
 use crate::config::modifier::Modifier;
 use crate::config::structs::{ScratchPad, WindowHook, Workspace};
+use crate::config::unknown::UnknownFields;
 use crate::config::values::{FocusBehaviour, InsertBehavior, LayoutMode, Size};
 
+pub mod backup;
 mod check;
 pub mod command;
+pub mod comments;
+pub mod convert;
+pub mod detect;
+pub mod diagnostic;
+pub mod document;
 pub mod filehandler;
 pub mod keybind;
 pub mod layout;
+pub mod location;
+pub mod migrate;
 pub mod modifier;
+pub mod monitor;
+pub mod overlay;
+pub mod profile;
+pub mod report;
 pub mod structs;
+pub mod unknown;
 pub mod values;
+pub mod wizard;
 
 #[derive(Copy, Clone)]
 #[allow(dead_code)]
@@ -29,11 +45,14 @@ pub enum Language {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(default)]
 pub struct Config {
-    pub modkey: String,
+    /// schema version, see `migrate`
+    pub version: u32,
+    pub modkey: Modifier,
     pub mousekey: Option<Modifier>,
     pub tags: Option<Vec<String>>,
     pub max_window_width: Option<Size>,
-    pub layouts: Vec<String>,
+    pub layouts: Vec<LayoutName>,
+    pub layout_definitions: Vec<LayoutDefinition>,
     pub layout_mode: LayoutMode,
     pub insert_behavior: InsertBehavior,
     pub scratchpad: Option<Vec<ScratchPad>>,
@@ -41,252 +60,274 @@ pub struct Config {
     //of you are on tag "1" and you goto tag "1" this takes you to the previous tag
     pub disable_current_tag_swap: bool,
     pub disable_tile_drag: bool,
+    pub disable_window_snap: bool,
     pub focus_behaviour: FocusBehaviour,
     pub focus_new_windows: bool,
+    pub single_window_border: bool,
+    pub sloppy_mouse_follows_focus: bool,
+    // when not set leftwm decides based on the focus behaviour
+    pub create_follows_cursor: Option<bool>,
+    // derive workspaces from the connected screens when none are defined
+    pub auto_derive_workspaces: bool,
+    pub reposition_cursor_on_resize: bool,
     pub keybind: Vec<Keybind>,
     pub workspaces: Option<Vec<Workspace>>,
+    pub state_path: Option<PathBuf>,
+    #[serde(skip)]
+    pub unknown_fields: UnknownFields,
 }
 
-fn default_terminal<'s>() -> &'s str {
-    // order from least common to most common.
-    // the thinking is if a machine has an uncommon terminal installed, it is intentional
-    let terms = &[
-        "alacritty",
-        "termite",
-        "kitty",
-        "urxvt",
-        "rxvt",
-        "st",
-        "roxterm",
-        "eterm",
-        "xterm",
-        "terminator",
-        "terminology",
-        "gnome-terminal",
-        "xfce4-terminal",
-        "konsole",
-        "uxterm",
-        "guake", // at the bottom because of odd behaviour. guake wants F12 and should really be
-                 // started using autostart instead of LeftWM keybind.
+/// The keybinds of a new config, bound to the given programs and the first `tag_count` tags.
+/// `exit` is the command which ends the session.
+// We allow this because this function would be difficult to reduce. If someone would like to
+// move the commands builder out, perhaps make a macro, this function could be reduced in size
+// considerably.
+#[allow(clippy::too_many_lines)]
+#[must_use]
+pub fn default_keybinds(
+    terminal: &str,
+    launcher: &str,
+    locker: Option<&str>,
+    exit: &str,
+    tag_count: usize,
+) -> Vec<Keybind> {
+    let mut commands = vec![
+        // Mod + p => Open the launcher
+        Keybind {
+            command: BaseCommand::Execute,
+            value: Argument::Text(launcher.to_owned()),
+            modifier: Some(vec!["modkey".to_owned()].into()),
+            key: "p".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        // Mod + Shift + Enter => Open A Shell
+        Keybind {
+            command: BaseCommand::Execute,
+            value: Argument::Text(terminal.to_owned()),
+            modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
+            key: "Return".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        // Mod + Shift + q => kill focused window
+        Keybind {
+            command: BaseCommand::CloseWindow,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
+            key: "q".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        // Mod + Shift + r => soft reload leftwm
+        Keybind {
+            command: BaseCommand::SoftReload,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
+            key: "r".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        // Mod + Shift + x => exit leftwm
+        Keybind {
+            command: BaseCommand::Execute,
+            value: Argument::Text(exit.to_owned()),
+            modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
+            key: "x".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        // Mod + Shift + w => swap the tags on the last to active workspaces
+        Keybind {
+            command: BaseCommand::MoveToLastWorkspace,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
+            key: "w".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        // Mod + w => move the active window to the previous workspace
+        Keybind {
+            command: BaseCommand::SwapTags,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned()].into()),
+            key: "w".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::MoveWindowUp,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
+            key: "k".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::MoveWindowDown,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
+            key: "j".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::MoveWindowTop,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned()].into()),
+            key: "Return".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::FocusWindowUp,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned()].into()),
+            key: "k".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::FocusWindowDown,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned()].into()),
+            key: "j".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::NextLayout,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned(), "Control".to_owned()].into()),
+            key: "k".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::PreviousLayout,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned(), "Control".to_owned()].into()),
+            key: "j".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::FocusWorkspaceNext,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned()].into()),
+            key: "l".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::FocusWorkspacePrevious,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned()].into()),
+            key: "h".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::MoveWindowUp,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
+            key: "Up".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::MoveWindowDown,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
+            key: "Down".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::FocusWindowUp,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned()].into()),
+            key: "Up".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::FocusWindowDown,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned()].into()),
+            key: "Down".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::NextLayout,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned(), "Control".to_owned()].into()),
+            key: "Up".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::PreviousLayout,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned(), "Control".to_owned()].into()),
+            key: "Down".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::FocusWorkspaceNext,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned()].into()),
+            key: "Right".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
+        Keybind {
+            command: BaseCommand::FocusWorkspacePrevious,
+            value: Argument::None,
+            modifier: Some(vec!["modkey".to_owned()].into()),
+            key: "Left".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        },
     ];
 
-    // If no terminal found in path, default to a good one
-    terms
-        .iter()
-        .find(|terminal| is_program_in_path(terminal))
-        .unwrap_or(&"termite")
-}
+    // Mod + Ctrl + l => lock the screen
+    if let Some(locker) = locker {
+        commands.push(Keybind {
+            command: BaseCommand::Execute,
+            value: Argument::Text(locker.to_owned()),
+            modifier: Some(vec!["modkey".to_owned(), "Control".to_owned()].into()),
+            key: "l".to_owned(),
+            unknown_fields: UnknownFields::default(),
+        });
+    }
 
-#[must_use]
-pub fn is_program_in_path(program: &str) -> bool {
-    if let Ok(path) = env::var("PATH") {
-        for p in path.split(':') {
-            let p_str = format!("{p}/{program}");
-            if fs::metadata(p_str).is_ok() {
-                return true;
-            }
-        }
+    // add "goto workspace"
+    for i in 1..=tag_count {
+        commands.push(Keybind {
+            command: BaseCommand::GotoTag,
+            value: Argument::Tag(i),
+            modifier: Some(vec!["modkey".to_owned()].into()),
+            key: i.to_string(),
+            unknown_fields: UnknownFields::default(),
+        });
+    }
+
+    // and "move to workspace"
+    for i in 1..=tag_count {
+        commands.push(Keybind {
+            command: BaseCommand::MoveToTag,
+            value: Argument::Tag(i),
+            modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
+            key: i.to_string(),
+            unknown_fields: UnknownFields::default(),
+        });
     }
-    false
+
+    commands
 }
 
+/// Binds the fallback programs, deserializing a config fills in missing keys from this and
+/// must not depend on what is installed. New configs use [`Config::for_environment`].
 impl Default for Config {
-    // We allow this because this function would be difficult to reduce. If someone would like to
-    // move the commands builder out, perhaps make a macro, this function could be reduced in size
-    // considerably.
-    #[allow(clippy::too_many_lines)]
     fn default() -> Self {
-        const WORKSPACES_NUM: usize = 10;
-        let mut commands = vec![
-            // Mod + p => Open dmenu
-            Keybind {
-                command: BaseCommand::Execute,
-                value: "dmenu_run".to_owned(),
-                modifier: Some(vec!["modkey".to_owned()].into()),
-                key: "p".to_owned(),
-            },
-            // Mod + Shift + Enter => Open A Shell
-            Keybind {
-                command: BaseCommand::Execute,
-                value: default_terminal().to_owned(),
-                modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
-                key: "Return".to_owned(),
-            },
-            // Mod + Shift + q => kill focused window
-            Keybind {
-                command: BaseCommand::CloseWindow,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
-                key: "q".to_owned(),
-            },
-            // Mod + Shift + r => soft reload leftwm
-            Keybind {
-                command: BaseCommand::SoftReload,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
-                key: "r".to_owned(),
-            },
-            // Mod + Shift + x => exit leftwm
-            Keybind {
-                command: BaseCommand::Execute,
-                value: exit_strategy().to_owned(),
-                modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
-                key: "x".to_owned(),
-            },
-            // Mod + Ctrl + l => lock the screen
-            Keybind {
-                command: BaseCommand::Execute,
-                value: "slock".to_owned(),
-                modifier: Some(vec!["modkey".to_owned(), "Control".to_owned()].into()),
-                key: "l".to_owned(),
-            },
-            // Mod + Shift + w => swap the tags on the last to active workspaces
-            Keybind {
-                command: BaseCommand::MoveToLastWorkspace,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
-                key: "w".to_owned(),
-            },
-            // Mod + w => move the active window to the previous workspace
-            Keybind {
-                command: BaseCommand::SwapTags,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned()].into()),
-                key: "w".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::MoveWindowUp,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
-                key: "k".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::MoveWindowDown,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
-                key: "j".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::MoveWindowTop,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned()].into()),
-                key: "Return".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::FocusWindowUp,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned()].into()),
-                key: "k".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::FocusWindowDown,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned()].into()),
-                key: "j".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::NextLayout,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned(), "Control".to_owned()].into()),
-                key: "k".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::PreviousLayout,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned(), "Control".to_owned()].into()),
-                key: "j".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::FocusWorkspaceNext,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned()].into()),
-                key: "l".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::FocusWorkspacePrevious,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned()].into()),
-                key: "h".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::MoveWindowUp,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
-                key: "Up".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::MoveWindowDown,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
-                key: "Down".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::FocusWindowUp,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned()].into()),
-                key: "Up".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::FocusWindowDown,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned()].into()),
-                key: "Down".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::NextLayout,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned(), "Control".to_owned()].into()),
-                key: "Up".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::PreviousLayout,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned(), "Control".to_owned()].into()),
-                key: "Down".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::FocusWorkspaceNext,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned()].into()),
-                key: "Right".to_owned(),
-            },
-            Keybind {
-                command: BaseCommand::FocusWorkspacePrevious,
-                value: String::default(),
-                modifier: Some(vec!["modkey".to_owned()].into()),
-                key: "Left".to_owned(),
-            },
-        ];
-
-        // add "goto workspace"
-        for i in 1..WORKSPACES_NUM {
-            commands.push(Keybind {
-                command: BaseCommand::GotoTag,
-                value: i.to_string(),
-                modifier: Some(vec!["modkey".to_owned()].into()),
-                key: i.to_string(),
-            });
-        }
-
-        // and "move to workspace"
-        for i in 1..WORKSPACES_NUM {
-            commands.push(Keybind {
-                command: BaseCommand::MoveToTag,
-                value: i.to_string(),
-                modifier: Some(vec!["modkey".to_owned(), "Shift".to_owned()].into()),
-                key: i.to_string(),
-            });
-        }
+        Self::for_environment(&Environment::default())
+    }
+}
 
+impl Config {
+    /// The default config with keybinds for the programs installed in `environment`.
+    #[must_use]
+    pub fn for_environment(environment: &Environment) -> Self {
         let tags = ["1", "2", "3", "4", "5", "6", "7", "8", "9"]
             .iter()
             .map(|s| (*s).to_string())
             .collect();
 
         Self {
+            version: migrate::CURRENT_VERSION,
             workspaces: Some(vec![]),
             tags: Some(tags),
             layouts: vec![],
+            layout_definitions: vec![],
             layout_mode: LayoutMode::Workspace,
             // TODO: add sane default for scratchpad config.
             // Currently default values are set in sane_dimension fn.
@@ -294,24 +335,31 @@ impl Default for Config {
             window_rules: Some(vec![]),
             disable_current_tag_swap: false,
             disable_tile_drag: false,
+            disable_window_snap: true,
             focus_behaviour: FocusBehaviour::Sloppy, // default behaviour: mouse move auto-focuses window
             focus_new_windows: true, // default behaviour: focuses windows on creation
+            single_window_border: true,
+            sloppy_mouse_follows_focus: true,
+            create_follows_cursor: None,
+            auto_derive_workspaces: true,
+            reposition_cursor_on_resize: true,
             insert_behavior: InsertBehavior::default(),
-            modkey: "Mod4".to_owned(),     //win key
+            modkey: "Mod4".into(),         //win key
             mousekey: Some("Mod4".into()), //win key
-            keybind: commands,
+            keybind: default_keybinds(
+                environment.terminal(),
+                environment.launcher(),
+                Some(environment.locker()),
+                environment.exit_command(),
+                9,
+            ),
             max_window_width: None,
+            state_path: None,
+            unknown_fields: UnknownFields::default(),
         }
     }
 }
 
-fn exit_strategy<'s>() -> &'s str {
-    if is_program_in_path("loginctl") {
-        return "loginctl kill-session $XDG_SESSION_ID";
-    }
-    "pkill leftwm"
-}
-
 #[allow(dead_code)]
 #[must_use]
 pub fn check_workspace_ids(config: &Config) -> bool {
@@ -341,3 +389,83 @@ pub fn all_ids_unique(ids: &[Option<i32>]) -> bool {
     sorted.dedup();
     ids.len() == sorted.len()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::detect::{self, Program};
+
+    fn executed(config: &Config) -> Vec<String> {
+        config
+            .keybind
+            .iter()
+            .filter(|k| k.command == BaseCommand::Execute)
+            .map(|k| k.value.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn default_binds_the_fallback_programs() {
+        let commands = executed(&Config::default());
+        assert!(commands.contains(&detect::FALLBACK_TERMINAL.to_string()));
+        assert!(commands.contains(&detect::FALLBACK_LAUNCHER.to_string()));
+        assert!(commands.contains(&detect::FALLBACK_LOCKER.to_string()));
+    }
+
+    #[test]
+    fn for_environment_binds_the_installed_programs() {
+        let environment = Environment {
+            terminals: vec![detect::TERMINALS[0]],
+            launchers: vec![detect::LAUNCHERS[0]],
+            lockers: vec![],
+            session_tools: vec![Program {
+                name: "loginctl",
+                command: "loginctl kill-session $XDG_SESSION_ID",
+            }],
+        };
+        let commands = executed(&Config::for_environment(&environment));
+        assert!(commands.contains(&detect::TERMINALS[0].command.to_string()));
+        assert!(commands.contains(&detect::LAUNCHERS[0].command.to_string()));
+        assert!(commands.contains(&"loginctl kill-session $XDG_SESSION_ID".to_string()));
+    }
+
+    #[test]
+    fn deserializes_the_leftwm_options() {
+        let ron = "#![enable(implicit_some)]\n(disable_window_snap: false, single_window_border: false, sloppy_mouse_follows_focus: false, create_follows_cursor: true, auto_derive_workspaces: false, reposition_cursor_on_resize: false, state_path: \"/tmp/leftwm.state\")";
+        let toml = "disable_window_snap = false\nsingle_window_border = false\nsloppy_mouse_follows_focus = false\ncreate_follows_cursor = true\nauto_derive_workspaces = false\nreposition_cursor_on_resize = false\nstate_path = \"/tmp/leftwm.state\"\n";
+        for config in [
+            filehandler::deserialize(ron, true).unwrap(),
+            filehandler::deserialize(toml, false).unwrap(),
+        ] {
+            assert!(!config.disable_window_snap);
+            assert!(!config.single_window_border);
+            assert!(!config.sloppy_mouse_follows_focus);
+            assert_eq!(config.create_follows_cursor, Some(true));
+            assert!(!config.auto_derive_workspaces);
+            assert!(!config.reposition_cursor_on_resize);
+            assert_eq!(config.state_path, Some(PathBuf::from("/tmp/leftwm.state")));
+        }
+    }
+
+    #[test]
+    fn leftwm_options_default_when_left_out() {
+        let config = filehandler::deserialize("()", true).unwrap();
+        let default = Config::default();
+        assert_eq!(config.disable_window_snap, default.disable_window_snap);
+        assert_eq!(config.single_window_border, default.single_window_border);
+        assert_eq!(
+            config.sloppy_mouse_follows_focus,
+            default.sloppy_mouse_follows_focus
+        );
+        assert_eq!(config.create_follows_cursor, None);
+        assert_eq!(
+            config.auto_derive_workspaces,
+            default.auto_derive_workspaces
+        );
+        assert_eq!(
+            config.reposition_cursor_on_resize,
+            default.reposition_cursor_on_resize
+        );
+        assert_eq!(config.state_path, None);
+    }
+}
@@ -1,13 +1,84 @@
+//! Modifier keys as used by `modkey`, `mousekey` and keybinds.
+//!
+//! Names are matched case-insensitively and aliases (`super`, `win`, `ctrl`, `alt`, `meta`, ...)
+//! are normalized to the names leftwm documents when a config is loaded.
+//!
+//! Mod2 is NumLock on nearly every keyboard layout. leftwm strips NumLock from key events
+//! so that bindings work whether NumLock is on or off, which means a binding with `Mod2`
+//! or `NumLock` could never fire. Both names are therefore rejected by `check`.
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::utils::xkeysym_lookup::into_mod;
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq)]
+#[serde(from = "ModifierRepr")]
 #[serde(untagged)]
 pub enum Modifier {
     Single(String),
     List(Vec<String>),
 }
 
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ModifierRepr {
+    Single(String),
+    List(Vec<String>),
+}
+
+impl From<ModifierRepr> for Modifier {
+    fn from(repr: ModifierRepr) -> Self {
+        let normalize = |m: String| canonical(&m).map_or(m, str::to_string);
+        match repr {
+            ModifierRepr::Single(m) => Self::Single(normalize(m)),
+            ModifierRepr::List(ms) => Self::List(ms.into_iter().map(normalize).collect()),
+        }
+    }
+}
+
+/// The name leftwm documents for a modifier, if `name` is one of its spellings.
+pub fn canonical(name: &str) -> Option<&'static str> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "none" => "None",
+        "shift" => "Shift",
+        "control" | "ctrl" | "ctl" => "Control",
+        "mod1" | "alt" | "meta" => "Mod1",
+        "mod2" | "numlock" => "Mod2",
+        "mod3" => "Mod3",
+        "mod4" | "super" | "win" | "windows" | "logo" => "Mod4",
+        "mod5" => "Mod5",
+        "modkey" => "modkey",
+        "mousekey" => "mousekey",
+        _ => return None,
+    })
+}
+
+#[derive(Debug, Error)]
+pub enum ModifierError {
+    #[error("Modifier `{0}` is not valid")]
+    Unknown(String),
+    #[error(
+        "Modifier `{0}` is NumLock, which leftwm ignores so that bindings work with NumLock on or off"
+    )]
+    NumLock(String),
+    #[error("`{0}` can only be used in keybinds")]
+    Placeholder(String),
+}
+
 impl Modifier {
+    /// Checks every name in the modifier, `placeholders` allows `modkey` and `mousekey`.
+    pub fn validate(&self, placeholders: bool) -> Vec<ModifierError> {
+        self.into_iter()
+            .filter_map(|m| match canonical(&m) {
+                Some("Mod2") => Some(ModifierError::NumLock(m)),
+                Some("modkey" | "mousekey") if !placeholders => Some(ModifierError::Placeholder(m)),
+                Some("modkey" | "mousekey") => None,
+                _ if into_mod(&m) == 0 => Some(ModifierError::Unknown(m)),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn is_empty(&self) -> bool {
         match self {
             Modifier::Single(single) => single.is_empty(),
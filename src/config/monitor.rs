@@ -0,0 +1,300 @@
+//! The monitor layout workspaces are checked against, read from a file or from
+//! `xrandr --query`.
+//!
+//! Every line with a geometry such as `1920x1080+0+0` describes a monitor, so both the
+//! output of `xrandr --query` and a hand written list like the following are understood:
+//!
+//! ```text
+//! DP-1 2560x1440+0+0
+//! HDMI-1 1920x1080+2560+0
+//! ```
+use std::fmt;
+use std::fs;
+use std::process::Command;
+
+use anyhow::{Context, Result, bail};
+
+/// A rectangle in screen coordinates, written like xrandr does as `WxH+X+Y`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    #[must_use]
+    pub fn right(&self) -> i32 {
+        self.x.saturating_add(self.width)
+    }
+
+    #[must_use]
+    pub fn bottom(&self) -> i32 {
+        self.y.saturating_add(self.height)
+    }
+
+    #[must_use]
+    pub fn area(&self) -> i64 {
+        i64::from(self.width) * i64::from(self.height)
+    }
+
+    /// The part both rectangles cover, `None` if they only touch or are apart.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        (x < right && y < bottom).then(|| Self {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        })
+    }
+
+    #[must_use]
+    pub fn contains(&self, other: &Self) -> bool {
+        self.intersection(other) == Some(*other)
+    }
+
+    /// The parts of `self` which none of `rects` cover. Rectangles next to each other on
+    /// the same rows are joined.
+    #[must_use]
+    pub fn uncovered(&self, rects: &[Self]) -> Vec<Self> {
+        let inside: Vec<Self> = rects.iter().filter_map(|r| r.intersection(self)).collect();
+        let edges = |start: i32, end: i32, sides: &dyn Fn(&Self) -> [i32; 2]| {
+            let mut edges: Vec<i32> = inside.iter().flat_map(sides).collect();
+            edges.extend([start, end]);
+            edges.sort_unstable();
+            edges.dedup();
+            edges
+        };
+        let xs = edges(self.x, self.right(), &|r| [r.x, r.right()]);
+        let ys = edges(self.y, self.bottom(), &|r| [r.y, r.bottom()]);
+        let mut found: Vec<Self> = Vec::new();
+        for row in ys.windows(2) {
+            for column in xs.windows(2) {
+                let cell = Self {
+                    x: column[0],
+                    y: row[0],
+                    width: column[1] - column[0],
+                    height: row[1] - row[0],
+                };
+                if inside.iter().any(|r| r.contains(&cell)) {
+                    continue;
+                }
+                match found.last_mut() {
+                    Some(last) if last.y == cell.y && last.right() == cell.x => {
+                        last.width += cell.width;
+                    }
+                    _ => found.push(cell),
+                }
+            }
+        }
+        found
+    }
+
+    /// Parses `WxH+X+Y`.
+    fn parse(s: &str) -> Option<Self> {
+        let (size, position) = s.split_once('+')?;
+        let (width, height) = size.split_once('x')?;
+        let (x, y) = position.split_once('+')?;
+        Some(Self {
+            x: x.parse().ok()?,
+            y: y.parse().ok()?,
+            width: width.parse().ok()?,
+            height: height.parse().ok()?,
+        })
+    }
+}
+
+impl fmt::Display for Rect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}+{}+{}", self.width, self.height, self.x, self.y)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Monitor {
+    /// the output name, e.g. `HDMI-1`, empty if the description has none
+    pub name: String,
+    pub area: Rect,
+}
+
+impl fmt::Display for Monitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.name.is_empty() {
+            write!(f, "monitor {}", self.area)
+        } else {
+            write!(f, "monitor {} ({})", self.name, self.area)
+        }
+    }
+}
+
+/// Reads the monitor layout from `source`, either a file or `xrandr` to ask xrandr for the
+/// layout of the running X server.
+pub fn load(source: &str) -> Result<Vec<Monitor>> {
+    let description = if source == "xrandr" {
+        let output = Command::new("xrandr")
+            .arg("--query")
+            .output()
+            .context("Could not run `xrandr --query`")?;
+        if !output.status.success() {
+            bail!(
+                "`xrandr --query` failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    } else {
+        fs::read_to_string(source).with_context(|| format!("Could not read {source}"))?
+    };
+    let monitors = parse(&description);
+    if monitors.is_empty() {
+        bail!("No monitor geometry like `1920x1080+0+0` was found in {source}");
+    }
+    Ok(monitors)
+}
+
+/// The monitors described by `description`, disconnected xrandr outputs are skipped.
+#[must_use]
+pub fn parse(description: &str) -> Vec<Monitor> {
+    description
+        .lines()
+        .filter(|line| !line.contains(" disconnected"))
+        .filter_map(|line| {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let area = words.iter().find_map(|word| Rect::parse(word))?;
+            let name = match words.first() {
+                Some(first) if Rect::parse(first).is_none() => (*first).to_string(),
+                _ => String::new(),
+            };
+            Some(Monitor { name, area })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: i32, height: i32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn intersection_of_overlapping_rects() {
+        let a = rect(0, 0, 100, 100);
+        let b = rect(50, 25, 100, 100);
+        assert_eq!(a.intersection(&b), Some(rect(50, 25, 50, 75)));
+        assert_eq!(b.intersection(&a), Some(rect(50, 25, 50, 75)));
+    }
+
+    #[test]
+    fn touching_and_disjoint_rects_do_not_intersect() {
+        let a = rect(0, 0, 100, 100);
+        assert_eq!(a.intersection(&rect(100, 0, 100, 100)), None);
+        assert_eq!(a.intersection(&rect(0, 100, 100, 100)), None);
+        assert_eq!(a.intersection(&rect(300, 300, 10, 10)), None);
+    }
+
+    #[test]
+    fn nested_rects() {
+        let outer = rect(0, 0, 1920, 1080);
+        let inner = rect(100, 100, 200, 200);
+        assert_eq!(outer.intersection(&inner), Some(inner));
+        assert!(outer.contains(&inner));
+        assert!(!inner.contains(&outer));
+    }
+
+    #[test]
+    fn uncovered_parts() {
+        let monitor = rect(0, 0, 1920, 1080);
+        assert_eq!(monitor.uncovered(&[monitor]), vec![]);
+        assert_eq!(monitor.uncovered(&[]), vec![monitor]);
+        // two workspaces side by side which touch cover the monitor
+        assert_eq!(
+            monitor.uncovered(&[rect(0, 0, 960, 1080), rect(960, 0, 960, 1080)]),
+            vec![]
+        );
+        // the right third is left over
+        assert_eq!(
+            monitor.uncovered(&[rect(0, 0, 1280, 1080)]),
+            vec![rect(1280, 0, 640, 1080)]
+        );
+        // rects reaching past the monitor count where they overlap it
+        assert_eq!(
+            monitor.uncovered(&[rect(-100, -100, 2120, 200), rect(0, 100, 1920, 980)]),
+            vec![]
+        );
+        // a gap between two rows is one rect
+        assert_eq!(
+            rect(0, 0, 300, 300).uncovered(&[rect(0, 0, 300, 100), rect(0, 200, 300, 100)]),
+            vec![rect(0, 100, 300, 100)]
+        );
+        // disjoint rects don't cover anything
+        assert_eq!(
+            monitor.uncovered(&[rect(1920, 0, 1920, 1080)]),
+            vec![monitor]
+        );
+    }
+
+    #[test]
+    fn parse_xrandr_query() {
+        let output = "\
+Screen 0: minimum 320 x 200, current 4480 x 1440, maximum 16384 x 16384
+eDP-1 connected primary 2560x1440+0+0 (normal left inverted right x axis y axis) 309mm x 174mm
+   2560x1440     60.00*+  59.96    48.00
+   1920x1080     60.01    59.97
+HDMI-1 disconnected (normal left inverted right x axis y axis)
+DP-1 connected 1920x1080+2560+180 (normal left inverted right x axis y axis) 527mm x 296mm
+   1920x1080     60.00*+  74.97    50.00
+DP-2 connected (normal left inverted right x axis y axis)
+   1920x1080     60.00 +
+";
+        assert_eq!(
+            parse(output),
+            vec![
+                Monitor {
+                    name: "eDP-1".to_string(),
+                    area: rect(0, 0, 2560, 1440),
+                },
+                Monitor {
+                    name: "DP-1".to_string(),
+                    area: rect(2560, 180, 1920, 1080),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_hand_written_list() {
+        assert_eq!(
+            parse("DP-1 2560x1440+0+0\n\n1920x1080+2560+0\n"),
+            vec![
+                Monitor {
+                    name: "DP-1".to_string(),
+                    area: rect(0, 0, 2560, 1440),
+                },
+                Monitor {
+                    name: String::new(),
+                    area: rect(2560, 0, 1920, 1080),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rect_display_parses_back() {
+        let area = rect(2560, 180, 1920, 1080);
+        assert_eq!(area.to_string(), "1920x1080+2560+180");
+        assert_eq!(Rect::parse(&area.to_string()), Some(area));
+    }
+}
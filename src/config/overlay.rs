@@ -0,0 +1,299 @@
+//! Host specific fragments in `config.d/*.ron` next to the config, merged over it.
+//! Profiles are configs for the same host, so they use the fragments next to config.ron.
+//!
+//! Fragments are applied in file name order and only the settings a fragment spells out are
+//! merged, everything else keeps the value from the base config or an earlier fragment:
+//! - keybinds replace the keybind with the same key combination, others are appended
+//! - window rules for the same class and title are merged field by field, others are appended
+//! - every other setting is replaced as a whole
+//!
+//! Fragments are read with the `implicit_some` extension enabled, like the generated config.
+//! Keys leftwm-config doesn't know are kept like in the base config and reported by
+//! [`Merged::unknown`].
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::document;
+use crate::config::keybind::Keybind;
+use crate::config::structs::WindowHook;
+use crate::config::{Config, filehandler, migrate, profile, unknown};
+
+pub const OVERLAY_DIR: &str = "config.d";
+
+/// Where an effective value came from, for values which do not come from the base config.
+#[derive(Debug, Serialize)]
+pub struct Source {
+    /// the setting, e.g. `modkey` or `keybind[12]`
+    pub setting: String,
+    pub fragment: PathBuf,
+    /// the index of the keybind or window rule in the fragment, which differs from the one
+    /// in the merged config
+    pub entry: Option<usize>,
+}
+
+/// A config with its fragments merged over it.
+#[derive(Debug)]
+pub struct Merged {
+    pub config: Config,
+    /// the values which do not come from the base config
+    pub sources: Vec<Source>,
+    /// keys of the fragments which are not a setting leftwm-config knows, also in `sources`
+    pub unknown: Vec<Source>,
+}
+
+/// The fragments for `config_file`, in the order they are applied.
+pub fn fragments(config_file: &Path) -> Result<Vec<PathBuf>> {
+    let main_file;
+    let config_file = if profile::is_profile(config_file) {
+        main_file = filehandler::main_ron_file()?;
+        &main_file
+    } else {
+        config_file
+    };
+    let Some(dir) = config_file.parent() else {
+        return Ok(vec![]);
+    };
+    let pattern = dir.join(OVERLAY_DIR).join("*.ron");
+    let pattern = pattern
+        .to_str()
+        .context("The config path is not valid UTF-8")?;
+    let mut fragments = glob::glob(pattern)?.collect::<Result<Vec<_>, _>>()?;
+    fragments.sort();
+    Ok(fragments)
+}
+
+/// Merges all fragments of `config_file` over `base`.
+pub fn merge(base: &Config, config_file: &Path) -> Result<Merged> {
+    let mut sources = Vec::new();
+    let mut unknown = Vec::new();
+    let mut unknown_fields = base.unknown_fields.clone();
+    // kept typed, a round trip through serde_json drops the unknown fields of the entries
+    let mut keybinds = base.keybind.clone();
+    let mut window_rules = base.window_rules.clone();
+    let mut workspaces = base.workspaces.clone();
+    let mut scratchpad = base.scratchpad.clone();
+    let mut layout_definitions = base.layout_definitions.clone();
+    let Value::Object(mut merged) = serde_json::to_value(base)? else {
+        bail!("The config did not serialize to a map");
+    };
+
+    for fragment in fragments(config_file)? {
+        let source = fs::read_to_string(&fragment)?;
+        let parse_error = || format!("Could not parse {}", fragment.display());
+        let fields = document::parse(&source).with_context(parse_error)?;
+        let mut overlay: Config = ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(&source)
+            .with_context(parse_error)?;
+        unknown::capture_ron(&mut overlay, &source).with_context(parse_error)?;
+        let Value::Object(overlay_values) = serde_json::to_value(&overlay)? else {
+            bail!("The fragment did not serialize to a map");
+        };
+        let mut record = |setting: String, entry: Option<usize>| {
+            sources.retain(|s: &Source| s.setting != setting);
+            sources.push(Source {
+                setting,
+                fragment: fragment.clone(),
+                entry,
+            });
+        };
+
+        for field in fields.fields() {
+            match field.name.as_str() {
+                "version" => {}
+                "keybind" => {
+                    for (entry, keybind) in overlay.keybind.iter().enumerate() {
+                        let index = merge_keybind(&mut keybinds, keybind);
+                        record(
+                            format!("keybind[{index}] {}", combination(keybind).join("+")),
+                            Some(entry),
+                        );
+                    }
+                }
+                "window_rules" => {
+                    let rules = window_rules.get_or_insert_with(Vec::new);
+                    for (entry, rule) in overlay.window_rules.iter().flatten().enumerate() {
+                        let index = merge_window_rule(rules, rule)?;
+                        record(format!("window_rules[{index}]"), Some(entry));
+                    }
+                }
+                name => {
+                    match name {
+                        "workspaces" => workspaces.clone_from(&overlay.workspaces),
+                        "scratchpad" => scratchpad.clone_from(&overlay.scratchpad),
+                        "layout_definitions" => {
+                            layout_definitions.clone_from(&overlay.layout_definitions);
+                        }
+                        _ => {}
+                    }
+                    if let Some(value) = overlay_values.get(name) {
+                        merged.insert(name.to_string(), value.clone());
+                    } else if let Some(value) = overlay.unknown_fields.get(name) {
+                        unknown_fields.insert(name, value);
+                        unknown.retain(|s: &Source| s.setting != name);
+                        unknown.push(Source {
+                            setting: name.to_string(),
+                            fragment: fragment.clone(),
+                            entry: None,
+                        });
+                    }
+                    record(name.to_string(), None);
+                }
+            }
+        }
+    }
+
+    let mut config: Config = serde_json::from_value(Value::Object(merged))?;
+    config.keybind = keybinds;
+    config.window_rules = window_rules;
+    config.workspaces = workspaces;
+    config.scratchpad = scratchpad;
+    config.layout_definitions = layout_definitions;
+    config.version = migrate::CURRENT_VERSION;
+    config.unknown_fields = unknown_fields;
+    Ok(Merged {
+        config,
+        sources,
+        unknown,
+    })
+}
+
+/// The modifiers, sorted, followed by the key.
+fn combination(keybind: &Keybind) -> Vec<String> {
+    let mut combination: Vec<String> = keybind.modifier.clone().map(Vec::from).unwrap_or_default();
+    combination.sort_unstable();
+    combination.dedup();
+    combination.push(keybind.key.clone());
+    combination
+}
+
+/// Replaces the keybind with the same combination or appends, returns the index used.
+fn merge_keybind(keybinds: &mut Vec<Keybind>, keybind: &Keybind) -> usize {
+    let wanted = combination(keybind);
+    if let Some(index) = keybinds.iter().position(|k| combination(k) == wanted) {
+        keybinds[index] = keybind.clone();
+        index
+    } else {
+        keybinds.push(keybind.clone());
+        keybinds.len() - 1
+    }
+}
+
+/// Merges the options `rule` sets into the rule for the same window, or appends it.
+fn merge_window_rule(rules: &mut Vec<WindowHook>, rule: &WindowHook) -> Result<usize> {
+    let Some(index) = rules
+        .iter()
+        .position(|r| r.window_class == rule.window_class && r.window_title == rule.window_title)
+    else {
+        rules.push(rule.clone());
+        return Ok(rules.len() - 1);
+    };
+    let (Value::Object(mut merged), Value::Object(overlay)) = (
+        serde_json::to_value(&rules[index])?,
+        serde_json::to_value(rule)?,
+    ) else {
+        bail!("A window rule did not serialize to a map");
+    };
+    merged.extend(overlay.into_iter().filter(|(_, value)| !value.is_null()));
+    let unknown_fields = rules[index].unknown_fields.clone();
+    rules[index] = serde_json::from_value(Value::Object(merged))?;
+    rules[index].unknown_fields = unknown_fields;
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::command::BaseCommand;
+    use crate::config::filehandler;
+
+    const BASE: &str = r#"#![enable(implicit_some)]
+(
+    modkey: "Mod4",
+    keybind: [
+        (command: Execute, value: "st", modifier: ["modkey"], key: "Return"),
+        (command: CloseWindow, modifier: ["modkey", "Shift"], key: "q"),
+    ],
+    window_rules: [
+        (window_class: "mpv", spawn_on_tag: 2, spawn_floating: true),
+    ],
+)
+"#;
+
+    /// Merges `fragment` over [`BASE`], both written to a directory of their own.
+    fn merge_fragment(name: &str, fragment: &str) -> Merged {
+        let dir = std::env::temp_dir().join(format!(
+            "leftwm-config-overlay-{}-{name}",
+            std::process::id()
+        ));
+        fs::create_dir_all(dir.join(OVERLAY_DIR)).unwrap();
+        let file = dir.join("config.ron");
+        fs::write(&file, BASE).unwrap();
+        fs::write(dir.join(OVERLAY_DIR).join("10-host.ron"), fragment).unwrap();
+        let base = filehandler::deserialize(BASE, true).unwrap();
+        let merged = merge(&base, &file).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        merged
+    }
+
+    #[test]
+    fn keybinds_replace_the_same_combination() {
+        let merged = merge_fragment(
+            "keybinds",
+            r#"(keybind: [
+                (command: Execute, value: "rofi", modifier: ["modkey"], key: "p"),
+                (command: Execute, value: "alacritty", modifier: ["modkey"], key: "Return"),
+            ])"#,
+        );
+        let keybinds = &merged.config.keybind;
+        assert_eq!(keybinds.len(), 3);
+        assert_eq!(keybinds[0].value.to_string(), "alacritty");
+        assert_eq!(keybinds[1].command, BaseCommand::CloseWindow);
+        assert_eq!(keybinds[2].value.to_string(), "rofi");
+        let entries: Vec<_> = merged
+            .sources
+            .iter()
+            .map(|s| (s.setting.as_str(), s.entry))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("keybind[2] modkey+p", Some(0)),
+                ("keybind[0] modkey+Return", Some(1))
+            ]
+        );
+    }
+
+    #[test]
+    fn window_rules_merge_field_by_field() {
+        let merged = merge_fragment(
+            "rules",
+            r#"#![enable(implicit_some)]
+            (window_rules: [
+                (window_class: "krita", spawn_on_tag: 4),
+                (window_class: "mpv", spawn_on_tag: 3),
+            ])"#,
+        );
+        let rules = merged.config.window_rules.unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].spawn_on_tag, Some(3));
+        assert_eq!(rules[0].spawn_floating, Some(true));
+        assert_eq!(rules[1].window_class.as_deref(), Some("krita"));
+        assert_eq!(merged.sources[1].setting, "window_rules[0]");
+        assert_eq!(merged.sources[1].entry, Some(1));
+    }
+
+    #[test]
+    fn other_settings_are_replaced_and_unknown_keys_kept() {
+        let merged = merge_fragment("settings", r#"(modkey: "Mod1", future_option: 7)"#);
+        assert_eq!(merged.config.modkey.to_string(), "Mod1");
+        assert_eq!(merged.config.keybind.len(), 2);
+        assert_eq!(merged.config.unknown_fields.get("future_option"), Some("7"));
+        assert_eq!(merged.unknown[0].setting, "future_option");
+        assert_eq!(merged.sources[0].entry, None);
+    }
+}
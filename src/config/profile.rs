@@ -0,0 +1,203 @@
+//! Named configs, e.g. for a laptop and a docked setup, stored in `profiles/` next to config.ron.
+//!
+//! leftwm only reads config.ron, so switching to a profile copies it there. The name of the
+//! profile in use is kept in `profiles/.active`, saving that profile updates config.ron too.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result, bail, ensure};
+
+use crate::config::{backup, filehandler};
+
+const PROFILE_DIR: &str = "profiles";
+const ACTIVE_FILE: &str = ".active";
+
+/// The profile chosen with `--profile`, see [`select`].
+static SELECTED: OnceLock<String> = OnceLock::new();
+
+/// Makes every mode edit the profile `name` instead of config.ron.
+pub fn select(name: String) {
+    let _ = SELECTED.set(name);
+}
+
+pub fn selected() -> Option<&'static str> {
+    SELECTED.get().map(String::as_str)
+}
+
+pub fn dir() -> Result<PathBuf> {
+    let main = filehandler::main_ron_file()?;
+    Ok(main
+        .parent()
+        .context("The config file has no parent directory")?
+        .join(PROFILE_DIR))
+}
+
+/// Whether `file` is one of the profiles.
+pub fn is_profile(file: &Path) -> bool {
+    let parent = file.parent();
+    parent.and_then(Path::file_name) == Some(PROFILE_DIR.as_ref())
+        && dir().is_ok_and(|dir| parent == Some(dir.as_path()))
+}
+
+/// The file of the profile `name`, which does not have to exist yet.
+pub fn path(name: &str) -> Result<PathBuf> {
+    path_in(&dir()?, name)
+}
+
+fn path_in(dir: &Path, name: &str) -> Result<PathBuf> {
+    ensure!(
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+        "Invalid profile name `{name}`, only letters, digits, `-` and `_` are allowed"
+    );
+    Ok(dir.join(format!("{name}.ron")))
+}
+
+/// The names of all profiles, sorted.
+pub fn list() -> Result<Vec<String>> {
+    let mut names: Vec<String> = match fs::read_dir(dir()?) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                name.strip_suffix(".ron").map(str::to_string)
+            })
+            .filter(|name| !name.starts_with('.'))
+            .collect(),
+        Err(_) => vec![],
+    };
+    names.sort();
+    Ok(names)
+}
+
+/// The profile which was last switched to.
+pub fn active() -> Result<Option<String>> {
+    Ok(active_in(&dir()?))
+}
+
+fn active_in(dir: &Path) -> Option<String> {
+    fs::read_to_string(dir.join(ACTIVE_FILE))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// Creates the profile `name` as a copy of the current config.
+pub fn create(name: &str) -> Result<()> {
+    let file = path(name)?;
+    ensure!(!file.exists(), "The profile `{name}` already exists");
+    fs::create_dir_all(dir()?)?;
+    let main = filehandler::main_ron_file()?;
+    if main.exists() {
+        fs::copy(&main, &file)?;
+    } else {
+        // a TOML config is converted, profiles are always RON
+        let config = filehandler::load_from_file(None, false)?;
+        filehandler::write_to_file(&file, &config)?;
+    }
+    Ok(())
+}
+
+/// Copies the profile `name` to config.ron. Changes made to config.ron since the last switch
+/// are saved to the previously active profile first, so they are not lost.
+pub fn switch(name: &str) -> Result<()> {
+    switch_in(&dir()?, &filehandler::main_ron_file()?, name)
+}
+
+fn switch_in(dir: &Path, main: &Path, name: &str) -> Result<()> {
+    let file = path_in(dir, name)?;
+    ensure!(file.exists(), "There is no profile `{name}`");
+    if let (Some(previous), Ok(current)) = (active_in(dir), fs::read_to_string(main))
+        && previous != name
+    {
+        let previous_file = path_in(dir, &previous)?;
+        if fs::read_to_string(&previous_file).ok().as_deref() != Some(current.as_str()) {
+            backup::write_atomic(&previous_file, &current)?;
+            println!(
+                "\x1b[0;94m::\x1b[0m Saved the changes in {} to profile `{previous}`",
+                main.display()
+            );
+        }
+    }
+    backup::write_atomic(main, &fs::read_to_string(&file)?)?;
+    fs::write(dir.join(ACTIVE_FILE), name)?;
+    Ok(())
+}
+
+pub fn delete(name: &str) -> Result<()> {
+    let file = path(name)?;
+    ensure!(file.exists(), "There is no profile `{name}`");
+    if active()?.as_deref() == Some(name) {
+        bail!("The profile `{name}` is in use, switch to another profile first");
+    }
+    fs::remove_file(file)?;
+    Ok(())
+}
+
+/// Copies `saved` to config.ron if it is the file of the active profile.
+pub fn apply_if_active(saved: &Path) -> Result<()> {
+    let Some(active) = active()? else {
+        return Ok(());
+    };
+    if path(&active)? == saved {
+        backup::write_atomic(&filehandler::main_ron_file()?, &fs::read_to_string(saved)?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A config.ron with the profiles `laptop` and `docked`, `laptop` being in use.
+    fn profiles(name: &str) -> (PathBuf, PathBuf) {
+        let main = backup::tests::config_file(&format!("profile-{name}"));
+        let dir = main.with_file_name(PROFILE_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("laptop.ron"), "laptop").unwrap();
+        fs::write(dir.join("docked.ron"), "docked").unwrap();
+        switch_in(&dir, &main, "laptop").unwrap();
+        (dir, main)
+    }
+
+    #[test]
+    fn switch_copies_the_profile_to_the_config() {
+        let (dir, main) = profiles("copy");
+        assert_eq!(fs::read_to_string(&main).unwrap(), "laptop");
+        switch_in(&dir, &main, "docked").unwrap();
+        assert_eq!(fs::read_to_string(&main).unwrap(), "docked");
+        assert_eq!(active_in(&dir).as_deref(), Some("docked"));
+        assert_eq!(
+            fs::read_to_string(dir.join("laptop.ron")).unwrap(),
+            "laptop"
+        );
+    }
+
+    #[test]
+    fn switch_saves_unsaved_changes_to_the_previous_profile() {
+        let (dir, main) = profiles("unsaved");
+        fs::write(&main, "laptop, edited").unwrap();
+        switch_in(&dir, &main, "docked").unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("laptop.ron")).unwrap(),
+            "laptop, edited"
+        );
+        switch_in(&dir, &main, "laptop").unwrap();
+        assert_eq!(fs::read_to_string(&main).unwrap(), "laptop, edited");
+        assert_eq!(
+            fs::read_to_string(dir.join("docked.ron")).unwrap(),
+            "docked"
+        );
+    }
+
+    #[test]
+    fn switch_rejects_unknown_profiles() {
+        let (dir, main) = profiles("unknown");
+        assert!(switch_in(&dir, &main, "missing").is_err());
+        assert!(switch_in(&dir, &main, "../laptop").is_err());
+        assert_eq!(active_in(&dir).as_deref(), Some("laptop"));
+    }
+}
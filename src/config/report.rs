@@ -0,0 +1,408 @@
+//! Everything `check` found, printed for people or written as JSON or SARIF for editor
+//! integrations and CI dashboards.
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{Result, bail};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::config::diagnostic::{self, Diagnostic, Severity};
+use crate::config::overlay;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const INFORMATION_URI: &str = "https://github.com/leftwm/leftwm-config";
+
+/// The commit leftwm-config was built from.
+#[must_use]
+pub fn git_hash() -> &'static str {
+    option_env!("GIT_HASH").unwrap_or(git_version::git_version!(fallback = "unknown"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Sarif,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "sarif" => Ok(Self::Sarif),
+            _ => bail!("Unknown format `{s}`, expected text, json or sarif"),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Text => "text",
+            Self::Json => "json",
+            Self::Sarif => "sarif",
+        })
+    }
+}
+
+/// The findings of one group of checks, e.g. `keybinds`.
+#[derive(Debug, Serialize)]
+pub struct Group {
+    pub name: &'static str,
+    /// printed when there is nothing to fix
+    #[serde(skip)]
+    pub ok: &'static str,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub config: PathBuf,
+    /// findings about reading the file, a config which failed to load has no groups
+    pub loading: Vec<Diagnostic>,
+    /// the settings which were taken from a `config.d` fragment
+    pub overlays: Vec<overlay::Source>,
+    pub groups: Vec<Group>,
+    /// findings about the system leftwm runs on rather than the config
+    pub environment: Vec<Diagnostic>,
+}
+
+impl Report {
+    /// The findings about the config, without those about the environment.
+    pub fn config_diagnostics(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.loading
+            .iter()
+            .chain(self.groups.iter().flat_map(|g| &g.diagnostics))
+    }
+
+    pub fn diagnostics(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.config_diagnostics().chain(&self.environment)
+    }
+
+    /// The most severe finding about the config, which sets the exit status of `check`.
+    /// The environment is left out, the same config is valid on a machine without elogind.
+    #[must_use]
+    pub fn worst(&self) -> Option<Severity> {
+        self.config_diagnostics().map(|d| d.severity).max()
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.config_diagnostics()
+            .filter(|d| d.severity == severity)
+            .count()
+    }
+
+    /// Prints the findings grouped by what was checked, after the version header which
+    /// [`print_header`] already printed.
+    pub fn print(&self) {
+        if diagnostic::worst(&self.loading) != Some(Severity::Error) {
+            println!("\x1b[0;92m    -> Configuration loaded OK \x1b[0m");
+        }
+        for diagnostic in &self.loading {
+            diagnostic.print();
+        }
+        if !self.overlays.is_empty() {
+            println!("\x1b[0;94m::\x1b[0m Merging overlays . . .");
+            let width = self
+                .overlays
+                .iter()
+                .map(|s| s.setting.len())
+                .max()
+                .unwrap_or(0);
+            for source in &self.overlays {
+                println!(
+                    "    {:width$} from {}",
+                    source.setting,
+                    source.fragment.display()
+                );
+            }
+            println!(
+                "\x1b[0;92m    -> All other values come from {}\x1b[0m",
+                self.config.display()
+            );
+        }
+        for group in &self.groups {
+            print_group(group.name, group.ok, &group.diagnostics);
+        }
+        print_group("environment", "Environment OK", &self.environment);
+        println!(
+            "\x1b[0;94m::\x1b[0m {} errors, {} warnings in the config",
+            self.count(Severity::Error),
+            self.count(Severity::Warning)
+        );
+    }
+
+    /// The report along with the version of leftwm-config and a count per severity.
+    pub fn to_json(&self) -> Result<String> {
+        #[derive(Serialize)]
+        struct Output<'r> {
+            version: &'static str,
+            git_hash: &'static str,
+            #[serde(flatten)]
+            report: &'r Report,
+            errors: usize,
+            warnings: usize,
+        }
+        Ok(serde_json::to_string_pretty(&Output {
+            version: VERSION,
+            git_hash: git_hash(),
+            report: self,
+            errors: self.count(Severity::Error),
+            warnings: self.count(Severity::Warning),
+        })?)
+    }
+
+    /// The report as a SARIF 2.1.0 log with a single run.
+    pub fn to_sarif(&self) -> Result<String> {
+        let mut codes: Vec<&str> = self.diagnostics().map(|d| d.code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        let rules: Vec<Value> = codes.iter().map(|code| json!({ "id": code })).collect();
+        let results: Vec<Value> = self
+            .diagnostics()
+            .map(|d| sarif_result(d, &self.config))
+            .collect();
+        let log = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "leftwm-config",
+                        "version": VERSION,
+                        "informationUri": INFORMATION_URI,
+                        "rules": rules,
+                    }
+                },
+                "invocations": [{ "executionSuccessful": true }],
+                "results": results,
+            }]
+        });
+        Ok(serde_json::to_string_pretty(&log)?)
+    }
+}
+
+/// Prints the version of leftwm-config, before the checks are run.
+pub fn print_header() {
+    println!("\x1b[0;94m::\x1b[0m LeftWM Config version: {VERSION}");
+    println!("\x1b[0;94m::\x1b[0m LeftWM git hash: {}", git_hash());
+}
+
+/// Prints the findings of one group of checks, or `ok` if there is nothing to fix.
+fn print_group(title: &str, ok: &str, diagnostics: &[Diagnostic]) {
+    println!("\x1b[0;94m::\x1b[0m Checking {title} . . .");
+    if diagnostics.iter().all(|d| d.severity == Severity::Info) {
+        println!("\x1b[0;92m    -> {ok}\x1b[0m");
+    }
+    for diagnostic in diagnostics {
+        diagnostic.print();
+    }
+}
+
+/// A SARIF result, settings without a known line point at the config as a whole and
+/// findings about the environment have no location.
+fn sarif_result(diagnostic: &Diagnostic, config: &Path) -> Value {
+    let level = match diagnostic.severity {
+        Severity::Info => "note",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    };
+    let mut result = json!({
+        "ruleId": diagnostic.code,
+        "level": level,
+        "message": { "text": diagnostic.message },
+    });
+    let physical = match &diagnostic.location {
+        Some(location) => Some(json!({
+            "artifactLocation": { "uri": uri(&location.file) },
+            "region": {
+                "startLine": location.line,
+                "startColumn": location.column,
+                "endColumn": location.column + location.length,
+            },
+        })),
+        None if !diagnostic.path.is_empty() => Some(json!({
+            "artifactLocation": { "uri": uri(config) },
+        })),
+        None => None,
+    };
+    if let Some(physical) = physical {
+        let mut location = json!({ "physicalLocation": physical });
+        if !diagnostic.path.is_empty() {
+            location["logicalLocations"] = json!([{ "fullyQualifiedName": diagnostic.path }]);
+        }
+        result["locations"] = json!([location]);
+    }
+    result
+}
+
+fn uri(file: &Path) -> String {
+    if file.is_absolute() {
+        format!("file://{}", file.display())
+    } else {
+        file.display().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::location::Location;
+
+    fn report(
+        loading: Vec<Diagnostic>,
+        group: Vec<Diagnostic>,
+        environment: Vec<Diagnostic>,
+    ) -> Report {
+        Report {
+            config: PathBuf::from("config.ron"),
+            loading,
+            overlays: vec![],
+            groups: vec![Group {
+                name: "keybinds",
+                ok: "All keybinds OK",
+                diagnostics: group,
+            }],
+            environment,
+        }
+    }
+
+    #[test]
+    fn worst_covers_loading_and_groups() {
+        assert_eq!(report(vec![], vec![], vec![]).worst(), None);
+        let warning = Diagnostic::warning("LW601", "keybind[0]", "");
+        let error = Diagnostic::error("LW001", "", "");
+        assert_eq!(
+            report(vec![], vec![warning.clone()], vec![]).worst(),
+            Some(Severity::Warning)
+        );
+        assert_eq!(
+            report(vec![error], vec![warning], vec![]).worst(),
+            Some(Severity::Error)
+        );
+    }
+
+    #[test]
+    fn worst_leaves_out_the_environment() {
+        let elogind = Diagnostic::error("LW701", "", "");
+        assert_eq!(report(vec![], vec![], vec![elogind]).worst(), None);
+    }
+
+    /// A report with a located error, a warning without a location and an environment note.
+    fn sample() -> Report {
+        let mut error = Diagnostic::error("LW602", "keybind[1].key", "Unknown key `Retrun`");
+        error.location = Some(Location {
+            file: PathBuf::from("/home/user/.config/leftwm/config.ron"),
+            line: 12,
+            column: 58,
+            line_text: String::new(),
+            length: 8,
+        });
+        let warning = Diagnostic::warning("LW105", "mousekey", "Your mousekey is set to nothing");
+        let elogind = Diagnostic::warning("LW701", "", "elogind is not running");
+        report(vec![], vec![error, warning], vec![elogind])
+    }
+
+    #[test]
+    fn json_lists_groups_and_counts() {
+        let mut json: Value = serde_json::from_str(&sample().to_json().unwrap()).unwrap();
+        assert_eq!(json["version"], VERSION);
+        assert!(json["git_hash"].is_string());
+        json.as_object_mut().unwrap().remove("git_hash");
+        json.as_object_mut().unwrap().remove("version");
+        assert_eq!(
+            json,
+            json!({
+                "config": "config.ron",
+                "loading": [],
+                "overlays": [],
+                "groups": [{
+                    "name": "keybinds",
+                    "diagnostics": [
+                        {
+                            "severity": "error",
+                            "code": "LW602",
+                            "path": "keybind[1].key",
+                            "message": "Unknown key `Retrun`",
+                            "location": {
+                                "file": "/home/user/.config/leftwm/config.ron",
+                                "line": 12,
+                                "column": 58,
+                                "length": 8,
+                            },
+                        },
+                        {
+                            "severity": "warning",
+                            "code": "LW105",
+                            "path": "mousekey",
+                            "message": "Your mousekey is set to nothing",
+                            "location": null,
+                        },
+                    ],
+                }],
+                "environment": [{
+                    "severity": "warning",
+                    "code": "LW701",
+                    "path": "",
+                    "message": "elogind is not running",
+                    "location": null,
+                }],
+                "errors": 1,
+                "warnings": 1,
+            })
+        );
+    }
+
+    #[test]
+    fn sarif_has_one_run_with_rules_and_results() {
+        let sarif: Value = serde_json::from_str(&sample().to_sarif().unwrap()).unwrap();
+        assert_eq!(sarif["version"], "2.1.0");
+        let runs = sarif["runs"].as_array().unwrap();
+        assert_eq!(runs.len(), 1);
+        let driver = &runs[0]["tool"]["driver"];
+        assert_eq!(driver["name"], "leftwm-config");
+        assert_eq!(
+            driver["rules"],
+            json!([{ "id": "LW105" }, { "id": "LW602" }, { "id": "LW701" }])
+        );
+        assert_eq!(
+            runs[0]["results"],
+            json!([
+                {
+                    "ruleId": "LW602",
+                    "level": "error",
+                    "message": { "text": "Unknown key `Retrun`" },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": {
+                                "uri": "file:///home/user/.config/leftwm/config.ron",
+                            },
+                            "region": { "startLine": 12, "startColumn": 58, "endColumn": 66 },
+                        },
+                        "logicalLocations": [{ "fullyQualifiedName": "keybind[1].key" }],
+                    }],
+                },
+                {
+                    "ruleId": "LW105",
+                    "level": "warning",
+                    "message": { "text": "Your mousekey is set to nothing" },
+                    "locations": [{
+                        "physicalLocation": { "artifactLocation": { "uri": "config.ron" } },
+                        "logicalLocations": [{ "fullyQualifiedName": "mousekey" }],
+                    }],
+                },
+                {
+                    "ruleId": "LW701",
+                    "level": "warning",
+                    "message": { "text": "elogind is not running" },
+                },
+            ])
+        );
+    }
+}
@@ -1,5 +1,6 @@
-use crate::config::layout::Layout;
-use crate::config::values::Size;
+use crate::config::layout::LayoutName;
+use crate::config::unknown::UnknownFields;
+use crate::config::values::{Size, WindowType};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Default, Deserialize, Debug, Clone, PartialEq)]
@@ -10,7 +11,9 @@ pub struct Workspace {
     pub width: i32,
     pub id: Option<i32>,
     pub max_window_width: Option<Size>,
-    pub layouts: Option<Vec<Layout>>,
+    pub layouts: Option<Vec<LayoutName>>,
+    #[serde(skip)]
+    pub unknown_fields: UnknownFields,
 }
 
 #[derive(Serialize, Default, Deserialize, Debug, Clone, PartialEq)]
@@ -25,10 +28,12 @@ pub struct ScratchPad {
     pub height: Option<Size>,
     // relative width of scratchpad, 50 means 50% of workspace width
     pub width: Option<Size>,
+    #[serde(skip)]
+    pub unknown_fields: UnknownFields,
 }
 
-/// Selecting by `WM_CLASS` and/or window title, allow the user to define if a
-/// window should spawn on a specified tag and/or its floating state.
+/// Selecting by `WM_CLASS` and/or window title, allow the user to define where a
+/// window should spawn, its type and its floating, sticky and fullscreen state.
 ///
 /// # Example
 ///
@@ -49,5 +54,103 @@ pub struct WindowHook {
     /// `_NET_WM_NAME` in X11
     pub window_title: Option<String>,
     pub spawn_on_tag: Option<usize>,
+    /// id of the workspace the window is moved to
+    pub spawn_on_workspace: Option<i32>,
     pub spawn_floating: Option<bool>,
+    pub spawn_sticky: Option<bool>,
+    pub spawn_fullscreen: Option<bool>,
+    pub spawn_as_type: Option<WindowType>,
+    /// only used when the window spawns floating
+    pub spawn_floating_geometry: Option<FloatingGeometry>,
+    #[serde(skip)]
+    pub unknown_fields: UnknownFields,
+}
+
+/// Position and size of a floating window, relative to its workspace.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloatingGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::filehandler;
+
+    #[test]
+    fn window_rules_read_every_leftwm_option() {
+        let ron = r#"#![enable(implicit_some)]
+(window_rules: [(
+    window_class: "krita",
+    window_title: "Krita",
+    spawn_on_tag: 3,
+    spawn_on_workspace: 1,
+    spawn_floating: true,
+    spawn_sticky: false,
+    spawn_fullscreen: false,
+    spawn_as_type: Dialog,
+    spawn_floating_geometry: (x: 10, y: 20, width: 800, height: 600),
+)])"#;
+        let toml = r#"
+[[window_rules]]
+window_class = "krita"
+window_title = "Krita"
+spawn_on_tag = 3
+spawn_on_workspace = 1
+spawn_floating = true
+spawn_sticky = false
+spawn_fullscreen = false
+spawn_as_type = "Dialog"
+spawn_floating_geometry = { x = 10, y = 20, width = 800, height = 600 }
+"#;
+        for config in [
+            filehandler::deserialize(ron, true).unwrap(),
+            filehandler::deserialize(toml, false).unwrap(),
+        ] {
+            let rule = &config.window_rules.unwrap()[0];
+            assert_eq!(rule.window_class.as_deref(), Some("krita"));
+            assert_eq!(rule.window_title.as_deref(), Some("Krita"));
+            assert_eq!(rule.spawn_on_tag, Some(3));
+            assert_eq!(rule.spawn_on_workspace, Some(1));
+            assert_eq!(rule.spawn_floating, Some(true));
+            assert_eq!(rule.spawn_sticky, Some(false));
+            assert_eq!(rule.spawn_fullscreen, Some(false));
+            assert_eq!(rule.spawn_as_type, Some(WindowType::Dialog));
+            assert_eq!(
+                rule.spawn_floating_geometry,
+                Some(FloatingGeometry {
+                    x: 10,
+                    y: 20,
+                    width: 800,
+                    height: 600,
+                })
+            );
+            assert!(rule.unknown_fields.get("spawn_as_type").is_none());
+        }
+    }
+
+    #[test]
+    fn window_rules_round_trip_with_unset_options() {
+        let rule = WindowHook {
+            window_class: Some("mpv".to_string()),
+            spawn_floating: Some(true),
+            ..WindowHook::default()
+        };
+        let written = ron::ser::to_string_pretty(
+            &rule,
+            ron::ser::PrettyConfig::new().extensions(ron::extensions::Extensions::IMPLICIT_SOME),
+        )
+        .unwrap();
+        let read: WindowHook = ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(&written)
+            .unwrap();
+        assert_eq!(read.window_class.as_deref(), Some("mpv"));
+        assert_eq!(read.spawn_floating, Some(true));
+        assert_eq!(read.spawn_as_type, None);
+        assert_eq!(read.spawn_floating_geometry, None);
+    }
 }
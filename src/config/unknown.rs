@@ -0,0 +1,487 @@
+//! Keeps keys which leftwm understands but the `Config` structs don't model (yet).
+//!
+//! They are collected from the source text when a config is loaded and written back
+//! next to the serialized known fields, so saving from the TUI never drops them.
+//!
+//! A key is unknown when serde left it out after reading the config: the source is compared
+//! with the serialization of what was read, down to nested structs such as a rule's
+//! `spawn_floating_geometry`. Keys of list entries which can be added, removed and moved
+//! in the TUI are kept with the entry, all others with the config.
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::config::document::{self, Edit, Field, Node};
+use crate::config::keybind::Keybind;
+use crate::config::layout::LayoutDefinition;
+use crate::config::structs::{ScratchPad, WindowHook, Workspace};
+
+/// The lists whose entries keep their own unknown keys.
+const ENTRY_LISTS: &[&str] = &[
+    "workspaces",
+    "scratchpad",
+    "window_rules",
+    "keybind",
+    "layout_definitions",
+];
+
+/// The way from the struct holding the unknown fields to a nested struct.
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Field(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Unknown {
+    /// where the key is, empty for a key of the struct itself
+    path: Vec<Step>,
+    key: String,
+    /// RON source text
+    value: String,
+}
+
+/// Unmodelled `key: value` pairs of one struct and the structs nested in it, the value is
+/// kept as RON source text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UnknownFields(Vec<Unknown>);
+
+impl UnknownFields {
+    fn own(&self) -> impl Iterator<Item = &Unknown> {
+        self.0.iter().filter(|u| u.path.is_empty())
+    }
+
+    /// The RON source of the value of `key`.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.own().find(|u| u.key == key).map(|u| u.value.as_str())
+    }
+
+    /// Sets `key` to the RON source `value`, in place if the key is already there.
+    pub fn insert(&mut self, key: &str, value: &str) {
+        match self
+            .0
+            .iter_mut()
+            .find(|u| u.path.is_empty() && u.key == key)
+        {
+            Some(unknown) => value.clone_into(&mut unknown.value),
+            None => self.0.push(Unknown {
+                path: vec![],
+                key: key.to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+}
+
+pub trait HasUnknownFields: Serialize {
+    fn unknown_fields(&self) -> &UnknownFields;
+    fn unknown_fields_mut(&mut self) -> &mut UnknownFields;
+}
+
+macro_rules! impl_has_unknown_fields {
+    ($($ty:ty),*) => {$(
+        impl HasUnknownFields for $ty {
+            fn unknown_fields(&self) -> &UnknownFields {
+                &self.unknown_fields
+            }
+
+            fn unknown_fields_mut(&mut self) -> &mut UnknownFields {
+                &mut self.unknown_fields
+            }
+        }
+    )*};
+}
+
+impl_has_unknown_fields!(
+    Config,
+    Workspace,
+    ScratchPad,
+    WindowHook,
+    Keybind,
+    LayoutDefinition
+);
+
+/// Collects the fields of `written` which are missing from `known`, the serialization of
+/// the same value, and does the same for the values both have. `skip` names fields which
+/// are not looked into.
+fn diff_ron(
+    written: &Node,
+    known: &Node,
+    source: &str,
+    skip: &[&str],
+    path: &mut Vec<Step>,
+    found: &mut Vec<Unknown>,
+) {
+    let (written, known) = (written.unwrap_some(), known.unwrap_some());
+    if written.is_struct() && known.is_struct() {
+        for field in written.fields() {
+            if skip.contains(&field.name.as_str()) {
+                continue;
+            }
+            match known.field(&field.name) {
+                Some(known) => {
+                    path.push(Step::Field(field.name.clone()));
+                    diff_ron(&field.value, &known.value, source, &[], path, found);
+                    path.pop();
+                }
+                None => found.push(Unknown {
+                    path: path.clone(),
+                    key: field.name.clone(),
+                    value: field.value.text(source).to_string(),
+                }),
+            }
+        }
+    } else {
+        for (i, (written, known)) in written.items().iter().zip(known.items()).enumerate() {
+            path.push(Step::Index(i));
+            diff_ron(written, known, source, &[], path, found);
+            path.pop();
+        }
+    }
+}
+
+fn from_ron_node<T: HasUnknownFields>(
+    item: &mut T,
+    node: &Node,
+    source: &str,
+    skip: &[&str],
+) -> Result<()> {
+    let known = ron::to_string(item)?;
+    let known = document::parse(&known)?;
+    let mut found = Vec::new();
+    diff_ron(node, &known, source, skip, &mut vec![], &mut found);
+    *item.unknown_fields_mut() = UnknownFields(found);
+    Ok(())
+}
+
+fn from_ron_seq<T: HasUnknownFields>(
+    items: Option<&mut Vec<T>>,
+    field: Option<&Field>,
+    source: &str,
+) -> Result<()> {
+    if let (Some(items), Some(field)) = (items, field) {
+        for (item, node) in items.iter_mut().zip(field.value.unwrap_some().items()) {
+            from_ron_node(item, node, source, &[])?;
+        }
+    }
+    Ok(())
+}
+
+/// Collects unknown keys of the config and of its workspaces, scratchpads, window rules,
+/// keybinds and layout definitions from the RON source the config was deserialized from.
+pub fn capture_ron(config: &mut Config, source: &str) -> Result<()> {
+    let root = document::parse(source).context("could not read unknown config keys")?;
+    from_ron_node(config, &root, source, ENTRY_LISTS)?;
+    from_ron_seq(config.workspaces.as_mut(), root.field("workspaces"), source)?;
+    from_ron_seq(config.scratchpad.as_mut(), root.field("scratchpad"), source)?;
+    from_ron_seq(
+        config.window_rules.as_mut(),
+        root.field("window_rules"),
+        source,
+    )?;
+    from_ron_seq(Some(&mut config.keybind), root.field("keybind"), source)?;
+    from_ron_seq(
+        Some(&mut config.layout_definitions),
+        root.field("layout_definitions"),
+        source,
+    )?;
+    Ok(())
+}
+
+/// Same as [`diff_ron`] for a TOML source.
+fn diff_toml(
+    written: &toml::Value,
+    known: &toml::Value,
+    skip: &[&str],
+    path: &mut Vec<Step>,
+    found: &mut Vec<Unknown>,
+) -> Result<()> {
+    match (written, known) {
+        (toml::Value::Table(written), toml::Value::Table(known)) => {
+            for (key, value) in written {
+                if skip.contains(&key.as_str()) {
+                    continue;
+                }
+                match known.get(key) {
+                    Some(known) => {
+                        path.push(Step::Field(key.clone()));
+                        diff_toml(value, known, &[], path, found)?;
+                        path.pop();
+                    }
+                    None => found.push(Unknown {
+                        path: path.clone(),
+                        key: key.clone(),
+                        value: toml_to_ron(value)?,
+                    }),
+                }
+            }
+        }
+        (toml::Value::Array(written), toml::Value::Array(known)) => {
+            for (i, (written, known)) in written.iter().zip(known).enumerate() {
+                path.push(Step::Index(i));
+                diff_toml(written, known, &[], path, found)?;
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn from_toml_value<T: HasUnknownFields>(
+    item: &mut T,
+    value: &toml::Value,
+    skip: &[&str],
+) -> Result<()> {
+    let known = toml::Value::try_from(&*item)?;
+    let mut found = Vec::new();
+    diff_toml(value, &known, skip, &mut vec![], &mut found)?;
+    *item.unknown_fields_mut() = UnknownFields(found);
+    Ok(())
+}
+
+fn from_toml_array<T: HasUnknownFields>(
+    items: Option<&mut Vec<T>>,
+    value: Option<&toml::Value>,
+) -> Result<()> {
+    if let (Some(items), Some(toml::Value::Array(values))) = (items, value) {
+        for (item, value) in items.iter_mut().zip(values) {
+            from_toml_value(item, value, &[])?;
+        }
+    }
+    Ok(())
+}
+
+/// Same as [`capture_ron`] for legacy TOML configs. The values are converted to RON,
+/// tables become structs, so TOML strings meant as enum variants stay strings.
+pub fn capture_toml(config: &mut Config, source: &str) -> Result<()> {
+    let root: toml::Table = toml::from_str(source)?;
+    let root = toml::Value::Table(root);
+    from_toml_value(config, &root, ENTRY_LISTS)?;
+    from_toml_array(config.workspaces.as_mut(), root.get("workspaces"))?;
+    from_toml_array(config.scratchpad.as_mut(), root.get("scratchpad"))?;
+    from_toml_array(config.window_rules.as_mut(), root.get("window_rules"))?;
+    from_toml_array(Some(&mut config.keybind), root.get("keybind"))?;
+    from_toml_array(
+        Some(&mut config.layout_definitions),
+        root.get("layout_definitions"),
+    )?;
+    Ok(())
+}
+
+fn toml_to_ron(value: &toml::Value) -> Result<String> {
+    Ok(match value {
+        toml::Value::String(s) => ron::to_string(s)?,
+        toml::Value::Integer(i) => i.to_string(),
+        toml::Value::Float(f) => ron::to_string(f)?,
+        toml::Value::Boolean(b) => b.to_string(),
+        toml::Value::Datetime(d) => ron::to_string(&d.to_string())?,
+        toml::Value::Array(values) => format!(
+            "[{}]",
+            values
+                .iter()
+                .map(toml_to_ron)
+                .collect::<Result<Vec<_>>>()?
+                .join(", ")
+        ),
+        toml::Value::Table(table) => format!(
+            "({})",
+            table
+                .iter()
+                .map(|(key, value)| Ok(format!("{key}: {}", toml_to_ron(value)?)))
+                .collect::<Result<Vec<_>>>()?
+                .join(", ")
+        ),
+    })
+}
+
+/// The struct `path` leads to from `node`, if it was serialized.
+fn follow<'n>(node: &'n Node, path: &[Step]) -> Option<&'n Node> {
+    path.iter().try_fold(node.unwrap_some(), |node, step| {
+        let next = match step {
+            Step::Field(name) => &node.field(name)?.value,
+            Step::Index(i) => node.items().get(*i)?,
+        };
+        Some(next.unwrap_some())
+    })
+}
+
+/// Inserts the unknown keys of `item` into `node`, its serialization. Keys which were
+/// modelled since, and so are serialized now, are left out.
+fn restore_node<T: HasUnknownFields>(item: &T, node: &Node, source: &str, edits: &mut Vec<Edit>) {
+    let unknown = &item.unknown_fields().0;
+    let mut paths: Vec<&[Step]> = Vec::new();
+    for u in unknown {
+        if !paths.contains(&u.path.as_slice()) {
+            paths.push(&u.path);
+        }
+    }
+    for path in paths {
+        let Some(target) = follow(node, path) else {
+            continue;
+        };
+        let entries: Vec<String> = unknown
+            .iter()
+            .filter(|u| u.path == path && target.field(&u.key).is_none())
+            .map(|u| format!("{}: {}", u.key, u.value))
+            .collect();
+        edits.extend(document::field_insertion(target, source, &entries));
+    }
+}
+
+fn restore_seq<T: HasUnknownFields>(
+    items: Option<&Vec<T>>,
+    field: Option<&Field>,
+    source: &str,
+    edits: &mut Vec<Edit>,
+) {
+    if let (Some(items), Some(field)) = (items, field) {
+        for (item, node) in items.iter().zip(field.value.unwrap_some().items()) {
+            restore_node(item, node, source, edits);
+        }
+    }
+}
+
+/// Adds the unknown keys held by `config` back into its serialized form.
+pub fn restore(config: &Config, serialized: &str) -> Result<String> {
+    let root = document::parse(serialized)?;
+    let mut edits: Vec<Edit> = Vec::new();
+    restore_node(config, &root, serialized, &mut edits);
+    restore_seq(
+        config.workspaces.as_ref(),
+        root.field("workspaces"),
+        serialized,
+        &mut edits,
+    );
+    restore_seq(
+        config.scratchpad.as_ref(),
+        root.field("scratchpad"),
+        serialized,
+        &mut edits,
+    );
+    restore_seq(
+        config.window_rules.as_ref(),
+        root.field("window_rules"),
+        serialized,
+        &mut edits,
+    );
+    restore_seq(
+        Some(&config.keybind),
+        root.field("keybind"),
+        serialized,
+        &mut edits,
+    );
+    restore_seq(
+        Some(&config.layout_definitions),
+        root.field("layout_definitions"),
+        serialized,
+        &mut edits,
+    );
+    Ok(document::apply_edits(serialized, edits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::filehandler;
+
+    const RON: &str = r#"#![enable(implicit_some)]
+(
+    modkey: "Mod4",
+    future_option: [1, 2],
+    keybind: [
+        (command: Execute, value: "st", modifier: ["modkey"], key: "Return", repeat: true),
+    ],
+    window_rules: [
+        (
+            window_class: "krita",
+            spawn_floating_geometry: (x: 1, y: 2, width: 3, height: 4, centered: true),
+        ),
+    ],
+    layout_definitions: [
+        (name: "Custom", columns: (main: (count: 2, weight: 0.7))),
+    ],
+)
+"#;
+
+    fn assert_kept(config: &Config) {
+        assert_eq!(config.unknown_fields.get("future_option"), Some("[1, 2]"));
+        assert_eq!(config.keybind[0].unknown_fields.get("repeat"), Some("true"));
+        let rule = &config.window_rules.as_ref().unwrap()[0].unknown_fields;
+        assert_eq!(rule.get("centered"), None);
+        assert_eq!(
+            rule.0,
+            vec![Unknown {
+                path: vec![Step::Field("spawn_floating_geometry".to_string())],
+                key: "centered".to_string(),
+                value: "true".to_string(),
+            }]
+        );
+        assert_eq!(
+            config.layout_definitions[0].unknown_fields.0,
+            vec![Unknown {
+                path: vec![
+                    Step::Field("columns".to_string()),
+                    Step::Field("main".to_string())
+                ],
+                key: "weight".to_string(),
+                value: "0.7".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn capture_ron_finds_nested_unknown_fields() {
+        let config = filehandler::deserialize(RON, true).unwrap();
+        assert_kept(&config);
+    }
+
+    #[test]
+    fn capture_toml_finds_nested_unknown_fields() {
+        let toml = r#"
+modkey = "Mod4"
+future_option = [1, 2]
+
+[[keybind]]
+command = "Execute"
+value = "st"
+modifier = ["modkey"]
+key = "Return"
+repeat = true
+
+[[window_rules]]
+window_class = "krita"
+spawn_floating_geometry = { x = 1, y = 2, width = 3, height = 4, centered = true }
+
+[[layout_definitions]]
+name = "Custom"
+columns = { main = { count = 2, weight = 0.7 } }
+"#;
+        let config = filehandler::deserialize(toml, false).unwrap();
+        assert_kept(&config);
+    }
+
+    #[test]
+    fn restore_writes_unknown_fields_back_in_place() {
+        let config = filehandler::deserialize(RON, true).unwrap();
+        let written = filehandler::to_ron(&config, true).unwrap();
+        let reread = filehandler::deserialize(&written, true).unwrap();
+        assert_kept(&reread);
+        assert_eq!(
+            filehandler::to_ron(&reread, true).unwrap(),
+            written,
+            "a second round trip changes nothing"
+        );
+    }
+
+    #[test]
+    fn restore_skips_keys_which_are_serialized() {
+        let mut config = Config::default();
+        config.unknown_fields.insert("modkey", "\"Mod1\"");
+        config.unknown_fields.insert("future_option", "1");
+        let written = filehandler::to_ron(&config, true).unwrap();
+        assert_eq!(written.matches("modkey:").count(), 1);
+        assert_eq!(written.matches("future_option: 1").count(), 1);
+    }
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::os::raw::c_ulong;
+use std::str::FromStr;
+use thiserror::Error;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Copy)]
 #[serde(untagged)]
@@ -8,6 +11,69 @@ pub enum Size {
     Ratio(f32),
 }
 
+#[derive(Debug, Error, PartialEq)]
+pub enum ParseSizeError {
+    #[error("`{0}` is not a size, use pixels (`800`, `800px`) or a ratio (`0.5`, `50%`)")]
+    Invalid(String),
+    #[error("a ratio must be between 0 and 1, found `{0}`")]
+    RatioOutOfRange(String),
+}
+
+/// Accepts pixels as `800` or `800px` and ratios as `0.5` or `50%`.
+impl FromStr for Size {
+    type Err = ParseSizeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || ParseSizeError::Invalid(s.to_string());
+        // a decimal comma is accepted as well
+        let decimal = |d: &str| {
+            d.trim()
+                .replace(',', ".")
+                .parse::<f32>()
+                .map_err(|_| invalid())
+        };
+        let ratio = if let Some(percent) = s.strip_suffix('%') {
+            decimal(percent)? / 100.0
+        } else if let Some(pixels) = s.strip_suffix("px") {
+            return pixels
+                .trim()
+                .parse()
+                .map(Self::Pixel)
+                .map_err(|_| invalid());
+        } else if let Ok(pixels) = s.parse() {
+            return Ok(Self::Pixel(pixels));
+        } else {
+            decimal(s)?
+        };
+        if !(0.0..=1.0).contains(&ratio) {
+            return Err(ParseSizeError::RatioOutOfRange(s.to_string()));
+        }
+        Ok(Self::Ratio(ratio))
+    }
+}
+
+/// Writes the size so that it parses back to the same value.
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pixel(p) => write!(f, "{p}px"),
+            // `{r:?}` keeps the decimal point, `1` would read back as pixels
+            Self::Ratio(r) => write!(f, "{r:?}"),
+        }
+    }
+}
+
+impl Size {
+    /// The unit in effect, for showing next to an input.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Pixel(p) => format!("{p} pixels"),
+            Self::Ratio(r) => format!("ratio {r} ({}% of the space)", (r * 1000.0).round() / 10.0),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LayoutMode {
     Tag,
@@ -30,6 +96,44 @@ pub enum FocusBehaviour {
     Driven,
 }
 
+/// The `_NET_WM_WINDOW_TYPE` a window rule can force onto a window.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowType {
+    Desktop,
+    Dock,
+    Toolbar,
+    Menu,
+    Utility,
+    Splash,
+    Dialog,
+    DropDownMenu,
+    PopupMenu,
+    Tooltip,
+    Notification,
+    Combo,
+    Dnd,
+    Normal,
+}
+
+impl WindowType {
+    pub const ALL: [Self; 14] = [
+        Self::Desktop,
+        Self::Dock,
+        Self::Toolbar,
+        Self::Menu,
+        Self::Utility,
+        Self::Splash,
+        Self::Dialog,
+        Self::DropDownMenu,
+        Self::PopupMenu,
+        Self::Tooltip,
+        Self::Notification,
+        Self::Combo,
+        Self::Dnd,
+        Self::Normal,
+    ];
+}
+
 pub type Window = c_ulong;
 type MockHandle = i32;
 
@@ -38,3 +142,51 @@ pub enum WindowHandle {
     MockHandle(MockHandle),
     XlibHandle(Window),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_parses_pixels() {
+        assert_eq!("800".parse(), Ok(Size::Pixel(800)));
+        assert_eq!(" 800px ".parse(), Ok(Size::Pixel(800)));
+        assert_eq!("800 px".parse(), Ok(Size::Pixel(800)));
+        assert_eq!("-20".parse(), Ok(Size::Pixel(-20)));
+    }
+
+    #[test]
+    fn size_parses_ratios() {
+        assert_eq!("0.5".parse(), Ok(Size::Ratio(0.5)));
+        assert_eq!("0,25".parse(), Ok(Size::Ratio(0.25)));
+        assert_eq!("50%".parse(), Ok(Size::Ratio(0.5)));
+        assert_eq!("1.0".parse(), Ok(Size::Ratio(1.0)));
+    }
+
+    #[test]
+    fn size_rejects_invalid_input() {
+        assert_eq!(
+            "wide".parse::<Size>(),
+            Err(ParseSizeError::Invalid("wide".to_string()))
+        );
+        assert_eq!(
+            "0.5px".parse::<Size>(),
+            Err(ParseSizeError::Invalid("0.5px".to_string()))
+        );
+        assert_eq!(
+            "1.5".parse::<Size>(),
+            Err(ParseSizeError::RatioOutOfRange("1.5".to_string()))
+        );
+        assert_eq!(
+            "150%".parse::<Size>(),
+            Err(ParseSizeError::RatioOutOfRange("150%".to_string()))
+        );
+    }
+
+    #[test]
+    fn size_display_parses_back() {
+        for size in [Size::Pixel(800), Size::Ratio(1.0), Size::Ratio(0.3)] {
+            assert_eq!(size.to_string().parse(), Ok(size));
+        }
+    }
+}
@@ -0,0 +1,265 @@
+//! The questions `--new` asks before writing a config. The answers pick the modkey, the
+//! programs the keybinds start, the tags and the focus behaviour, installed programs are
+//! offered as choices.
+use std::io::{self, BufRead, Write};
+
+use anyhow::{Result, bail};
+
+use crate::config::detect::{self, Environment, Program};
+use crate::config::values::FocusBehaviour;
+use crate::config::{Config, default_keybinds};
+
+/// Tags are switched with modkey+1 to modkey+9.
+const MAX_TAGS: usize = 9;
+
+/// Asks for every choice and builds the config from the answers.
+pub fn run() -> Result<Config> {
+    run_with(&mut io::stdin().lock(), &Environment::detect())
+}
+
+/// [`run`] with the answers read from `input`, offering the programs of `environment`.
+fn run_with(input: &mut impl BufRead, environment: &Environment) -> Result<Config> {
+    println!(
+        "\x1b[0;94m::\x1b[0m Setting up a new config, press Enter to take the answer in brackets"
+    );
+    let modkey = choose(
+        input,
+        "Which key should be the modkey, used by every keybind?",
+        &[("Super (the Windows key)", "Mod4"), ("Alt", "Mod1")],
+    )?;
+    let terminal = choose_program(
+        input,
+        "Which terminal should modkey+Shift+Return open?",
+        detect::TERMINALS,
+        &environment.terminals,
+        Some(detect::FALLBACK_TERMINAL),
+    )?
+    .unwrap_or_default();
+    let launcher = choose_program(
+        input,
+        "Which launcher should modkey+p open?",
+        detect::LAUNCHERS,
+        &environment.launchers,
+        Some(detect::FALLBACK_LAUNCHER),
+    )?
+    .unwrap_or_default();
+    // a locker is optional, without one installed not binding one is suggested
+    let locker = choose_program(
+        input,
+        "Which screen locker should modkey+Control+l start?",
+        detect::LOCKERS,
+        &environment.lockers,
+        None,
+    )?;
+    let tags = ask_tags(input)?;
+    let focus_behaviour = choose(
+        input,
+        "How should windows get focus?",
+        &[
+            (
+                "Sloppy, the window under the mouse is focused",
+                FocusBehaviour::Sloppy,
+            ),
+            (
+                "ClickTo, a window is focused by clicking it",
+                FocusBehaviour::ClickTo,
+            ),
+            (
+                "Driven, only keybinds change the focus",
+                FocusBehaviour::Driven,
+            ),
+        ],
+    )?;
+
+    Ok(Config {
+        modkey: modkey.into(),
+        mousekey: Some(modkey.into()),
+        keybind: default_keybinds(
+            &terminal,
+            &launcher,
+            locker.as_deref(),
+            environment.exit_command(),
+            tags.len(),
+        ),
+        tags: Some(tags),
+        focus_behaviour,
+        ..Config::default()
+    })
+}
+
+/// Prints `question` and returns the trimmed answer, `default` if it is empty.
+fn ask(input: &mut impl BufRead, question: &str, default: &str) -> Result<String> {
+    print!("{question} [{default}] ");
+    io::stdout().flush()?;
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        bail!("The input ended before the config was set up");
+    }
+    let answer = line.trim();
+    Ok(if answer.is_empty() { default } else { answer }.to_string())
+}
+
+/// Lets the user pick one of `options` by number, the first one is the default.
+fn choose<T: Clone>(input: &mut impl BufRead, question: &str, options: &[(&str, T)]) -> Result<T> {
+    println!("{question}");
+    for (i, (label, _)) in options.iter().enumerate() {
+        println!("    {}) {label}", i + 1);
+    }
+    loop {
+        let answer = ask(input, "Number:", "1")?;
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=options.len()).contains(&n) => return Ok(options[n - 1].1.clone()),
+            _ => println!(
+                "\x1b[1;91mERROR: Enter a number from 1 to {}\x1b[0m",
+                options.len()
+            ),
+        }
+    }
+}
+
+/// Offers the `installed` programs out of `known`, any other command can be typed in.
+/// The first installed program is suggested, else `fallback`. Without a fallback the user
+/// can also choose to have none.
+fn choose_program(
+    input: &mut impl BufRead,
+    question: &str,
+    known: &[Program],
+    installed: &[Program],
+    fallback: Option<&str>,
+) -> Result<Option<String>> {
+    println!("{question}");
+    if installed.is_empty() {
+        let names: Vec<&str> = known.iter().map(|p| p.name).collect();
+        println!("    None of {} is installed", names.join(", "));
+    }
+    for (i, program) in installed.iter().enumerate() {
+        println!("    {}) {}", i + 1, program.command);
+    }
+    let none = installed.len() + 1;
+    if fallback.is_none() {
+        println!("    {none}) none");
+    }
+    let default = installed
+        .first()
+        .map(|p| p.command)
+        .or(fallback)
+        .map_or(none.to_string(), str::to_string);
+    loop {
+        let answer = ask(input, "Number or another command:", &default)?;
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=installed.len()).contains(&n) => {
+                return Ok(Some(installed[n - 1].command.to_string()));
+            }
+            Ok(n) if fallback.is_none() && n == none => return Ok(None),
+            Ok(_) => println!("\x1b[1;91mERROR: There is no choice {answer}\x1b[0m"),
+            Err(_) => return Ok(Some(answer)),
+        }
+    }
+}
+
+fn ask_tags(input: &mut impl BufRead) -> Result<Vec<String>> {
+    let count = loop {
+        let answer = ask(
+            input,
+            &format!("How many tags do you want (1 to {MAX_TAGS})?"),
+            &MAX_TAGS.to_string(),
+        )?;
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=MAX_TAGS).contains(&n) => break n,
+            _ => println!("\x1b[1;91mERROR: Enter a number from 1 to {MAX_TAGS}\x1b[0m"),
+        }
+    };
+    let numbers: Vec<String> = (1..=count).map(|i| i.to_string()).collect();
+    loop {
+        let answer = ask(
+            input,
+            "Names for the tags, separated by commas",
+            &numbers.join(", "),
+        )?;
+        let names: Vec<String> = answer.split(',').map(|n| n.trim().to_string()).collect();
+        if names.len() == count && names.iter().all(|n| !n.is_empty()) {
+            return Ok(names);
+        }
+        println!("\x1b[1;91mERROR: Enter {count} names which are not empty\x1b[0m");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::command::BaseCommand;
+
+    fn answer(lines: &[&str], environment: &Environment) -> Result<Config> {
+        let input = lines
+            .iter()
+            .map(|line| format!("{line}\n"))
+            .collect::<String>();
+        run_with(&mut input.as_bytes(), environment)
+    }
+
+    fn executed(config: &Config) -> Vec<String> {
+        config
+            .keybind
+            .iter()
+            .filter(|k| k.command == BaseCommand::Execute)
+            .map(|k| k.value.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn defaults_use_the_fallback_programs() {
+        let config = answer(&[""; 7], &Environment::default()).unwrap();
+        assert_eq!(config.modkey, "Mod4".into());
+        assert_eq!(config.tags.as_ref().unwrap().len(), MAX_TAGS);
+        assert_eq!(config.focus_behaviour, FocusBehaviour::Sloppy);
+        let executed = executed(&config);
+        assert!(executed.iter().any(|e| e == detect::FALLBACK_TERMINAL));
+        assert!(executed.iter().any(|e| e == detect::FALLBACK_LAUNCHER));
+        assert!(!executed.iter().any(|e| e == detect::FALLBACK_LOCKER));
+    }
+
+    #[test]
+    fn answers_pick_installed_programs_tags_and_focus() {
+        let environment = Environment {
+            terminals: detect::TERMINALS[..2].to_vec(),
+            lockers: detect::LOCKERS[..1].to_vec(),
+            ..Environment::default()
+        };
+        let config = answer(
+            &["2", "2", "rofi -show run", "1", "3", "web, chat, mail", "3"],
+            &environment,
+        )
+        .unwrap();
+        assert_eq!(config.modkey, "Mod1".into());
+        assert_eq!(
+            config.tags,
+            Some(vec![
+                "web".to_string(),
+                "chat".to_string(),
+                "mail".to_string()
+            ])
+        );
+        assert_eq!(config.focus_behaviour, FocusBehaviour::Driven);
+        let executed = executed(&config);
+        assert!(executed.iter().any(|e| e == detect::TERMINALS[1].command));
+        assert!(executed.iter().any(|e| e == "rofi -show run"));
+        assert!(executed.iter().any(|e| e == detect::LOCKERS[0].command));
+    }
+
+    #[test]
+    fn invalid_answers_are_asked_again() {
+        let config = answer(
+            &["0", "x", "1", "", "", "", "12", "2", "a,", "a, b", ""],
+            &Environment::default(),
+        )
+        .unwrap();
+        assert_eq!(config.modkey, "Mod4".into());
+        assert_eq!(config.tags, Some(vec!["a".to_string(), "b".to_string()]));
+    }
+
+    #[test]
+    fn input_ending_early_is_an_error() {
+        assert!(answer(&["1", ""], &Environment::default()).is_err());
+    }
+}
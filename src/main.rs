@@ -12,13 +12,16 @@ mod tui;
 mod utils;
 
 use crate::config::check_config;
-use crate::config::filehandler::{load_from_file, write_to_file};
-use anyhow::Result;
+use crate::config::convert::Format;
+use crate::config::diagnostic::Severity;
+use crate::config::filehandler::load_from_file;
+use crate::config::report::OutputFormat;
+use anyhow::{Context, Result};
 use clap::{Arg, ArgAction, Command as ClapCmd};
-use std::path::Path;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::{env, fs, io};
-use xdg::BaseDirectories;
 
 #[cfg(debug_assertions)]
 const CONFIG_NAME: &str = "test_config";
@@ -26,10 +29,115 @@ const CONFIG_NAME: &str = "test_config";
 const CONFIG_NAME: &str = "config";
 
 fn main() -> Result<()> {
-    let matches = ClapCmd::new("LeftWM Command")
+    let matches = cli().get_matches();
+
+    let verbose = matches.get_flag("Verbose");
+
+    if let Some(path) = config_path(&matches, env::var_os("LEFTWM_CONFIG")) {
+        config::filehandler::set_config_path(path);
+    }
+    if let Some(name) = matches.get_one::<String>("Profile") {
+        config::profile::select(name.clone());
+    }
+
+    if let Some(migrate) = matches.subcommand_matches("migrate") {
+        if migrate.get_flag("ToLatest") {
+            println!("\x1b[0;94m::\x1b[0m Upgrading configuration . . .");
+            config::filehandler::migrate_to_latest(&config::filehandler::get_config_file()?)?;
+        } else {
+            migrate_toml_to_ron(verbose, migrate.get_flag("DryRun"))?;
+        }
+    } else if let Some(profile) = matches.subcommand_matches("profile") {
+        run_profile_command(profile)?;
+    } else if let Some(merge) = matches.subcommand_matches("merge") {
+        let file = config::filehandler::get_config_file()?;
+        let base = load_from_file(None, verbose)?;
+        let config::overlay::Merged {
+            config: merged,
+            sources,
+            unknown,
+        } = config::overlay::merge(&base, &file)?;
+        for key in &unknown {
+            eprintln!(
+                "\x1b[1;93mWARN: `{}` in {} is not a setting leftwm-config knows, it is kept as written.\x1b[0m",
+                key.setting,
+                key.fragment.display()
+            );
+        }
+        match merge.get_one::<PathBuf>("Output") {
+            Some(output) => {
+                config::backup::replace(output, &config::filehandler::with_header(&merged)?)?;
+                println!(
+                    "\x1b[0;92m    -> Merged {} overlay values into {}\x1b[0m",
+                    sources.len(),
+                    output.display()
+                );
+            }
+            None => print!("{}", config::filehandler::with_header(&merged)?),
+        }
+    } else if let Some(convert) = matches.subcommand_matches("convert") {
+        run_convert(convert)?;
+    } else if matches.subcommand_matches("detect").is_some() {
+        config::detect::Environment::detect().report();
+    } else if let Some(restore) = matches.subcommand_matches("restore") {
+        let file = config::filehandler::get_config_file()?;
+        match restore.get_one::<usize>("Backup") {
+            Some(number) => {
+                let taken = config::backup::restore(&file, *number)?;
+                println!("\x1b[0;92m    -> Restored the backup from {taken}\x1b[0m");
+            }
+            None => list_backups(&file)?,
+        }
+    } else if matches.get_flag("Migrate") {
+        migrate_toml_to_ron(verbose, false)?;
+    } else if matches.get_flag("Editor") {
+        run_editor(config::filehandler::get_config_file()?.as_path())?;
+    } else if matches.get_flag("TUI") {
+        crate::tui::run()?;
+    } else if matches.get_flag("New") {
+        config::filehandler::generate_new_config()?;
+    } else if matches.get_flag("Check") {
+        if matches.get_flag("Watch") {
+            let monitors = load_monitors(&matches)?;
+            config::watch_config(None, monitors.as_deref(), verbose)?;
+        } else {
+            // a check which could not run must not look like a config with warnings
+            let code = match run_check(&matches, verbose) {
+                Ok(worst) => Severity::exit_code(worst),
+                Err(e) => {
+                    eprintln!("Error: {e:?}");
+                    Severity::CHECK_FAILED
+                }
+            };
+            std::process::exit(code);
+        }
+    } else {
+        run_editor(config::filehandler::get_config_file()?.as_path())?;
+    }
+
+    Ok(())
+}
+
+fn cli() -> ClapCmd {
+    ClapCmd::new("LeftWM Command")
         .author("BlackDragon2447 <blackdragon2447@e.email>")
         .version(env!("CARGO_PKG_VERSION"))
         .about("a tool for managing your LeftWM config")
+        .arg(
+            Arg::new("Config")
+                .long("config")
+                .value_name("PATH")
+                .value_parser(clap::value_parser!(PathBuf))
+                .global(true)
+                .help("Use this config file instead of the one in $XDG_CONFIG_HOME/leftwm, defaults to $LEFTWM_CONFIG"),
+        )
+        .arg(
+            Arg::new("Profile")
+                .long("profile")
+                .value_name("NAME")
+                .global(true)
+                .help("Edit or check the profile NAME instead of the config leftwm reads"),
+        )
         .arg(
             Arg::new("New")
                 .short('n')
@@ -56,7 +164,30 @@ fn main() -> Result<()> {
                 .short('c')
                 .action(ArgAction::SetTrue)
                 .long("check")
-                .help("Check if the current config is valid"),
+                .help("Check if the current config is valid, exits with 1 on warnings, 2 on errors in the config and 3 if it could not be checked"),
+        )
+        .arg(
+            Arg::new("Watch")
+                .short('w')
+                .long("watch")
+                .action(ArgAction::SetTrue)
+                .requires("Check")
+                .help("With --check, check again whenever the config file changes"),
+        )
+        .arg(
+            Arg::new("Format")
+                .long("format")
+                .value_name("FORMAT")
+                .requires("Check")
+                .conflicts_with("Watch")
+                .help("With --check, print the findings as text (default), json or sarif"),
+        )
+        .arg(
+            Arg::new("Monitors")
+                .long("monitors")
+                .value_name("FILE")
+                .requires("Check")
+                .help("With --check, check the workspaces against the monitors in FILE, pass `xrandr` to use the output of `xrandr --query`"),
         )
         .arg(
             Arg::new("Verbose")
@@ -71,41 +202,344 @@ fn main() -> Result<()> {
                 .action(ArgAction::SetTrue)
                 .help("Migrate an old .toml config to the RON format."),
         )
-        .get_matches();
+        .subcommand(
+            ClapCmd::new("migrate")
+                .about("Migrate an old .toml config to the RON format")
+                .arg(
+                    Arg::new("ToLatest")
+                        .long("to-latest")
+                        .action(ArgAction::SetTrue)
+                        .help("Upgrade the current RON config to the latest schema version instead, TOML configs are upgraded by converting them"),
+                )
+                .arg(
+                    Arg::new("DryRun")
+                        .long("dry-run")
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with("ToLatest")
+                        .help("Print the RON config and how it differs from the existing one, write nothing"),
+                ),
+        )
+        .subcommand(
+            ClapCmd::new("merge")
+                .about("Write the config with the config.d/*.ron overlays next to it merged in")
+                .arg(
+                    Arg::new("Output")
+                        .short('o')
+                        .long("output")
+                        .value_name("PATH")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Write the merged config to PATH instead of stdout"),
+                ),
+        )
+        .subcommand(
+            ClapCmd::new("convert")
+                .about("Convert a config between RON, TOML and JSON")
+                .arg(
+                    Arg::new("Input")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("The config to convert, `-` for stdin, defaults to the current config"),
+                )
+                .arg(
+                    Arg::new("Output")
+                        .short('o')
+                        .long("output")
+                        .value_name("PATH")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .help("Write the converted config to PATH instead of stdout"),
+                )
+                .arg(
+                    Arg::new("From")
+                        .long("from")
+                        .value_name("FORMAT")
+                        .help("The format of the input: ron, toml or json, defaults to its extension"),
+                )
+                .arg(
+                    Arg::new("To")
+                        .long("to")
+                        .value_name("FORMAT")
+                        .help("The format to write: ron, toml or json, defaults to the extension of the output"),
+                )
+                .arg(
+                    Arg::new("Verify")
+                        .long("verify")
+                        .action(ArgAction::SetTrue)
+                        .help("Read the result back and refuse to write it if anything was lost"),
+                ),
+        )
+        .subcommand(
+            ClapCmd::new("detect")
+                .about("Show the terminals, launchers, lockers and session tools a new config would use"),
+        )
+        .subcommand(
+            ClapCmd::new("restore")
+                .about("List the backups of the current config or roll back to one")
+                .arg(
+                    Arg::new("Backup")
+                        .value_parser(clap::value_parser!(usize))
+                        .help("The number of the backup to restore, as listed without it"),
+                ),
+        )
+        .subcommand(
+            ClapCmd::new("profile")
+                .about("Manage named profiles stored next to the config")
+                .subcommand_required(true)
+                .subcommand(ClapCmd::new("list").about("List the profiles"))
+                .subcommand(
+                    ClapCmd::new("create")
+                        .about("Create a profile as a copy of the current config")
+                        .arg(Arg::new("Name").required(true)),
+                )
+                .subcommand(
+                    ClapCmd::new("switch")
+                        .about("Make a profile the config leftwm reads")
+                        .arg(Arg::new("Name").required(true)),
+                )
+                .subcommand(
+                    ClapCmd::new("delete")
+                        .about("Delete a profile")
+                        .arg(Arg::new("Name").required(true)),
+                ),
+        )
+}
 
-    let verbose = matches.get_flag("Verbose");
+/// The config file given with `--config`, or else by `LEFTWM_CONFIG` (`env`).
+fn config_path(matches: &clap::ArgMatches, env: Option<OsString>) -> Option<PathBuf> {
+    matches
+        .get_one::<PathBuf>("Config")
+        .cloned()
+        .or_else(|| env.map(PathBuf::from))
+}
+
+fn load_monitors(matches: &clap::ArgMatches) -> Result<Option<Vec<config::monitor::Monitor>>> {
+    matches
+        .get_one::<String>("Monitors")
+        .map(|source| config::monitor::load(source))
+        .transpose()
+}
+
+/// Runs `check` once and prints the report in the requested format.
+fn run_check(matches: &clap::ArgMatches, verbose: bool) -> Result<Option<Severity>> {
+    let monitors = load_monitors(matches)?;
+    let monitors = monitors.as_deref();
+    let format = matches
+        .get_one::<String>("Format")
+        .map_or(Ok(OutputFormat::Text), |f| f.parse())?;
+    Ok(match format {
+        OutputFormat::Text => check_config(None, monitors, verbose)?.worst(),
+        // verbose output would end up in the middle of the document
+        OutputFormat::Json | OutputFormat::Sarif => {
+            let report = config::run_checks(None, monitors, false)?;
+            if format == OutputFormat::Json {
+                println!("{}", report.to_json()?);
+            } else {
+                println!("{}", report.to_sarif()?);
+            }
+            report.worst()
+        }
+    })
+}
 
-    if matches.get_flag("Migrate") {
-        println!("\x1b[0;94m::\x1b[0m Migrating configuration . . .");
-        let path = BaseDirectories::with_prefix("leftwm");
-        let ron_file = path.place_config_file(crate::CONFIG_NAME.to_string() + ".ron")?;
-        let toml_file = path.place_config_file(crate::CONFIG_NAME.to_string() + ".toml")?;
+/// Converts the TOML config to RON, keeping its comments. The result is read back before
+/// anything is written and an existing RON config is only replaced after confirmation.
+fn migrate_toml_to_ron(verbose: bool, dry_run: bool) -> Result<()> {
+    println!("\x1b[0;94m::\x1b[0m Migrating configuration . . .");
+    let ron_file = config::filehandler::ron_config_file()?;
+    let toml_file = ron_file.with_extension("toml");
 
-        let config = load_from_file(toml_file.as_os_str().to_str(), verbose)?;
+    let toml_source = fs::read_to_string(&toml_file)
+        .with_context(|| format!("Could not read {}", toml_file.display()))?;
+    let config = load_from_file(toml_file.as_os_str().to_str(), verbose)?;
+    let ron =
+        config::comments::carry_over(&toml_source, &config::filehandler::with_header(&config)?)?;
 
-        write_to_file(&ron_file, &config)?;
+    println!("\x1b[0;94m::\x1b[0m Validating the RON config . . .");
+    let lost = config::convert::verify(&config, &ron, Format::Ron)?;
+    if !lost.is_empty() {
+        for difference in &lost {
+            println!("\x1b[1;91mERROR: Not kept in RON: {difference}\x1b[0m");
+        }
+        anyhow::bail!(
+            "The RON config does not match {}, nothing was written",
+            toml_file.display()
+        );
+    }
+    println!("\x1b[0;92m    -> The RON config holds every value\x1b[0m");
 
+    let existing = fs::read_to_string(&ron_file).ok();
+    if let Some(existing) = &existing {
+        println!(
+            "\x1b[0;94m::\x1b[0m Differences to the existing {}:",
+            ron_file.display()
+        );
+        match config::filehandler::parse(existing, true) {
+            Ok((existing, _)) => {
+                let changes = config::filehandler::changed_settings(&existing, &config)?;
+                if changes.is_empty() {
+                    println!("    none, both configs hold the same settings");
+                }
+                for change in changes {
+                    println!("    {change}");
+                }
+            }
+            Err(e) => println!("    could not read the existing config: {e}"),
+        }
+    }
+
+    if dry_run {
+        println!(
+            "\x1b[0;94m::\x1b[0m {} would be written as:",
+            ron_file.display()
+        );
+        print!("{ron}");
         return Ok(());
-    } else if matches.get_flag("Editor") {
-        run_editor(config::filehandler::get_config_file()?.as_path())?;
-    } else if matches.get_flag("TUI") {
-        crate::tui::run()?;
-    } else if matches.get_flag("New") {
-        config::filehandler::generate_new_config()?;
-    } else if matches.get_flag("Check") {
-        check_config(None, verbose)?;
+    }
+    if existing.is_some() {
+        println!(
+            "\x1b[0;94m::\x1b[0m {} already exists, do you want to override it? [y/N]",
+            ron_file.display()
+        );
+        let mut line = String::new();
+        io::stdin().read_line(&mut line)?;
+        if !(line.contains('y') || line.contains('Y')) {
+            println!("\x1b[0;94m::\x1b[0m Kept the existing config");
+            return Ok(());
+        }
+    }
+    config::backup::write_atomic(&ron_file, &ron)?;
+    config::profile::apply_if_active(&ron_file)?;
+    println!(
+        "\x1b[0;92m    -> Migrated {} to {}\x1b[0m",
+        toml_file.display(),
+        ron_file.display()
+    );
+    Ok(())
+}
+
+/// Converts a config, status messages go to stderr as the result may be written to stdout.
+fn run_convert(matches: &clap::ArgMatches) -> Result<()> {
+    let stdio = |path: Option<&PathBuf>| path.is_none_or(|p| p.as_os_str() == "-");
+    let format = |name: &str, path: Option<&PathBuf>| -> Result<Format> {
+        match (matches.get_one::<String>(name), path) {
+            (Some(format), _) => format.parse(),
+            (None, Some(path)) if !stdio(Some(path)) => Format::of(path).with_context(|| {
+                format!(
+                    "Can not tell the format of {}, pass --{}",
+                    path.display(),
+                    name.to_lowercase()
+                )
+            }),
+            _ => anyhow::bail!("Pass --{} when using stdin or stdout", name.to_lowercase()),
+        }
+    };
+
+    let input = match matches.get_one::<PathBuf>("Input") {
+        Some(input) => input.clone(),
+        None => config::filehandler::get_config_file()?,
+    };
+    let source = if stdio(Some(&input)) {
+        io::read_to_string(io::stdin())?
     } else {
-        run_editor(config::filehandler::get_config_file()?.as_path())?;
+        fs::read_to_string(&input).with_context(|| format!("Could not read {}", input.display()))?
+    };
+    let from = format("From", Some(&input))?;
+    let output = matches.get_one::<PathBuf>("Output");
+    let to = format("To", output)?;
+
+    let (config, applied) = config::convert::read(&source, from)?;
+    for step in &applied {
+        eprintln!("\x1b[0;94m::\x1b[0m Upgraded the schema: {step}");
+    }
+    let converted = config::convert::write(&config, to)?;
+    if matches.get_flag("Verify") {
+        let lost = config::convert::verify(&config, &converted, to)?;
+        if !lost.is_empty() {
+            for difference in &lost {
+                eprintln!("\x1b[1;91mERROR: Not kept in {to}: {difference}\x1b[0m");
+            }
+            anyhow::bail!(
+                "The conversion from {from} to {to} would lose data, nothing was written"
+            );
+        }
+        eprintln!("\x1b[0;92m    -> Round trip through {to} kept every value\x1b[0m");
     }
 
+    match output.filter(|output| !stdio(Some(output))) {
+        Some(output) => {
+            config::backup::replace(output, &converted)?;
+            eprintln!(
+                "\x1b[0;92m    -> Converted {from} to {to} in {}\x1b[0m",
+                output.display()
+            );
+        }
+        None => print!("{converted}"),
+    }
+    Ok(())
+}
+
+fn run_profile_command(matches: &clap::ArgMatches) -> Result<()> {
+    let name = |m: &clap::ArgMatches| m.get_one::<String>("Name").cloned().unwrap_or_default();
+    match matches.subcommand() {
+        Some(("create", m)) => {
+            config::profile::create(&name(m))?;
+            println!("\x1b[0;92m    -> Created profile `{}`\x1b[0m", name(m));
+        }
+        Some(("switch", m)) => {
+            config::profile::switch(&name(m))?;
+            println!("\x1b[0;92m    -> Switched to profile `{}`\x1b[0m", name(m));
+        }
+        Some(("delete", m)) => {
+            config::profile::delete(&name(m))?;
+            println!("\x1b[0;92m    -> Deleted profile `{}`\x1b[0m", name(m));
+        }
+        _ => {
+            let profiles = config::profile::list()?;
+            if profiles.is_empty() {
+                println!(
+                    "\x1b[0;94m::\x1b[0m There are no profiles, create one with `leftwm-config profile create <name>`"
+                );
+            }
+            let active = config::profile::active()?;
+            for profile in profiles {
+                if active.as_ref() == Some(&profile) {
+                    println!("\x1b[0;92m  * {profile}\x1b[0m");
+                } else {
+                    println!("    {profile}");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn list_backups(file: &Path) -> Result<()> {
+    let backups = config::backup::list(file)?;
+    if backups.is_empty() {
+        println!(
+            "\x1b[0;94m::\x1b[0m There are no backups of {}",
+            file.display()
+        );
+        return Ok(());
+    }
+    println!("\x1b[0;94m::\x1b[0m Backups of {}:", file.display());
+    for (i, backup) in backups.iter().enumerate() {
+        println!("    {}. {}", i + 1, backup.taken);
+    }
+    println!("Restore one with `leftwm-config restore <number>`.");
     Ok(())
 }
 
 fn run_editor(file: &Path) -> Result<()> {
     let editor = env::var("EDITOR")?;
 
-    let tmp_file = Path::new("/tmp/leftwm-config.ron");
-    fs::copy(file, tmp_file)?;
+    let original = fs::read_to_string(file)?;
+    let extension = file.extension().and_then(|e| e.to_str()).unwrap_or("ron");
+    // a private copy, so other users and sessions can neither read nor swap it
+    let tmp_dir = utils::PrivateTempDir::new("leftwm-config")?;
+    let tmp_file = tmp_dir.path.join(format!("config.{extension}"));
+    fs::write(&tmp_file, &original)?;
+    let tmp_file_str = tmp_file
+        .to_str()
+        .context("The temporary path is not valid UTF-8")?;
 
     let run_internal = || -> Result<()> {
         let mut process = Command::new(&editor).arg(tmp_file.as_os_str()).spawn()?;
@@ -119,7 +553,15 @@ fn run_editor(file: &Path) -> Result<()> {
 
     run_internal()?;
 
-    while check_config(Some("/tmp/leftwm-config.ron"), false).is_err() {
+    // only the config decides, a machine without elogind would otherwise never get past this
+    let invalid = || match check_config(Some(tmp_file_str), None, false) {
+        Ok(report) => report.worst() == Some(Severity::Error),
+        Err(e) => {
+            println!("\x1b[1;91mERROR: The config could not be checked: {e:#}\x1b[0m");
+            true
+        }
+    };
+    while invalid() {
         println!("Do you want to reopen your editor? [Y/n] ");
 
         let mut buffer = String::new();
@@ -134,7 +576,76 @@ fn run_editor(file: &Path) -> Result<()> {
         }
     }
 
-    fs::copy(tmp_file, file)?;
+    let edited = fs::read_to_string(&tmp_file)?;
+    if edited == original {
+        println!(
+            "\x1b[0;94m::\x1b[0m No changes, {} was left untouched",
+            file.display()
+        );
+        return Ok(());
+    }
+
+    println!("\x1b[0;94m::\x1b[0m Changes to {}:", file.display());
+    match config::filehandler::summarize_changes(&original, &edited, extension == "ron") {
+        Ok(summary) => {
+            for line in summary {
+                println!("    {line}");
+            }
+        }
+        Err(e) => println!("    could not compare the settings: {e}"),
+    }
+    println!("Do you want to save these changes? [Y/n] ");
+    let mut buffer = String::new();
+    io::stdin().read_line(&mut buffer)?;
+    if let Some("n" | "N") = buffer.get(0..1) {
+        println!("\x1b[0;94m::\x1b[0m Discarded the changes");
+        return Ok(());
+    }
+
+    config::backup::write_atomic(file, &edited)?;
+    config::profile::apply_if_active(file)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_path_of(args: &[&str], env: Option<&str>) -> Option<PathBuf> {
+        let matches = cli()
+            .try_get_matches_from(std::iter::once("leftwm-config").chain(args.iter().copied()))
+            .unwrap();
+        config_path(&matches, env.map(OsString::from))
+    }
+
+    #[test]
+    fn cli_is_consistent() {
+        cli().debug_assert();
+    }
+
+    #[test]
+    fn config_flag_wins_over_the_environment() {
+        assert_eq!(
+            config_path_of(&["--config", "flag.ron"], Some("env.ron")),
+            Some(PathBuf::from("flag.ron"))
+        );
+        assert_eq!(
+            config_path_of(&["-c"], Some("env.ron")),
+            Some(PathBuf::from("env.ron"))
+        );
+        assert_eq!(config_path_of(&["-c"], None), None);
+    }
+
+    #[test]
+    fn config_flag_is_accepted_after_subcommands() {
+        assert_eq!(
+            config_path_of(&["migrate", "--config", "flag.toml"], None),
+            Some(PathBuf::from("flag.toml"))
+        );
+        assert_eq!(
+            config_path_of(&["merge", "--config", "flag.ron"], Some("env.ron")),
+            Some(PathBuf::from("flag.ron"))
+        );
+    }
+}
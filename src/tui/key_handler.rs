@@ -5,20 +5,47 @@ use crossterm::event;
 use crossterm::event::{Event, KeyCode};
 use tui::widgets::ListState;
 
-use crate::config::command::BaseCommand;
+use crate::config::command::{ArgumentKind, BaseCommand};
 use crate::config::filehandler::save_to_file;
 use crate::config::keybind::Keybind;
-use crate::config::layout::Layout as WMLayout;
-use crate::config::modifier::{Modifier as KeyModifier, Modifier};
-use crate::config::structs::{ScratchPad, WindowHook, Workspace};
-use crate::config::values::{FocusBehaviour, InsertBehavior, LayoutMode, Size};
-use crate::tui::{App, MultiselectListState, PopupState, Window, next, previous};
+use crate::config::layout::{
+    Flip, LayoutDefinition, Main, Reserve, Rotation, SecondStack, SplitAxis,
+};
+use crate::config::modifier::{self, Modifier as KeyModifier, Modifier};
+use crate::config::structs::{FloatingGeometry, ScratchPad, WindowHook, Workspace};
+use crate::config::values::{FocusBehaviour, InsertBehavior, LayoutMode, Size, WindowType};
+use crate::tui::{
+    App, MultiselectListState, PopupState, SAVED_POPUP, STATE_PATH_POPUP, Window, layout_choices,
+    next, previous, selected_layouts,
+};
 use crate::utils::xkeysym_lookup::into_keysym;
 use crate::utils::{TryRemove, TryUnwrap};
 
+/// The modifiers in the order of the rows of the modifier popups, the `modkey` placeholder
+/// is only listed in the keybind popup.
+const MODIFIER_ROWS: [&str; 8] = [
+    "None", "Shift", "Control", "Mod1", "Mod3", "Mod4", "Mod5", "modkey",
+];
+
+/// The row of the `modkey` placeholder in the keybind modifier popup.
+const MODKEY_INDEX: usize = 7;
+
+/// The row of a modifier in the modifier popups.
+fn modifier_index(name: &str) -> Option<usize> {
+    let name = modifier::canonical(name)?;
+    MODIFIER_ROWS.iter().position(|row| *row == name)
+}
+
+fn modifier_name(index: usize) -> Result<String> {
+    match MODIFIER_ROWS.get(index) {
+        Some(name) => Ok((*name).to_string()),
+        None => bail!("Unexpected value"),
+    }
+}
+
 pub(super) fn handle_keys(app: &mut App) -> Result<bool> {
     if let Event::Key(key) = event::read()? {
-        if let Some(15) = app.current_popup {
+        if let Some(SAVED_POPUP) = app.current_popup {
             app.current_popup = None;
         } else {
             return match key.code {
@@ -33,6 +60,9 @@ pub(super) fn handle_keys(app: &mut App) -> Result<bool> {
                     Window::WindowRules { index, empty } => enter_window_rules(app, index, empty),
                     Window::Scratchpads { index, empty } => enter_scratchpads(app, index, empty),
                     Window::KeyBinds { index, empty } => enter_keybinds(app, index, empty),
+                    Window::LayoutDefinitions { index, empty } => {
+                        enter_layout_definitions(app, index, empty)
+                    }
                 },
                 KeyCode::Esc => {
                     app.current_popup = None;
@@ -51,6 +81,9 @@ pub(super) fn handle_keys(app: &mut App) -> Result<bool> {
     Ok(false)
 }
 
+/// Characters accepted by the size popups, see [`Size`]'s `FromStr`.
+const SIZE_CHARS: &str = "1234567890,.-%px";
+
 fn up(app: &mut App) -> Result<bool> {
     if app.current_popup.is_none() {
         match app.config_list_state.selected() {
@@ -92,22 +125,34 @@ fn up(app: &mut App) -> Result<bool> {
                     }
                 }
                 9 => {
+                    let len =
+                        layout_choices(&app.current_config, &app.current_config.layouts).len();
                     if let PopupState::MultiList(s) = &mut app.current_popup_state {
-                        previous(&mut s.liststate, 14);
+                        previous(&mut s.liststate, len);
                     } else {
                         bail!("Invalid popup state");
                     }
                 }
-                10 => {}
-                11 => {}
-                12 => {}
-                13 => {}
-                14 => {}
                 _ => {}
             },
-            Window::Workspaces { .. } if s == 6 => {
+            Window::Workspaces { index, .. } if s == 6 => {
+                let current = app
+                    .current_config
+                    .workspaces
+                    .as_ref()
+                    .and_then(|w| w.get(index))
+                    .and_then(|w| w.layouts.as_deref())
+                    .unwrap_or_default();
+                let len = layout_choices(&app.current_config, current).len();
                 if let PopupState::MultiList(s) = &mut app.current_popup_state {
-                    previous(&mut s.liststate, 14);
+                    previous(&mut s.liststate, len);
+                } else {
+                    bail!("Invalid popup state");
+                }
+            }
+            Window::WindowRules { .. } if s == 4 => {
+                if let PopupState::List(l) = &mut app.current_popup_state {
+                    previous(l, WindowType::ALL.len());
                 } else {
                     bail!("Invalid popup state");
                 }
@@ -177,22 +222,34 @@ fn down(app: &mut App) -> Result<bool> {
                     }
                 }
                 9 => {
+                    let len =
+                        layout_choices(&app.current_config, &app.current_config.layouts).len();
                     if let PopupState::MultiList(s) = &mut app.current_popup_state {
-                        next(&mut s.liststate, 14);
+                        next(&mut s.liststate, len);
                     } else {
                         bail!("Invalid popup state");
                     }
                 }
-                10 => {}
-                11 => {}
-                12 => {}
-                13 => {}
-                14 => {}
                 _ => {}
             },
-            Window::Workspaces { .. } if s == 6 => {
+            Window::Workspaces { index, .. } if s == 6 => {
+                let current = app
+                    .current_config
+                    .workspaces
+                    .as_ref()
+                    .and_then(|w| w.get(index))
+                    .and_then(|w| w.layouts.as_deref())
+                    .unwrap_or_default();
+                let len = layout_choices(&app.current_config, current).len();
                 if let PopupState::MultiList(s) = &mut app.current_popup_state {
-                    next(&mut s.liststate, 14);
+                    next(&mut s.liststate, len);
+                } else {
+                    bail!("Invalid popup state");
+                }
+            }
+            Window::WindowRules { .. } if s == 4 => {
+                if let PopupState::List(l) = &mut app.current_popup_state {
+                    next(l, WindowType::ALL.len());
                 } else {
                     bail!("Invalid popup state");
                 }
@@ -281,6 +338,27 @@ fn right(app: &mut App) -> Result<bool> {
                 }
             }
         }
+        Window::LayoutDefinitions { index, empty } => {
+            if let Some(1) = app.current_popup {
+                if let PopupState::Int { current, min, max } = app.current_popup_state {
+                    if current < max {
+                        app.current_popup_state = PopupState::Int {
+                            current: current + 1,
+                            min,
+                            max,
+                        }
+                    }
+                } else {
+                    bail!("Invalid popup state");
+                }
+            } else if !empty {
+                if index >= app.current_config.layout_definitions.len() - 1 {
+                    app.current_window.try_set_index(0);
+                } else {
+                    app.current_window.try_increment_index();
+                }
+            }
+        }
         Window::Home => {}
     }
 
@@ -354,6 +432,28 @@ fn left(app: &mut App) -> Result<bool> {
                 }
             }
         }
+        Window::LayoutDefinitions { index, empty } => {
+            if let Some(1) = app.current_popup {
+                if let PopupState::Int { current, min, max } = app.current_popup_state {
+                    if current > min {
+                        app.current_popup_state = PopupState::Int {
+                            current: current - 1,
+                            min,
+                            max,
+                        }
+                    }
+                } else {
+                    bail!("Invalid popup state");
+                }
+            } else if !empty {
+                if index == 0 {
+                    app.current_window
+                        .try_set_index(app.current_config.layout_definitions.len() - 1);
+                } else {
+                    app.current_window.try_decrement_index();
+                }
+            }
+        }
         Window::Home => {}
     }
 
@@ -367,47 +467,30 @@ fn enter_home(app: &mut App) -> Result<bool> {
                 0 => {
                     app.current_popup = Some(0);
                     let mut state = ListState::default();
-                    match app.current_config.modkey.as_str() {
-                        "None" => state.select(Some(0)),
-                        "Shift" => state.select(Some(1)),
-                        "Control" => state.select(Some(2)),
-                        "Mod1" | "Alt" => state.select(Some(3)),
-                        //"Mod2" => xlib::Mod2Mask,     // NOTE: we are ignoring the state of Numlock
-                        //"NumLock" => xlib::Mod2Mask,  // this is left here as a reminder
-                        "Mod3" => state.select(Some(4)),
-                        "Mod4" | "Super" => state.select(Some(5)),
-                        "Mod5" => state.select(Some(6)),
-                        _ => state.select(None),
-                    }
+                    state.select(
+                        modifier_index(&app.current_config.modkey.to_string())
+                            .filter(|&i| i != MODKEY_INDEX),
+                    );
                     app.current_popup_state = PopupState::List(state);
                 }
                 1 => {
                     app.current_popup = Some(1);
                     let mut state = ListState::default();
-                    match app
-                        .current_config
-                        .mousekey
-                        .clone()
-                        .unwrap_or_else(|| KeyModifier::Single("None".to_string()))
-                        .to_string()
-                        .as_str()
-                    {
-                        "None" => state.select(Some(0)),
-                        "Shift" => state.select(Some(1)),
-                        "Control" => state.select(Some(2)),
-                        "Mod1" | "Alt" => state.select(Some(3)),
-                        //"Mod2" => xlib::Mod2Mask,     // NOTE: we are ignoring the state of Numlock
-                        //"NumLock" => xlib::Mod2Mask,  // this is left here as a reminder
-                        "Mod3" => state.select(Some(4)),
-                        "Mod4" | "Super" => state.select(Some(5)),
-                        "Mod5" => state.select(Some(6)),
-                        _ => state.select(None),
-                    }
+                    state.select(
+                        modifier_index(
+                            &app.current_config
+                                .mousekey
+                                .clone()
+                                .unwrap_or_else(|| KeyModifier::Single("None".to_string()))
+                                .to_string(),
+                        )
+                        .filter(|&i| i != MODKEY_INDEX),
+                    );
                     app.current_popup_state = PopupState::List(state);
                 }
                 2 => {
                     app.current_popup = Some(2);
-                    app.current_popup_state = PopupState::String(String::new());
+                    app.current_popup_state = size_popup_state(app.current_config.max_window_width);
                 }
                 3 => {
                     app.current_config.disable_current_tag_swap =
@@ -454,25 +537,10 @@ fn enter_home(app: &mut App) -> Result<bool> {
                 }
                 9 => {
                     app.current_popup = Some(9);
-                    let selected: Vec<usize> = vec![];
-                    // for l in &app.current_config.layouts {
-                    //     match l {
-                    //         WMLayout::MainAndVertStack => selected.push(0),
-                    //         WMLayout::MainAndHorizontalStack => selected.push(1),
-                    //         WMLayout::MainAndDeck => selected.push(2),
-                    //         WMLayout::GridHorizontal => selected.push(3),
-                    //         WMLayout::EvenHorizontal => selected.push(4),
-                    //         WMLayout::EvenVertical => selected.push(5),
-                    //         WMLayout::Fibonacci => selected.push(6),
-                    //         WMLayout::LeftMain => selected.push(7),
-                    //         WMLayout::CenterMain => selected.push(8),
-                    //         WMLayout::CenterMainBalanced => selected.push(9),
-                    //         WMLayout::CenterMainFluid => selected.push(10),
-                    //         WMLayout::Monocle => selected.push(11),
-                    //         WMLayout::RightWiderLeftStack => selected.push(12),
-                    //         WMLayout::LeftWiderRightStack => selected.push(13),
-                    //     }
-                    // }
+                    let selected = selected_layouts(
+                        &layout_choices(&app.current_config, &app.current_config.layouts),
+                        &app.current_config.layouts,
+                    );
                     let mut liststate = ListState::default();
                     liststate.select(Some(0));
                     app.current_popup_state = PopupState::MultiList(MultiselectListState {
@@ -481,6 +549,44 @@ fn enter_home(app: &mut App) -> Result<bool> {
                     });
                 }
                 10 => {
+                    app.current_config.disable_window_snap =
+                        !app.current_config.disable_window_snap;
+                }
+                11 => {
+                    app.current_config.single_window_border =
+                        !app.current_config.single_window_border;
+                }
+                12 => {
+                    app.current_config.sloppy_mouse_follows_focus =
+                        !app.current_config.sloppy_mouse_follows_focus;
+                }
+                13 => {
+                    app.current_config.create_follows_cursor =
+                        match app.current_config.create_follows_cursor {
+                            None => Some(true),
+                            Some(true) => Some(false),
+                            Some(false) => None,
+                        };
+                }
+                14 => {
+                    app.current_config.auto_derive_workspaces =
+                        !app.current_config.auto_derive_workspaces;
+                }
+                15 => {
+                    app.current_config.reposition_cursor_on_resize =
+                        !app.current_config.reposition_cursor_on_resize;
+                }
+                16 => {
+                    app.current_popup = Some(STATE_PATH_POPUP);
+                    app.current_popup_state = PopupState::String(
+                        app.current_config
+                            .state_path
+                            .as_ref()
+                            .map(|p| p.display().to_string())
+                            .unwrap_or_default(),
+                    );
+                }
+                17 => {
                     app.current_window = Window::Workspaces {
                         index: 0,
                         empty: if let Some(v) = &app.current_config.workspaces {
@@ -490,7 +596,7 @@ fn enter_home(app: &mut App) -> Result<bool> {
                         },
                     };
                 }
-                11 => {
+                18 => {
                     app.current_window = Window::Tags {
                         index: 0,
                         empty: if let Some(v) = &app.current_config.tags {
@@ -500,7 +606,7 @@ fn enter_home(app: &mut App) -> Result<bool> {
                         },
                     }
                 }
-                12 => {
+                19 => {
                     app.current_window = Window::WindowRules {
                         index: 0,
                         empty: if let Some(v) = &app.current_config.window_rules {
@@ -510,7 +616,7 @@ fn enter_home(app: &mut App) -> Result<bool> {
                         },
                     }
                 }
-                13 => {
+                20 => {
                     app.current_window = Window::Scratchpads {
                         index: 0,
                         empty: if let Some(v) = &app.current_config.scratchpad {
@@ -520,12 +626,18 @@ fn enter_home(app: &mut App) -> Result<bool> {
                         },
                     }
                 }
-                14 => {
+                21 => {
                     app.current_window = Window::KeyBinds {
                         index: 0,
                         empty: app.current_config.keybind.is_empty(),
                     }
                 }
+                22 => {
+                    app.current_window = Window::LayoutDefinitions {
+                        index: 0,
+                        empty: app.current_config.layout_definitions.is_empty(),
+                    }
+                }
                 _ => {}
             }
         } else if let Some(s) = app.current_popup {
@@ -535,31 +647,31 @@ fn enter_home(app: &mut App) -> Result<bool> {
                         if let Some(s) = s.selected() {
                             match s {
                                 0 => {
-                                    app.current_config.modkey = "None".to_string();
+                                    app.current_config.modkey = "None".into();
                                     app.current_popup = None;
                                 }
                                 1 => {
-                                    app.current_config.modkey = "Shift".to_string();
+                                    app.current_config.modkey = "Shift".into();
                                     app.current_popup = None;
                                 }
                                 2 => {
-                                    app.current_config.modkey = "Control".to_string();
+                                    app.current_config.modkey = "Control".into();
                                     app.current_popup = None;
                                 }
                                 3 => {
-                                    app.current_config.modkey = "Mod1".to_string();
+                                    app.current_config.modkey = "Mod1".into();
                                     app.current_popup = None;
                                 }
                                 4 => {
-                                    app.current_config.modkey = "Mod3".to_string();
+                                    app.current_config.modkey = "Mod3".into();
                                     app.current_popup = None;
                                 }
                                 5 => {
-                                    app.current_config.modkey = "Mod4".to_string();
+                                    app.current_config.modkey = "Mod4".into();
                                     app.current_popup = None;
                                 }
                                 6 => {
-                                    app.current_config.modkey = "Mod5".to_string();
+                                    app.current_config.modkey = "Mod5".into();
                                     app.current_popup = None;
                                 }
                                 _ => {}
@@ -609,16 +721,10 @@ fn enter_home(app: &mut App) -> Result<bool> {
                     }
                 }
                 2 => {
-                    app.current_config.max_window_width =
-                        if let PopupState::String(s) = &app.current_popup_state {
-                            if s.contains('.') || s.contains(',') {
-                                Some(Size::Ratio(s.parse().unwrap_or(0.0)))
-                            } else {
-                                Some(Size::Pixel(s.parse().unwrap_or(0)))
-                            }
-                        } else {
-                            bail!("Invalid popup state");
-                        };
+                    let Some(size) = size_input(&app.current_popup_state)? else {
+                        return Ok(false);
+                    };
+                    app.current_config.max_window_width = size;
                     app.current_popup = None;
                 }
                 6 => {
@@ -688,35 +794,29 @@ fn enter_home(app: &mut App) -> Result<bool> {
                     app.current_popup = None;
                 }
                 9 => {
-                    // TODO: we want to support config defined layouts eventually
-                    // then we will be using `l`
-                    if let PopupState::MultiList(_l) = &app.current_popup_state {
-                        let layouts: Vec<String> = vec![];
-                        // for s in &l.selected {
-                        //     match s {
-                        //         0 => layouts.push(WMLayout::MainAndVertStack),
-                        //         1 => layouts.push(WMLayout::MainAndHorizontalStack),
-                        //         2 => layouts.push(WMLayout::MainAndDeck),
-                        //         3 => layouts.push(WMLayout::GridHorizontal),
-                        //         4 => layouts.push(WMLayout::EvenHorizontal),
-                        //         5 => layouts.push(WMLayout::EvenVertical),
-                        //         6 => layouts.push(WMLayout::Fibonacci),
-                        //         7 => layouts.push(WMLayout::LeftMain),
-                        //         8 => layouts.push(WMLayout::CenterMain),
-                        //         9 => layouts.push(WMLayout::CenterMainBalanced),
-                        //         10 => layouts.push(WMLayout::CenterMainFluid),
-                        //         11 => layouts.push(WMLayout::Monocle),
-                        //         12 => layouts.push(WMLayout::RightWiderLeftStack),
-                        //         13 => layouts.push(WMLayout::LeftWiderRightStack),
-                        //         _ => {}
-                        //     }
-                        // }
+                    if let PopupState::MultiList(l) = &app.current_popup_state {
+                        let choices =
+                            layout_choices(&app.current_config, &app.current_config.layouts);
+                        let layouts = l
+                            .selected
+                            .iter()
+                            .filter_map(|i| choices.get(*i).cloned())
+                            .collect();
                         app.current_config.layouts = layouts;
                         app.current_popup = None;
                     } else {
                         bail!("Invalid popup state");
                     }
                 }
+                STATE_PATH_POPUP => {
+                    app.current_config.state_path =
+                        if let PopupState::String(s) = &app.current_popup_state {
+                            if s.is_empty() { None } else { Some(s.into()) }
+                        } else {
+                            bail!("Invalid popup state");
+                        };
+                    app.current_popup = None;
+                }
                 _ => {}
             }
         }
@@ -811,6 +911,9 @@ fn enter_workspaces(app: &mut App, index: usize, empty: bool) -> Result<bool> {
                 }
             }
             5 => {
+                let Some(size) = size_input(&app.current_popup_state)? else {
+                    return Ok(false);
+                };
                 app.current_popup = None;
                 app.current_config
                     .workspaces
@@ -818,38 +921,26 @@ fn enter_workspaces(app: &mut App, index: usize, empty: bool) -> Result<bool> {
                     .try_unwrap()?
                     .get_mut(index)
                     .try_unwrap()?
-                    .max_window_width = if let PopupState::String(s) = &app.current_popup_state {
-                    if s.contains('.') || s.contains(',') {
-                        Some(Size::Ratio(s.parse().unwrap_or(0.0)))
-                    } else {
-                        Some(Size::Pixel(s.parse().unwrap_or(0)))
-                    }
-                } else {
-                    bail!("Invalid popup state");
-                };
+                    .max_window_width = size;
             }
             6 => {
                 if let PopupState::MultiList(l) = &app.current_popup_state {
-                    let mut layouts: Vec<WMLayout> = vec![];
-                    for s in &l.selected {
-                        match s {
-                            0 => layouts.push(WMLayout::MainAndVertStack),
-                            1 => layouts.push(WMLayout::MainAndHorizontalStack),
-                            2 => layouts.push(WMLayout::MainAndDeck),
-                            3 => layouts.push(WMLayout::GridHorizontal),
-                            4 => layouts.push(WMLayout::EvenHorizontal),
-                            5 => layouts.push(WMLayout::EvenVertical),
-                            6 => layouts.push(WMLayout::Fibonacci),
-                            7 => layouts.push(WMLayout::LeftMain),
-                            8 => layouts.push(WMLayout::CenterMain),
-                            9 => layouts.push(WMLayout::CenterMainBalanced),
-                            10 => layouts.push(WMLayout::CenterMainFluid),
-                            11 => layouts.push(WMLayout::Monocle),
-                            12 => layouts.push(WMLayout::RightWiderLeftStack),
-                            13 => layouts.push(WMLayout::LeftWiderRightStack),
-                            _ => {}
-                        }
-                    }
+                    let current = app
+                        .current_config
+                        .workspaces
+                        .as_ref()
+                        .try_unwrap()?
+                        .get(index)
+                        .try_unwrap()?
+                        .layouts
+                        .as_deref()
+                        .unwrap_or_default();
+                    let choices = layout_choices(&app.current_config, current);
+                    let layouts = l
+                        .selected
+                        .iter()
+                        .filter_map(|i| choices.get(*i).cloned())
+                        .collect();
                     let mut workspace = app
                         .current_config
                         .workspaces
@@ -901,12 +992,19 @@ fn enter_workspaces(app: &mut App, index: usize, empty: bool) -> Result<bool> {
             }
             7 => {
                 app.current_popup = Some(5);
-                app.current_popup_state = PopupState::String(String::new());
+                app.current_popup_state = size_popup_state(
+                    app.current_config
+                        .workspaces
+                        .as_ref()
+                        .try_unwrap()?
+                        .get(index)
+                        .try_unwrap()?
+                        .max_window_width,
+                );
             }
             8 => {
                 app.current_popup = Some(6);
-                let mut selected: Vec<usize> = vec![];
-                for l in app
+                let current = app
                     .current_config
                     .workspaces
                     .as_ref()
@@ -914,28 +1012,10 @@ fn enter_workspaces(app: &mut App, index: usize, empty: bool) -> Result<bool> {
                     .get(index)
                     .try_unwrap()?
                     .layouts
-                    .as_ref()
-                    .unwrap_or(&vec![])
-                {
-                    match l {
-                        WMLayout::MainAndVertStack => selected.push(0),
-                        WMLayout::MainAndHorizontalStack => {
-                            selected.push(1);
-                        }
-                        WMLayout::MainAndDeck => selected.push(2),
-                        WMLayout::GridHorizontal => selected.push(3),
-                        WMLayout::EvenHorizontal => selected.push(4),
-                        WMLayout::EvenVertical => selected.push(5),
-                        WMLayout::Fibonacci => selected.push(6),
-                        WMLayout::LeftMain => selected.push(7),
-                        WMLayout::CenterMain => selected.push(8),
-                        WMLayout::CenterMainBalanced => selected.push(9),
-                        WMLayout::CenterMainFluid => selected.push(10),
-                        WMLayout::Monocle => selected.push(11),
-                        WMLayout::RightWiderLeftStack => selected.push(12),
-                        WMLayout::LeftWiderRightStack => selected.push(13),
-                    }
-                }
+                    .as_deref()
+                    .unwrap_or_default();
+                let selected =
+                    selected_layouts(&layout_choices(&app.current_config, current), current);
                 let mut liststate = ListState::default();
                 liststate.select(Some(0));
                 app.current_popup_state = PopupState::MultiList(MultiselectListState {
@@ -1097,6 +1177,70 @@ fn enter_window_rules(app: &mut App, index: usize, empty: bool) -> Result<bool>
             app.current_popup = None;
             app.current_popup_state = PopupState::None;
         }
+        Some(3) => {
+            app.current_config
+                .window_rules
+                .as_mut()
+                .try_unwrap()?
+                .get_mut(index)
+                .try_unwrap()?
+                .spawn_on_workspace = if let PopupState::String(s) = &app.current_popup_state {
+                if s.is_empty() { None } else { Some(s.parse()?) }
+            } else {
+                bail!("Invalid popup state")
+            };
+            app.current_popup = None;
+            app.current_popup_state = PopupState::None;
+        }
+        Some(4) => {
+            if let PopupState::List(l) = &app.current_popup_state {
+                if let Some(i) = l.selected() {
+                    app.current_config
+                        .window_rules
+                        .as_mut()
+                        .try_unwrap()?
+                        .get_mut(index)
+                        .try_unwrap()?
+                        .spawn_as_type = Some(*WindowType::ALL.get(i).try_unwrap()?);
+                }
+            } else {
+                bail!("Invalid popup state");
+            }
+            app.current_popup = None;
+            app.current_popup_state = PopupState::None;
+        }
+        Some(5) => {
+            app.current_config
+                .window_rules
+                .as_mut()
+                .try_unwrap()?
+                .get_mut(index)
+                .try_unwrap()?
+                .spawn_floating_geometry = if let PopupState::String(s) = &app.current_popup_state {
+                if s.is_empty() {
+                    None
+                } else {
+                    let values = s
+                        .split(',')
+                        .map(|v| v.trim().parse())
+                        .collect::<Result<Vec<i32>, _>>()?;
+                    if let [x, y, width, height] = values[..] {
+                        Some(FloatingGeometry {
+                            x,
+                            y,
+                            width,
+                            height,
+                        })
+                    } else {
+                        bail!("Expected x,y,width,height")
+                    }
+                }
+            } else {
+                bail!("Invalid popup state")
+            };
+            app.current_popup = None;
+            app.current_popup_state = PopupState::None;
+        }
         Some(_) => {}
         None => match app.config_list_state.selected() {
             Some(2) => {
@@ -1182,14 +1326,78 @@ fn enter_window_rules(app: &mut App, index: usize, empty: bool) -> Result<bool>
                         .unwrap_or(false),
                 );
             }
+            Some(6) => {
+                app.current_popup = Some(3);
+                app.current_popup_state = PopupState::String(
+                    app.current_config
+                        .window_rules
+                        .as_ref()
+                        .try_unwrap()?
+                        .get(index)
+                        .try_unwrap()?
+                        .spawn_on_workspace
+                        .map(|id| id.to_string())
+                        .unwrap_or_default(),
+                );
+            }
             Some(7) => {
+                let mut state = ListState::default();
+                state.select(Some(
+                    app.current_config
+                        .window_rules
+                        .as_ref()
+                        .try_unwrap()?
+                        .get(index)
+                        .try_unwrap()?
+                        .spawn_as_type
+                        .and_then(|t| WindowType::ALL.iter().position(|a| *a == t))
+                        .unwrap_or_default(),
+                ));
+                app.current_popup = Some(4);
+                app.current_popup_state = PopupState::List(state);
+            }
+            Some(8) => {
+                let rule = app
+                    .current_config
+                    .window_rules
+                    .as_mut()
+                    .try_unwrap()?
+                    .get_mut(index)
+                    .try_unwrap()?;
+                rule.spawn_sticky = Some(!rule.spawn_sticky.unwrap_or(false));
+            }
+            Some(9) => {
+                let rule = app
+                    .current_config
+                    .window_rules
+                    .as_mut()
+                    .try_unwrap()?
+                    .get_mut(index)
+                    .try_unwrap()?;
+                rule.spawn_fullscreen = Some(!rule.spawn_fullscreen.unwrap_or(false));
+            }
+            Some(10) => {
+                app.current_popup = Some(5);
+                app.current_popup_state = PopupState::String(
+                    app.current_config
+                        .window_rules
+                        .as_ref()
+                        .try_unwrap()?
+                        .get(index)
+                        .try_unwrap()?
+                        .spawn_floating_geometry
+                        .map(|g| format!("{},{},{},{}", g.x, g.y, g.width, g.height))
+                        .unwrap_or_default(),
+                );
+            }
+            Some(12) => {
                 app.current_config
                     .window_rules
                     .as_mut()
                     .try_unwrap()?
                     .push(WindowHook::default());
             }
-            Some(8) => {
+            Some(13) => {
                 app.current_config
                     .window_rules
                     .as_mut()
@@ -1265,78 +1473,58 @@ fn enter_scratchpads(app: &mut App, index: usize, empty: bool) -> Result<bool> {
             app.current_popup = None;
         }
         Some(2) => {
+            let Some(size) = size_input(&app.current_popup_state)? else {
+                return Ok(false);
+            };
             app.current_config
                 .scratchpad
                 .as_mut()
                 .try_unwrap()?
                 .get_mut(index)
                 .try_unwrap()?
-                .x = if let PopupState::String(s) = &app.current_popup_state {
-                if s.contains('.') || s.contains(',') {
-                    Some(Size::Ratio(s.parse()?))
-                } else {
-                    Some(Size::Pixel(s.parse()?))
-                }
-            } else {
-                bail!("Invalid popup state")
-            };
+                .x = size;
             app.current_popup_state = PopupState::None;
             app.current_popup = None;
         }
         Some(3) => {
+            let Some(size) = size_input(&app.current_popup_state)? else {
+                return Ok(false);
+            };
             app.current_config
                 .scratchpad
                 .as_mut()
                 .try_unwrap()?
                 .get_mut(index)
                 .try_unwrap()?
-                .y = if let PopupState::String(s) = &app.current_popup_state {
-                if s.contains('.') || s.contains(',') {
-                    Some(Size::Ratio(s.parse()?))
-                } else {
-                    Some(Size::Pixel(s.parse()?))
-                }
-            } else {
-                bail!("Invalid popup state")
-            };
+                .y = size;
             app.current_popup_state = PopupState::None;
             app.current_popup = None;
         }
         Some(4) => {
+            let Some(size) = size_input(&app.current_popup_state)? else {
+                return Ok(false);
+            };
             app.current_config
                 .scratchpad
                 .as_mut()
                 .try_unwrap()?
                 .get_mut(index)
                 .try_unwrap()?
-                .width = if let PopupState::String(s) = &app.current_popup_state {
-                if s.contains('.') || s.contains(',') {
-                    Some(Size::Ratio(s.parse()?))
-                } else {
-                    Some(Size::Pixel(s.parse()?))
-                }
-            } else {
-                bail!("Invalid popup state")
-            };
+                .width = size;
             app.current_popup_state = PopupState::None;
             app.current_popup = None;
         }
         Some(5) => {
+            let Some(size) = size_input(&app.current_popup_state)? else {
+                return Ok(false);
+            };
             app.current_config
                 .scratchpad
                 .as_mut()
                 .try_unwrap()?
                 .get_mut(index)
                 .try_unwrap()?
-                .height = if let PopupState::String(s) = &app.current_popup_state {
-                if s.contains('.') || s.contains(',') {
-                    Some(Size::Ratio(s.parse()?))
-                } else {
-                    Some(Size::Pixel(s.parse()?))
-                }
-            } else {
-                bail!("Invalid popup state")
-            };
+                .height = size;
             app.current_popup_state = PopupState::None;
             app.current_popup = None;
         }
@@ -1381,8 +1569,20 @@ fn enter_scratchpads(app: &mut App, index: usize, empty: bool) -> Result<bool> {
                 );
             }
             Some(i @ 4..=7) => {
+                let scratchpad = app
+                    .current_config
+                    .scratchpad
+                    .as_ref()
+                    .try_unwrap()?
+                    .get(index)
+                    .try_unwrap()?;
+                app.current_popup_state = size_popup_state(match i {
+                    4 => scratchpad.x,
+                    5 => scratchpad.y,
+                    6 => scratchpad.width,
+                    _ => scratchpad.height,
+                });
                 app.current_popup = Some(u8::try_from(i)? - 2);
-                app.current_popup_state = PopupState::String(String::new());
             }
             Some(9) => app
                 .current_config
@@ -1439,11 +1639,7 @@ fn enter_keybinds(app: &mut App, index: usize, empty: bool) -> Result<bool> {
     } else {
         match app.current_popup {
             Some(0) => {
-                app.current_config
-                    .keybind
-                    .get_mut(index)
-                    .try_unwrap()?
-                    .command = if let PopupState::List(l) = &app.current_popup_state {
+                let command = if let PopupState::List(l) = &app.current_popup_state {
                     match l.selected() {
                         Some(0) => BaseCommand::Execute,
                         Some(1) => BaseCommand::CloseWindow,
@@ -1491,31 +1687,36 @@ fn enter_keybinds(app: &mut App, index: usize, empty: bool) -> Result<bool> {
                 } else {
                     bail!("Invalid popup _state")
                 };
+                app.current_config
+                    .keybind
+                    .get_mut(index)
+                    .try_unwrap()?
+                    .set_command(command);
                 app.current_popup = None;
                 app.current_popup_state = PopupState::None;
             }
             Some(1) => {
+                let PopupState::String(s) = &app.current_popup_state else {
+                    bail!("Invalid popup state")
+                };
+                let keybind = app.current_config.keybind.get(index).try_unwrap()?;
+                // the popup shows why the value is not accepted
+                let Ok(value) = keybind.check_value(&app.current_config, s) else {
+                    return Ok(false);
+                };
                 app.current_config
                     .keybind
                     .get_mut(index)
                     .try_unwrap()?
-                    .value = if let PopupState::String(s) = app.current_popup_state.clone() {
-                    s
-                } else {
-                    bail!("Invalid popup state")
-                };
+                    .value = value;
                 app.current_popup = None;
                 app.current_popup_state = PopupState::None;
             }
             Some(2) => {
                 if let PopupState::MultiList(m) = &mut app.current_popup_state {
-                    let modkey_index = match app.current_config.modkey.as_str() {
-                        "Shift" => 1,
-                        "Control" => 2,
-                        "Mod1" | "Alt" => 3,
-                        "Mod3" => 4,
-                        "Mod4" | "Super" => 5,
-                        "Mod5" => 6,
+                    let modkey_index = match modifier_index(&app.current_config.modkey.to_string())
+                    {
+                        Some(i) if i != 0 && i != MODKEY_INDEX => i,
                         _ => bail!("unexpected value"),
                     };
 
@@ -1523,14 +1724,7 @@ fn enter_keybinds(app: &mut App, index: usize, empty: bool) -> Result<bool> {
                         let modifier = match m.selected.first().try_unwrap()? {
                             i if *i == modkey_index => Some("modkey".to_string()),
                             0 => None,
-                            1 => Some("Shift".to_string()),
-                            2 => Some("Control".to_string()),
-                            3 => Some("Mod1".to_string()),
-                            4 => Some("Mod4".to_string()),
-                            5 => Some("Mod5".to_string()),
-                            6 => Some("Mod5".to_string()),
-                            7 => Some("modkey".to_string()),
-                            _ => bail!("Unexpected value"),
+                            i => Some(modifier_name(*i)?),
                         };
 
                         app.current_config
@@ -1547,14 +1741,8 @@ fn enter_keybinds(app: &mut App, index: usize, empty: bool) -> Result<bool> {
                             }
 
                             match i {
-                                1 => keys.push("Shift".to_string()),
-                                2 => keys.push("Control".to_string()),
-                                3 => keys.push("Mod1".to_string()),
-                                4 => keys.push("Mod4".to_string()),
-                                5 => keys.push("Mod5".to_string()),
-                                6 => keys.push("Mod5".to_string()),
-                                7 => keys.push("modkey".to_string()),
-                                _ => bail!("Unexpected value"),
+                                0 => bail!("Unexpected value"),
+                                i => keys.push(modifier_name(*i)?),
                             }
                         }
                         app.current_config
@@ -1587,7 +1775,8 @@ fn enter_keybinds(app: &mut App, index: usize, empty: bool) -> Result<bool> {
                     .get(index)
                     .try_unwrap()?
                     .command
-                    .needs_value()
+                    .argument()
+                    != ArgumentKind::None
                 {
                     match app.config_list_state.selected() {
                         Some(2) => {
@@ -1602,7 +1791,7 @@ fn enter_keybinds(app: &mut App, index: usize, empty: bool) -> Result<bool> {
                                     .get(index)
                                     .try_unwrap()?
                                     .value
-                                    .clone(),
+                                    .to_string(),
                             );
                         }
                         Some(4) => {
@@ -1616,7 +1805,7 @@ fn enter_keybinds(app: &mut App, index: usize, empty: bool) -> Result<bool> {
                             {
                                 vec![]
                             } else {
-                                let mut modifier = match app
+                                let mut modifier: Vec<usize> = match app
                                     .current_config
                                     .keybind
                                     .get(index)
@@ -1625,56 +1814,21 @@ fn enter_keybinds(app: &mut App, index: usize, empty: bool) -> Result<bool> {
                                     .as_ref()
                                     .unwrap()
                                 {
-                                    Modifier::Single(s) => {
-                                        match s.as_str() {
-                                            "None" => vec![0],
-                                            "Shift" => vec![1],
-                                            "Control" => vec![2],
-                                            "Mod1" | "Alt" => vec![3],
-                                            //"Mod2" => xlib::Mod2Mask,     // NOTE: we are ignoring the state of Numlock
-                                            //"NumLock" => xlib::Mod2Mask,  // this is left here as a reminder
-                                            "Mod3" => vec![4],
-                                            "Mod4" | "Super" => vec![5],
-                                            "Mod5" => vec![6],
-                                            "modkey" => vec![7],
-                                            _ => vec![],
-                                        }
-                                    }
+                                    Modifier::Single(s) => modifier_index(s).into_iter().collect(),
                                     Modifier::List(s) => {
-                                        let mut vec = vec![];
-                                        for s in s {
-                                            match s.as_str() {
-                                                "None" => vec.push(0),
-                                                "Shift" => vec.push(1),
-                                                "Control" => vec.push(2),
-                                                "Mod1" | "Alt" => vec.push(3),
-                                                //"Mod2" => xlib::Mod2Mask,     // NOTE: we are ignoring the state of Numlock
-                                                //"NumLock" => xlib::Mod2Mask,  // this is left here as a reminder
-                                                "Mod3" => vec.push(4),
-                                                "Mod4" | "Super" => vec.push(5),
-                                                "Mod5" => vec.push(6),
-                                                "modkey" => vec.push(7),
-                                                _ => (),
-                                            }
-                                        }
-
-                                        vec
+                                        s.iter().filter_map(|s| modifier_index(s)).collect()
                                     }
                                 };
 
-                                let modkey_index = match app.current_config.modkey.as_str() {
-                                    "Shift" => 1,
-                                    "Control" => 2,
-                                    "Mod1" | "Alt" => 3,
-                                    "Mod3" => 4,
-                                    "Mod4" | "Super" => 5,
-                                    "Mod5" => 6,
-                                    _ => bail!("unexpected value"),
-                                };
+                                let modkey_index =
+                                    match modifier_index(&app.current_config.modkey.to_string()) {
+                                        Some(i) if i != 0 && i != MODKEY_INDEX => i,
+                                        _ => bail!("unexpected value"),
+                                    };
 
                                 if modifier.contains(&modkey_index) {
-                                    modifier.push(7);
-                                } else if modifier.contains(&7) {
+                                    modifier.push(MODKEY_INDEX);
+                                } else if modifier.contains(&MODKEY_INDEX) {
                                     modifier.push(modkey_index);
                                 }
 
@@ -1724,7 +1878,7 @@ fn enter_keybinds(app: &mut App, index: usize, empty: bool) -> Result<bool> {
                             {
                                 vec![]
                             } else {
-                                let mut modifier = match app
+                                let mut modifier: Vec<usize> = match app
                                     .current_config
                                     .keybind
                                     .get(index)
@@ -1733,56 +1887,21 @@ fn enter_keybinds(app: &mut App, index: usize, empty: bool) -> Result<bool> {
                                     .as_ref()
                                     .unwrap()
                                 {
-                                    Modifier::Single(s) => {
-                                        match s.as_str() {
-                                            "None" => vec![0],
-                                            "Shift" => vec![1],
-                                            "Control" => vec![2],
-                                            "Mod1" | "Alt" => vec![3],
-                                            //"Mod2" => xlib::Mod2Mask,     // NOTE: we are ignoring the state of Numlock
-                                            //"NumLock" => xlib::Mod2Mask,  // this is left here as a reminder
-                                            "Mod3" => vec![4],
-                                            "Mod4" | "Super" => vec![5],
-                                            "Mod5" => vec![6],
-                                            "modkey" => vec![7],
-                                            _ => vec![],
-                                        }
-                                    }
+                                    Modifier::Single(s) => modifier_index(s).into_iter().collect(),
                                     Modifier::List(s) => {
-                                        let mut vec = vec![];
-                                        for s in s {
-                                            match s.as_str() {
-                                                "None" => vec.push(0),
-                                                "Shift" => vec.push(1),
-                                                "Control" => vec.push(2),
-                                                "Mod1" | "Alt" => vec.push(3),
-                                                //"Mod2" => xlib::Mod2Mask,     // NOTE: we are ignoring the state of Numlock
-                                                //"NumLock" => xlib::Mod2Mask,  // this is left here as a reminder
-                                                "Mod3" => vec.push(4),
-                                                "Mod4" | "Super" => vec.push(5),
-                                                "Mod5" => vec.push(6),
-                                                "modkey" => vec.push(7),
-                                                _ => (),
-                                            }
-                                        }
-
-                                        vec
+                                        s.iter().filter_map(|s| modifier_index(s)).collect()
                                     }
                                 };
 
-                                let modkey_index = match app.current_config.modkey.as_str() {
-                                    "Shift" => 1,
-                                    "Control" => 2,
-                                    "Mod1" | "Alt" => 3,
-                                    "Mod3" => 4,
-                                    "Mod4" | "Super" => 5,
-                                    "Mod5" => 6,
-                                    _ => bail!("unexpected value"),
-                                };
+                                let modkey_index =
+                                    match modifier_index(&app.current_config.modkey.to_string()) {
+                                        Some(i) if i != 0 && i != MODKEY_INDEX => i,
+                                        _ => bail!("unexpected value"),
+                                    };
 
                                 if modifier.contains(&modkey_index) {
-                                    modifier.push(7);
-                                } else if modifier.contains(&7) {
+                                    modifier.push(MODKEY_INDEX);
+                                } else if modifier.contains(&MODKEY_INDEX) {
                                     modifier.push(modkey_index);
                                 }
 
@@ -1824,10 +1943,174 @@ fn enter_keybinds(app: &mut App, index: usize, empty: bool) -> Result<bool> {
     Ok(false)
 }
 
+fn enter_layout_definitions(app: &mut App, index: usize, empty: bool) -> Result<bool> {
+    if empty {
+        if let Some(2) = app.config_list_state.selected() {
+            app.current_config
+                .layout_definitions
+                .push(LayoutDefinition::default());
+            app.current_window = Window::LayoutDefinitions {
+                index: 0,
+                empty: false,
+            };
+        }
+        return Ok(false);
+    }
+    let definition = app
+        .current_config
+        .layout_definitions
+        .get_mut(index)
+        .try_unwrap()?;
+    match app.current_popup {
+        Some(0) => {
+            definition.name = if let PopupState::String(s) = &app.current_popup_state {
+                s.clone()
+            } else {
+                bail!("Invalid popup state")
+            };
+            app.current_popup = None;
+            app.current_popup_state = PopupState::None;
+        }
+        Some(1) => {
+            if let (Some(main), PopupState::Int { current, .. }) =
+                (definition.columns.main.as_mut(), &app.current_popup_state)
+            {
+                main.count = usize::try_from(*current)?;
+            } else {
+                bail!("Invalid popup state")
+            }
+            app.current_popup = None;
+            app.current_popup_state = PopupState::None;
+        }
+        Some(2) => {
+            let Some(Some(size)) = size_input(&app.current_popup_state)? else {
+                return Ok(false);
+            };
+            definition.columns.main.as_mut().try_unwrap()?.size = size;
+            app.current_popup = None;
+            app.current_popup_state = PopupState::None;
+        }
+        Some(_) => {}
+        None => match app.config_list_state.selected() {
+            Some(2) => {
+                app.current_popup = Some(0);
+                app.current_popup_state = PopupState::String(definition.name.clone());
+            }
+            Some(3) => definition.flip = cycle(&Flip::ALL, definition.flip),
+            Some(4) => definition.rotate = cycle(&Rotation::ALL, definition.rotate),
+            Some(5) => definition.reserve = cycle(&Reserve::ALL, definition.reserve),
+            Some(6) => definition.columns.flip = cycle(&Flip::ALL, definition.columns.flip),
+            Some(7) => {
+                definition.columns.rotate = cycle(&Rotation::ALL, definition.columns.rotate);
+            }
+            Some(8) => {
+                definition.columns.main = match definition.columns.main {
+                    Some(_) => None,
+                    None => Some(Main::default()),
+                };
+            }
+            Some(9) => {
+                if let Some(main) = &definition.columns.main {
+                    app.current_popup = Some(1);
+                    app.current_popup_state = PopupState::Int {
+                        current: isize::try_from(main.count)?,
+                        min: 1,
+                        max: 10,
+                    };
+                }
+            }
+            Some(10) => {
+                if let Some(main) = &definition.columns.main {
+                    app.current_popup = Some(2);
+                    app.current_popup_state = PopupState::String(main.size.to_string());
+                }
+            }
+            Some(11) => {
+                if let Some(main) = definition.columns.main.as_mut() {
+                    main.split = cycle_optional(&SplitAxis::ALL, main.split);
+                }
+            }
+            Some(12) => {
+                definition.columns.stack.split =
+                    cycle_optional(&SplitAxis::ALL, definition.columns.stack.split);
+            }
+            Some(13) => {
+                definition.columns.second_stack = match definition.columns.second_stack {
+                    Some(_) => None,
+                    None => Some(SecondStack::default()),
+                };
+            }
+            Some(14) => {
+                if let Some(second_stack) = definition.columns.second_stack.as_mut() {
+                    second_stack.split = cycle(&SplitAxis::ALL, second_stack.split);
+                }
+            }
+            Some(16) => {
+                app.current_config
+                    .layout_definitions
+                    .push(LayoutDefinition::default());
+            }
+            Some(17) => {
+                app.current_config
+                    .layout_definitions
+                    .try_remove_temp(index)?;
+                if app.current_config.layout_definitions.is_empty() {
+                    app.current_window = Window::LayoutDefinitions {
+                        index: 0,
+                        empty: true,
+                    };
+                    app.config_list_state.select(None);
+                } else if index == app.current_config.layout_definitions.len() {
+                    app.current_window.try_decrement_index();
+                }
+            }
+            _ => {}
+        },
+    }
+
+    Ok(false)
+}
+
+/// The value following `current` in `all`, wrapping around at the end.
+fn cycle<T: Copy + PartialEq>(all: &[T], current: T) -> T {
+    let position = all.iter().position(|v| *v == current).unwrap_or_default();
+    all.get(position + 1)
+        .or_else(|| all.first())
+        .copied()
+        .unwrap_or(current)
+}
+
+/// Like [`cycle`] with an unset value between the last and the first entry.
+fn cycle_optional<T: Copy + PartialEq>(all: &[T], current: Option<T>) -> Option<T> {
+    match current {
+        None => all.first().copied(),
+        Some(current) => {
+            let position = all.iter().position(|v| *v == current)?;
+            all.get(position + 1).copied()
+        }
+    }
+}
+
+fn size_popup_state(size: Option<Size>) -> PopupState {
+    PopupState::String(size.map(|s| s.to_string()).unwrap_or_default())
+}
+
+/// Reads the input of a size popup, an empty input unsets the size.
+/// Returns `None` while the input does not parse, so that the popup stays open.
+fn size_input(state: &PopupState) -> Result<Option<Option<Size>>> {
+    let PopupState::String(s) = state else {
+        bail!("Invalid popup state")
+    };
+    if s.trim().is_empty() {
+        return Ok(Some(None));
+    }
+    Ok(s.parse().ok().map(Some))
+}
+
 fn space(app: &mut App) -> Result<bool> {
     match app.current_window {
-        Window::Home => {
-            if let Some(9) = app.current_popup {
+        Window::Home => match app.current_popup {
+            Some(9) => {
                 if let PopupState::MultiList(l) = &mut app.current_popup_state {
                     if l.selected.contains(&l.liststate.selected().unwrap_or(14)) {
                         let index = l
@@ -1843,7 +2126,10 @@ fn space(app: &mut App) -> Result<bool> {
                     bail!("Invalid popup state");
                 }
             }
-        }
+            // the state path is a text input, a space is typed like any other character
+            Some(STATE_PATH_POPUP) => return char(app, ' '),
+            _ => {}
+        },
         Window::Workspaces { .. } => {
             if let Some(6) = app.current_popup {
                 if let PopupState::MultiList(l) = &mut app.current_popup_state {
@@ -1882,15 +2168,11 @@ fn space(app: &mut App) -> Result<bool> {
             Some(2) => {
                 if let PopupState::MultiList(s) = &mut app.current_popup_state {
                     if let Some(i) = s.liststate.selected() {
-                        let modkey_index = match app.current_config.modkey.as_str() {
-                            "Shift" => 1,
-                            "Control" => 2,
-                            "Mod1" | "Alt" => 3,
-                            "Mod3" => 4,
-                            "Mod4" | "Super" => 5,
-                            "Mod5" => 6,
-                            _ => bail!("unexpected value"),
-                        };
+                        let modkey_index =
+                            match modifier_index(&app.current_config.modkey.to_string()) {
+                                Some(i) if i != 0 && i != MODKEY_INDEX => i,
+                                _ => bail!("unexpected value"),
+                            };
 
                         if i == 0 {
                             s.selected.clear();
@@ -1942,13 +2224,20 @@ fn char(app: &mut App, c: char) -> Result<bool> {
         Window::Home => match app.current_popup {
             Some(2) => {
                 if let PopupState::String(s) = &mut app.current_popup_state {
-                    if "1234567890,.".contains(c) {
+                    if SIZE_CHARS.contains(c) {
                         s.push(c);
                     }
                 } else {
                     bail!("Invalid popup state");
                 }
             }
+            Some(STATE_PATH_POPUP) => {
+                if let PopupState::String(s) = &mut app.current_popup_state {
+                    s.push(c);
+                } else {
+                    bail!("Invalid popup state");
+                }
+            }
             Some(_) => {}
             None => match c {
                 'q' => {
@@ -1956,14 +2245,14 @@ fn char(app: &mut App, c: char) -> Result<bool> {
                 }
                 's' => {
                     save_to_file(&app.current_config)?;
-                    app.current_popup = Some(15);
+                    app.current_popup = Some(SAVED_POPUP);
                     app.current_popup_state = PopupState::None;
                 }
                 _ => {}
             },
         },
         Window::Workspaces { .. } => match app.current_popup {
-            Some(0..=5) => {
+            Some(0..=4) => {
                 if let PopupState::String(s) = &mut app.current_popup_state {
                     if "1234567890,.".contains(c) {
                         s.push(c);
@@ -1972,6 +2261,15 @@ fn char(app: &mut App, c: char) -> Result<bool> {
                     bail!("Invalid popup state");
                 }
             }
+            Some(5) => {
+                if let PopupState::String(s) = &mut app.current_popup_state {
+                    if SIZE_CHARS.contains(c) {
+                        s.push(c);
+                    }
+                } else {
+                    bail!("Invalid popup state");
+                }
+            }
             Some(_) => {}
             None => match c {
                 'q' => {
@@ -1979,7 +2277,7 @@ fn char(app: &mut App, c: char) -> Result<bool> {
                 }
                 's' => {
                     save_to_file(&app.current_config)?;
-                    app.current_popup = Some(15);
+                    app.current_popup = Some(SAVED_POPUP);
                     app.current_popup_state = PopupState::None;
                 }
                 _ => {}
@@ -2000,7 +2298,7 @@ fn char(app: &mut App, c: char) -> Result<bool> {
                 }
                 's' => {
                     save_to_file(&app.current_config)?;
-                    app.current_popup = Some(15);
+                    app.current_popup = Some(SAVED_POPUP);
                     app.current_popup_state = PopupState::None;
                 }
                 _ => {}
@@ -2014,6 +2312,24 @@ fn char(app: &mut App, c: char) -> Result<bool> {
                     bail!("Invalid popup state");
                 }
             }
+            Some(3) => {
+                if let PopupState::String(s) = &mut app.current_popup_state {
+                    if "1234567890-".contains(c) {
+                        s.push(c);
+                    }
+                } else {
+                    bail!("Invalid popup state");
+                }
+            }
+            Some(5) => {
+                if let PopupState::String(s) = &mut app.current_popup_state {
+                    if "1234567890,-".contains(c) {
+                        s.push(c);
+                    }
+                } else {
+                    bail!("Invalid popup state");
+                }
+            }
             Some(_) => {}
             None => match c {
                 'q' => {
@@ -2021,7 +2337,7 @@ fn char(app: &mut App, c: char) -> Result<bool> {
                 }
                 's' => {
                     save_to_file(&app.current_config)?;
-                    app.current_popup = Some(15);
+                    app.current_popup = Some(SAVED_POPUP);
                     app.current_popup_state = PopupState::None;
                 }
                 _ => {}
@@ -2037,7 +2353,7 @@ fn char(app: &mut App, c: char) -> Result<bool> {
             }
             Some(2..=5) => {
                 if let PopupState::String(s) = &mut app.current_popup_state {
-                    if "1234567890,.".contains(c) {
+                    if SIZE_CHARS.contains(c) {
                         s.push(c);
                     }
                 } else {
@@ -2050,7 +2366,7 @@ fn char(app: &mut App, c: char) -> Result<bool> {
                 }
                 's' => {
                     save_to_file(&app.current_config)?;
-                    app.current_popup = Some(15);
+                    app.current_popup = Some(SAVED_POPUP);
                     app.current_popup_state = PopupState::None;
                 }
                 _ => {}
@@ -2071,7 +2387,37 @@ fn char(app: &mut App, c: char) -> Result<bool> {
                 }
                 's' => {
                     save_to_file(&app.current_config)?;
-                    app.current_popup = Some(15);
+                    app.current_popup = Some(SAVED_POPUP);
+                    app.current_popup_state = PopupState::None;
+                }
+                _ => {}
+            },
+            _ => {}
+        },
+        Window::LayoutDefinitions { .. } => match app.current_popup {
+            Some(0) => {
+                if let PopupState::String(s) = &mut app.current_popup_state {
+                    s.push(c);
+                } else {
+                    bail!("Invalid popup state")
+                }
+            }
+            Some(2) => {
+                if let PopupState::String(s) = &mut app.current_popup_state {
+                    if SIZE_CHARS.contains(c) {
+                        s.push(c);
+                    }
+                } else {
+                    bail!("Invalid popup state");
+                }
+            }
+            None => match c {
+                'q' => {
+                    return Ok(true);
+                }
+                's' => {
+                    save_to_file(&app.current_config)?;
+                    app.current_popup = Some(SAVED_POPUP);
                     app.current_popup_state = PopupState::None;
                 }
                 _ => {}
@@ -2086,7 +2432,7 @@ fn char(app: &mut App, c: char) -> Result<bool> {
 fn backspace(app: &mut App) -> Result<bool> {
     match app.current_window {
         Window::Home => {
-            if let Some(2) = app.current_popup {
+            if let Some(2 | 16) = app.current_popup {
                 if let PopupState::String(s) = &mut app.current_popup_state {
                     s.pop();
                 } else {
@@ -2139,15 +2485,14 @@ fn backspace(app: &mut App) -> Result<bool> {
             }
         }
         Window::WindowRules { .. } => match app.current_popup {
-            Some(0 | 1) => {
+            Some(0 | 1 | 3 | 5) => {
                 if let PopupState::String(s) = &mut app.current_popup_state {
                     s.pop();
                 } else {
                     bail!("Invalid popup state");
                 }
             }
-            Some(2) => {}
-            Some(3) => {}
+            Some(2 | 4) => {}
             None => app.current_window = Window::Home,
             _ => {}
         },
@@ -2173,6 +2518,17 @@ fn backspace(app: &mut App) -> Result<bool> {
             None => app.current_window = Window::Home,
             _ => {}
         },
+        Window::LayoutDefinitions { .. } => match app.current_popup {
+            Some(0 | 2) => {
+                if let PopupState::String(s) = &mut app.current_popup_state {
+                    s.pop();
+                } else {
+                    bail!("Invalid popup state")
+                }
+            }
+            None => app.current_window = Window::Home,
+            _ => {}
+        },
     }
 
     Ok(false)
@@ -2180,6 +2536,12 @@ fn backspace(app: &mut App) -> Result<bool> {
 
 fn delete(app: &mut App) -> Result<bool> {
     match app.current_window {
+        Window::Home => match app.config_list_state.selected().unwrap_or(0) {
+            2 => app.current_config.max_window_width = None,
+            13 => app.current_config.create_follows_cursor = None,
+            16 => app.current_config.state_path = None,
+            _ => {}
+        },
         Window::Workspaces { index, .. } => match app.config_list_state.selected().unwrap_or(0) {
             6 => {
                 app.current_config
@@ -2238,6 +2600,51 @@ fn delete(app: &mut App) -> Result<bool> {
                     .try_unwrap()?
                     .spawn_on_tag = None;
             }
+            6 => {
+                app.current_config
+                    .window_rules
+                    .as_mut()
+                    .try_unwrap()?
+                    .get_mut(index)
+                    .try_unwrap()?
+                    .spawn_on_workspace = None;
+            }
+            7 => {
+                app.current_config
+                    .window_rules
+                    .as_mut()
+                    .try_unwrap()?
+                    .get_mut(index)
+                    .try_unwrap()?
+                    .spawn_as_type = None;
+            }
+            8 => {
+                app.current_config
+                    .window_rules
+                    .as_mut()
+                    .try_unwrap()?
+                    .get_mut(index)
+                    .try_unwrap()?
+                    .spawn_sticky = None;
+            }
+            9 => {
+                app.current_config
+                    .window_rules
+                    .as_mut()
+                    .try_unwrap()?
+                    .get_mut(index)
+                    .try_unwrap()?
+                    .spawn_fullscreen = None;
+            }
+            10 => {
+                app.current_config
+                    .window_rules
+                    .as_mut()
+                    .try_unwrap()?
+                    .get_mut(index)
+                    .try_unwrap()?
+                    .spawn_floating_geometry = None;
+            }
             _ => {}
         },
         Window::Scratchpads { index, .. } => match app.config_list_state.selected().unwrap_or(0) {
@@ -2279,8 +2686,43 @@ fn delete(app: &mut App) -> Result<bool> {
             }
             _ => {}
         },
+        Window::LayoutDefinitions { index, .. } => {
+            let columns = &mut app
+                .current_config
+                .layout_definitions
+                .get_mut(index)
+                .try_unwrap()?
+                .columns;
+            match app.config_list_state.selected().unwrap_or(0) {
+                8 => columns.main = None,
+                11 => {
+                    if let Some(main) = columns.main.as_mut() {
+                        main.split = None;
+                    }
+                }
+                12 => columns.stack.split = None,
+                13 => columns.second_stack = None,
+                _ => {}
+            }
+        }
         _ => {}
     }
 
     Ok(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifier_rows_round_trip() {
+        for (i, name) in MODIFIER_ROWS.iter().enumerate() {
+            assert_eq!(modifier_index(name), Some(i));
+            assert_eq!(modifier_name(i).unwrap(), *name);
+        }
+        assert_eq!(modifier_index("super"), Some(5));
+        assert_eq!(modifier_index("NumLock"), None);
+        assert!(modifier_name(MODIFIER_ROWS.len()).is_err());
+    }
+}
@@ -17,15 +17,24 @@ use tui::{
 };
 
 use crate::config::Config;
+use crate::config::command::ArgumentKind;
 use crate::config::filehandler::load;
+use crate::config::layout::{LAYOUTS, LayoutName};
 use crate::config::modifier::Modifier as KeyModifier;
-use crate::config::values::{FocusBehaviour, InsertBehavior, LayoutMode};
+use crate::config::values::{FocusBehaviour, InsertBehavior, LayoutMode, Size};
 use crate::utils;
 use crate::utils::TryUnwrap;
 
 mod key_handler;
 mod popups;
 
+/// The popup shown in every window after saving, closed by the next key.
+const SAVED_POPUP: u8 = 15;
+/// The text input for `state_path` in the Home window. The other Home popups share the
+/// number of the row which opens them, this one is numbered past the rows so adding a
+/// row doesn't change it.
+const STATE_PATH_POPUP: u8 = 100;
+
 #[derive(Clone)]
 pub enum PopupState {
     None,
@@ -55,6 +64,7 @@ pub enum Window {
     WindowRules { index: usize, empty: bool },
     Scratchpads { index: usize, empty: bool },
     KeyBinds { index: usize, empty: bool },
+    LayoutDefinitions { index: usize, empty: bool },
 }
 
 impl Window {
@@ -65,7 +75,8 @@ impl Window {
             | Window::Tags { index, .. }
             | Window::WindowRules { index, .. }
             | Window::Scratchpads { index, .. }
-            | Window::KeyBinds { index, .. } => {
+            | Window::KeyBinds { index, .. }
+            | Window::LayoutDefinitions { index, .. } => {
                 *index += 1;
             }
         }
@@ -78,7 +89,8 @@ impl Window {
             | Window::Tags { index, .. }
             | Window::WindowRules { index, .. }
             | Window::Scratchpads { index, .. }
-            | Window::KeyBinds { index, .. } => {
+            | Window::KeyBinds { index, .. }
+            | Window::LayoutDefinitions { index, .. } => {
                 *index -= 1;
             }
         }
@@ -91,7 +103,8 @@ impl Window {
             | Window::Tags { index, .. }
             | Window::WindowRules { index, .. }
             | Window::Scratchpads { index, .. }
-            | Window::KeyBinds { index, .. } => {
+            | Window::KeyBinds { index, .. }
+            | Window::LayoutDefinitions { index, .. } => {
                 *index = new_index;
             }
         }
@@ -162,7 +175,10 @@ impl App<'_> {
                     .border_style(Style::default().fg(Color::White))
                     .border_type(BorderType::Rounded)
                     .style(Style::default().bg(Color::Black))
-                    .title("LeftWM-Config");
+                    .title(match crate::config::profile::selected() {
+                        Some(profile) => format!("LeftWM-Config - profile {profile}"),
+                        None => "LeftWM-Config".to_string(),
+                    });
 
                 let list = List::new(self.config_list.clone())
                     .block(Block::default().borders(Borders::NONE))
@@ -185,7 +201,9 @@ impl App<'_> {
 
                         vec![Spans::from(spans)]
                     }
-                    Window::WindowRules { .. } | Window::Scratchpads { .. } => {
+                    Window::WindowRules { .. }
+                    | Window::Scratchpads { .. }
+                    | Window::LayoutDefinitions { .. } => {
                         vec![Spans::from(vec![
                             Span::raw("Exit: q, "),
                             Span::raw("Save: s, "),
@@ -210,7 +228,11 @@ impl App<'_> {
                         vec![Spans::from(spans)]
                     }
                     Window::Home => {
-                        let mut spans = vec![Span::raw("Exit: q, "), Span::raw("Save: s")];
+                        let mut spans = vec![
+                            Span::raw("Exit: q, "),
+                            Span::raw("Save: s, "),
+                            Span::raw("Delete Optional Value: Delete"),
+                        ];
 
                         if let Some(9) = self.current_popup {
                             spans.push(Span::raw(", Space: Toggle item"));
@@ -249,7 +271,11 @@ impl App<'_> {
                                     f,
                                     true,
                                 ),
-                                2 => popups::max_window_width(&mut self.current_popup_state, f),
+                                2 => popups::size(
+                                    &mut self.current_popup_state,
+                                    "Max Window Width",
+                                    f,
+                                ),
                                 // 3, 4 and 5 dont need a popup
                                 6 => popups::focus_behavior(
                                     &self.current_config,
@@ -266,15 +292,27 @@ impl App<'_> {
                                     &mut self.current_popup_state,
                                     f,
                                 ),
-                                9 => popups::layouts(&mut self.current_popup_state, f),
-                                15 => popups::saved(f),
+                                9 => popups::layouts(
+                                    &layout_choices(
+                                        &self.current_config,
+                                        &self.current_config.layouts,
+                                    ),
+                                    &mut self.current_popup_state,
+                                    f,
+                                ),
+                                SAVED_POPUP => popups::saved(f),
+                                STATE_PATH_POPUP => popups::text_input(
+                                    &mut self.current_popup_state,
+                                    "State Path".to_string(),
+                                    f,
+                                ),
                                 _ => Ok(()),
                             }
                         } else {
                             Ok(())
                         }
                     }
-                    Window::Workspaces { .. } => {
+                    Window::Workspaces { index, .. } => {
                         if let Some(s) = self.current_popup {
                             match s {
                                 0 => popups::text_input(
@@ -302,13 +340,26 @@ impl App<'_> {
                                     "Id".to_string(),
                                     f,
                                 ),
-                                5 => popups::text_input(
+                                5 => popups::size(
                                     &mut self.current_popup_state,
-                                    "Max window width".to_string(),
+                                    "Max Window Width",
                                     f,
                                 ),
-                                6 => popups::layouts(&mut self.current_popup_state, f),
-                                15 => popups::saved(f),
+                                6 => {
+                                    let current = self
+                                        .current_config
+                                        .workspaces
+                                        .as_ref()
+                                        .and_then(|w| w.get(index))
+                                        .and_then(|w| w.layouts.as_deref())
+                                        .unwrap_or_default();
+                                    popups::layouts(
+                                        &layout_choices(&self.current_config, current),
+                                        &mut self.current_popup_state,
+                                        f,
+                                    )
+                                }
+                                SAVED_POPUP => popups::saved(f),
                                 _ => Ok(()),
                             }
                         } else {
@@ -316,7 +367,7 @@ impl App<'_> {
                         }
                     }
                     Window::Tags { .. } => {
-                        if let Some(15) = self.current_popup {
+                        if let Some(SAVED_POPUP) = self.current_popup {
                             popups::saved(f)
                         } else if self.current_popup.is_some() {
                             popups::text_input(&mut self.current_popup_state, "Name".to_string(), f)
@@ -324,7 +375,7 @@ impl App<'_> {
                             Ok(())
                         }
                     }
-                    Window::WindowRules { .. } => match self.current_popup {
+                    Window::WindowRules { index, .. } => match self.current_popup {
                         Some(0) => popups::text_input(
                             &mut self.current_popup_state,
                             "Title".to_string(),
@@ -340,7 +391,23 @@ impl App<'_> {
                             "Spawn on tag".to_string(),
                             f,
                         ),
-                        Some(15) => popups::saved(f),
+                        Some(3) => popups::text_input(
+                            &mut self.current_popup_state,
+                            "Spawn on workspace (id)".to_string(),
+                            f,
+                        ),
+                        Some(4) => popups::window_type(
+                            &self.current_config,
+                            index,
+                            &mut self.current_popup_state,
+                            f,
+                        ),
+                        Some(5) => popups::text_input(
+                            &mut self.current_popup_state,
+                            "Floating geometry (x,y,width,height)".to_string(),
+                            f,
+                        ),
+                        Some(SAVED_POPUP) => popups::saved(f),
                         _ => Ok(()),
                     },
                     Window::Scratchpads { .. } => match self.current_popup {
@@ -352,23 +419,24 @@ impl App<'_> {
                             "Value".to_string(),
                             f,
                         ),
-                        Some(2) => {
-                            popups::text_input(&mut self.current_popup_state, "X".to_string(), f)
-                        }
-                        Some(3) => {
-                            popups::text_input(&mut self.current_popup_state, "Y".to_string(), f)
+                        Some(2) => popups::size(&mut self.current_popup_state, "X", f),
+                        Some(3) => popups::size(&mut self.current_popup_state, "Y", f),
+                        Some(4) => popups::size(&mut self.current_popup_state, "Width", f),
+                        Some(5) => popups::size(&mut self.current_popup_state, "Height", f),
+                        Some(SAVED_POPUP) => popups::saved(f),
+                        _ => Ok(()),
+                    },
+                    Window::LayoutDefinitions { .. } => match self.current_popup {
+                        Some(0) => {
+                            popups::text_input(&mut self.current_popup_state, "Name".to_string(), f)
                         }
-                        Some(4) => popups::text_input(
+                        Some(1) => popups::counter(
                             &mut self.current_popup_state,
-                            "Width".to_string(),
+                            "Main count".to_string(),
                             f,
                         ),
-                        Some(5) => popups::text_input(
-                            &mut self.current_popup_state,
-                            "Height".to_string(),
-                            f,
-                        ),
-                        Some(15) => popups::saved(f),
+                        Some(2) => popups::size(&mut self.current_popup_state, "Main Size", f),
+                        Some(SAVED_POPUP) => popups::saved(f),
                         _ => Ok(()),
                     },
                     Window::KeyBinds { index, .. } => match self.current_popup {
@@ -378,16 +446,20 @@ impl App<'_> {
                             &mut self.current_popup_state,
                             f,
                         ),
-                        Some(1) => popups::text_input(
-                            &mut self.current_popup_state,
-                            "Value".to_string(),
-                            f,
-                        ),
+                        Some(1) => match self.current_config.keybind.get(index) {
+                            Some(keybind) => popups::keybind_value(
+                                &mut self.current_popup_state,
+                                &self.current_config,
+                                keybind,
+                                f,
+                            ),
+                            None => Ok(()),
+                        },
                         Some(2) => popups::keybind_modkey(&mut self.current_popup_state, f),
                         Some(3) => {
                             popups::keybind_key(&mut self.current_popup_state, "Key".to_string(), f)
                         }
-                        Some(15) => popups::saved(f),
+                        Some(SAVED_POPUP) => popups::saved(f),
                         _ => Ok(()),
                     },
                 } {
@@ -411,7 +483,7 @@ impl App<'_> {
             Window::Home => Vec::from([
                 ListItem::new(format!(
                     "Modkey - {}",
-                    format_modkey_name(self.current_config.modkey.clone())
+                    format_modkey_name(self.current_config.modkey.to_string())
                 )),
                 ListItem::new(format!(
                     "Mousekey - {}",
@@ -424,7 +496,7 @@ impl App<'_> {
                     )
                 )),
                 ListItem::new(match &self.current_config.max_window_width {
-                    Some(w) => format!("Max Window Width - {w:?}"),
+                    Some(w) => format!("Max Window Width - {w}"),
                     None => "Max Window Width - not set".to_string(),
                 }),
                 ListItem::new(format!(
@@ -467,6 +539,34 @@ impl App<'_> {
                     "Layouts - {} set",
                     self.current_config.layouts.len()
                 )),
+                ListItem::new(format!(
+                    "Disable Window Snap - {}",
+                    self.current_config.disable_window_snap
+                )),
+                ListItem::new(format!(
+                    "Single Window Border - {}",
+                    self.current_config.single_window_border
+                )),
+                ListItem::new(format!(
+                    "Sloppy Mouse Follows Focus - {}",
+                    self.current_config.sloppy_mouse_follows_focus
+                )),
+                ListItem::new(match self.current_config.create_follows_cursor {
+                    Some(b) => format!("Create Follows Cursor - {b}"),
+                    None => "Create Follows Cursor - not set".to_string(),
+                }),
+                ListItem::new(format!(
+                    "Auto Derive Workspaces - {}",
+                    self.current_config.auto_derive_workspaces
+                )),
+                ListItem::new(format!(
+                    "Reposition Cursor On Resize - {}",
+                    self.current_config.reposition_cursor_on_resize
+                )),
+                ListItem::new(match &self.current_config.state_path {
+                    Some(p) => format!("State Path - {}", p.display()),
+                    None => "State Path - not set".to_string(),
+                }),
                 ListItem::new(match &self.current_config.workspaces {
                     Some(v) => format!("Workspaces - {} set", v.len()),
                     None => "Workspaces".to_string(),
@@ -487,6 +587,10 @@ impl App<'_> {
                     "Keybinds - {} set",
                     self.current_config.keybind.len()
                 )),
+                ListItem::new(format!(
+                    "Layout Definitions - {} set",
+                    self.current_config.layout_definitions.len()
+                )),
             ]),
             Window::Workspaces { index, .. } => {
                 let current_workspace = if let Some(w) = &self.current_config.workspaces {
@@ -518,7 +622,10 @@ impl App<'_> {
                         ListItem::new(format!("Width - {}", c.width)),
                         ListItem::new(format!("Height - {}", c.height)),
                         ListItem::new(format!("Id - {:?}", c.id)),
-                        ListItem::new(format!("Max Window Width - {:?}", c.max_window_width)),
+                        ListItem::new(format!(
+                            "Max Window Width - {}",
+                            size_or_not_set(c.max_window_width)
+                        )),
                         ListItem::new(format!(
                             "Layouts - {}",
                             if c.layouts.is_some() {
@@ -611,6 +718,29 @@ impl App<'_> {
                             "Spawn floating - {}",
                             rule.spawn_floating.unwrap_or(false)
                         )),
+                        ListItem::new(format!(
+                            "Spawn on workspace - {:?}",
+                            rule.spawn_on_workspace
+                        )),
+                        ListItem::new(format!("Spawn as type - {:?}", rule.spawn_as_type)),
+                        ListItem::new(format!(
+                            "Spawn sticky - {}",
+                            rule.spawn_sticky.unwrap_or(false)
+                        )),
+                        ListItem::new(format!(
+                            "Spawn fullscreen - {}",
+                            rule.spawn_fullscreen.unwrap_or(false)
+                        )),
+                        ListItem::new(format!(
+                            "Floating geometry - {}",
+                            rule.spawn_floating_geometry.map_or_else(
+                                || "not set".to_string(),
+                                |g| format!("{}, {} {}x{}", g.x, g.y, g.width, g.height)
+                            )
+                        )),
+                        ListItem::new("--------------------------"),
+                        ListItem::new("Add new rule"),
+                        ListItem::new("Delete this rule"),
                         ListItem::new("--------------------------"),
                     ];
 
@@ -638,8 +768,22 @@ impl App<'_> {
                         vec.push(ListItem::new("--------------------------"));
                     }
 
-                    vec.push(ListItem::new("Add new rule"));
-                    vec.push(ListItem::new("Delete this rule"));
+                    if rule.spawn_fullscreen == Some(true) && rule.spawn_floating == Some(true) {
+                        vec.push(ListItem::new("WARNING:").style(Style::default().fg(Color::Red)));
+                        vec.push(
+                            ListItem::new("Both fullscreen and floating are set")
+                                .style(Style::default().fg(Color::Red)),
+                        );
+                        vec.push(ListItem::new("--------------------------"));
+                    }
+                    if rule.spawn_floating_geometry.is_some() && rule.spawn_floating != Some(true) {
+                        vec.push(ListItem::new("WARNING:").style(Style::default().fg(Color::Red)));
+                        vec.push(
+                            ListItem::new("Geometry is only used for floating windows")
+                                .style(Style::default().fg(Color::Red)),
+                        );
+                        vec.push(ListItem::new("--------------------------"));
+                    }
 
                     vec
                 }
@@ -669,10 +813,10 @@ impl App<'_> {
                         ListItem::new("--------------------------"),
                         ListItem::new(format!("Name - {}", scratchpad.name)),
                         ListItem::new(format!("Value - {}", scratchpad.value)),
-                        ListItem::new(format!("X - {:?}", scratchpad.x)),
-                        ListItem::new(format!("Y - {:?}", scratchpad.y)),
-                        ListItem::new(format!("Width - {:?}", scratchpad.width)),
-                        ListItem::new(format!("Height - {:?}", scratchpad.height)),
+                        ListItem::new(format!("X - {}", size_or_not_set(scratchpad.x))),
+                        ListItem::new(format!("Y - {}", size_or_not_set(scratchpad.y))),
+                        ListItem::new(format!("Width - {}", size_or_not_set(scratchpad.width))),
+                        ListItem::new(format!("Height - {}", size_or_not_set(scratchpad.height))),
                         ListItem::new("--------------------------"),
                         ListItem::new("Add new scratchpad"),
                         ListItem::new("Delete this scratchpad"),
@@ -698,7 +842,7 @@ impl App<'_> {
                         ListItem::new(format!("Command - {:?}", keybind.command)),
                     ];
 
-                    if keybind.command.needs_value() {
+                    if keybind.command.argument() != ArgumentKind::None {
                         vec.push(ListItem::new(format!("Value - {}", keybind.value)));
                     }
 
@@ -717,9 +861,83 @@ impl App<'_> {
                     vec.push(ListItem::new("Add new keybind"));
                     vec.push(ListItem::new("Delete this keybind"));
 
+                    if let Err(err) = keybind.try_convert_to_core_keybind(&self.current_config) {
+                        vec.push(ListItem::new("--------------------------"));
+                        vec.push(ListItem::new("WARNING:").style(Style::default().fg(Color::Red)));
+                        vec.push(
+                            ListItem::new(err.to_string()).style(Style::default().fg(Color::Red)),
+                        );
+                    }
+
                     vec
                 }
             }
+            Window::LayoutDefinitions { index, empty } => {
+                if empty {
+                    vec![
+                        ListItem::new("None out of 0"),
+                        ListItem::new("--------------------------"),
+                        ListItem::new("Add new layout"),
+                    ]
+                } else {
+                    let definition = self
+                        .current_config
+                        .layout_definitions
+                        .get(index)
+                        .try_unwrap()?;
+                    let columns = &definition.columns;
+                    let not_set = || "not set".to_string();
+
+                    vec![
+                        ListItem::new(format!(
+                            "{} out of {}",
+                            index + 1,
+                            self.current_config.layout_definitions.len()
+                        )),
+                        ListItem::new("--------------------------"),
+                        ListItem::new(format!("Name - {}", definition.name)),
+                        ListItem::new(format!("Flip - {:?}", definition.flip)),
+                        ListItem::new(format!("Rotate - {:?}", definition.rotate)),
+                        ListItem::new(format!("Reserve - {:?}", definition.reserve)),
+                        ListItem::new(format!("Columns Flip - {:?}", columns.flip)),
+                        ListItem::new(format!("Columns Rotate - {:?}", columns.rotate)),
+                        ListItem::new(format!("Main Column - {}", columns.main.is_some())),
+                        ListItem::new(format!(
+                            "Main Count - {}",
+                            columns
+                                .main
+                                .as_ref()
+                                .map_or_else(not_set, |m| m.count.to_string())
+                        )),
+                        ListItem::new(format!(
+                            "Main Size - {}",
+                            columns
+                                .main
+                                .as_ref()
+                                .map_or_else(not_set, |m| m.size.to_string())
+                        )),
+                        ListItem::new(format!(
+                            "Main Split - {}",
+                            columns
+                                .main
+                                .as_ref()
+                                .map_or_else(not_set, |m| format!("{:?}", m.split))
+                        )),
+                        ListItem::new(format!("Stack Split - {:?}", columns.stack.split)),
+                        ListItem::new(format!("Second Stack - {}", columns.second_stack.is_some())),
+                        ListItem::new(format!(
+                            "Second Stack Split - {}",
+                            columns
+                                .second_stack
+                                .as_ref()
+                                .map_or_else(not_set, |s| format!("{:?}", s.split))
+                        )),
+                        ListItem::new("--------------------------"),
+                        ListItem::new("Add new layout"),
+                        ListItem::new("Delete this layout"),
+                    ]
+                }
+            }
         })
     }
 }
@@ -752,6 +970,36 @@ fn previous(state: &mut ListState, len: usize) {
     state.select(Some(i));
 }
 
+/// A size as shown in the lists, `not set` when leftwm uses its default.
+fn size_or_not_set(size: Option<Size>) -> String {
+    size.map_or_else(|| "not set".to_string(), |s| s.to_string())
+}
+
+/// The entries of the layouts multiselect: the built-in layouts, the custom definitions
+/// and any other name already in `current`, so confirming the popup never drops one.
+fn layout_choices(config: &Config, current: &[LayoutName]) -> Vec<LayoutName> {
+    let mut choices: Vec<LayoutName> = LAYOUTS.iter().map(|l| LayoutName::from(*l)).collect();
+    for name in config
+        .layout_definitions
+        .iter()
+        .map(|d| LayoutName(d.name.clone()))
+        .chain(current.iter().cloned())
+    {
+        if !choices.contains(&name) {
+            choices.push(name);
+        }
+    }
+    choices
+}
+
+/// Indices into `choices` of the `current` layouts, in their configured order.
+fn selected_layouts(choices: &[LayoutName], current: &[LayoutName]) -> Vec<usize> {
+    current
+        .iter()
+        .filter_map(|name| choices.iter().position(|c| c == name))
+        .collect()
+}
+
 fn format_modkey_name(modkey: String) -> String {
     match modkey.as_str() {
         "Mod1" | "Alt" => "Alt".to_string(),
@@ -759,3 +1007,44 @@ fn format_modkey_name(modkey: String) -> String {
         _ => modkey,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::layout::{Layout, LayoutDefinition};
+
+    fn names(names: &[&str]) -> Vec<LayoutName> {
+        names.iter().map(|n| LayoutName((*n).to_string())).collect()
+    }
+
+    #[test]
+    fn layout_choices_keep_every_configured_name() {
+        let config = Config {
+            layout_definitions: vec![LayoutDefinition {
+                name: "Wide".to_string(),
+                ..LayoutDefinition::default()
+            }],
+            ..Config::default()
+        };
+        let choices = layout_choices(&config, &names(&["Typo", "Monocle", "Wide"]));
+        assert_eq!(choices.len(), LAYOUTS.len() + 2);
+        assert_eq!(choices[LAYOUTS.len()..], names(&["Wide", "Typo"]));
+    }
+
+    #[test]
+    fn selected_layouts_keep_the_configured_order() {
+        let config = Config::default();
+        let current = names(&["Monocle", "MainAndVertStack", "Fibonacci"]);
+        let choices = layout_choices(&config, &current);
+        let selected = selected_layouts(&choices, &current);
+        let position = |layout: Layout| LAYOUTS.iter().position(|l| *l == layout).unwrap();
+        assert_eq!(
+            selected,
+            vec![
+                position(Layout::Monocle),
+                position(Layout::MainAndVertStack),
+                position(Layout::Fibonacci)
+            ]
+        );
+    }
+}
@@ -11,9 +11,11 @@ use tui::widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph,
 
 use crate::config::Config;
 use crate::config::command::BaseCommand;
+use crate::config::keybind::Keybind;
+use crate::config::layout::LayoutName;
 use crate::config::modifier::Modifier as KeyModifier;
 use crate::config::modifier::Modifier::Single;
-use crate::config::values::{FocusBehaviour, InsertBehavior, LayoutMode};
+use crate::config::values::{FocusBehaviour, InsertBehavior, LayoutMode, Size, WindowType};
 use crate::tui::PopupState;
 use crate::utils::xkeysym_lookup::into_keysym;
 use crate::utils::{TryUnwrap, centered_rect};
@@ -77,37 +79,41 @@ pub fn modkey(
         ]
     } else {
         [
-            if current_config.modkey == "None" {
+            if check_modifier(Some(&current_config.modkey), "None") {
                 ListItem::new("None").style(Style::default().fg(Color::Green))
             } else {
                 ListItem::new("None")
             },
-            if current_config.modkey == "Shift" {
+            if check_modifier(Some(&current_config.modkey), "Shift") {
                 ListItem::new("Shift").style(Style::default().fg(Color::Green))
             } else {
                 ListItem::new("Shift")
             },
-            if current_config.modkey == "Control" {
+            if check_modifier(Some(&current_config.modkey), "Control") {
                 ListItem::new("Control").style(Style::default().fg(Color::Green))
             } else {
                 ListItem::new("Control")
             },
-            if current_config.modkey == "Alt" || current_config.modkey == "Mod1" {
+            if check_modifier(Some(&current_config.modkey), "Alt")
+                || check_modifier(Some(&current_config.modkey), "Mod1")
+            {
                 ListItem::new("Alt").style(Style::default().fg(Color::Green))
             } else {
                 ListItem::new("Alt")
             },
-            if current_config.modkey == "Mod3" {
+            if check_modifier(Some(&current_config.modkey), "Mod3") {
                 ListItem::new("Mod3").style(Style::default().fg(Color::Green))
             } else {
                 ListItem::new("Mod3")
             },
-            if current_config.modkey == "Super" || current_config.modkey == "Mod4" {
+            if check_modifier(Some(&current_config.modkey), "Super")
+                || check_modifier(Some(&current_config.modkey), "Mod4")
+            {
                 ListItem::new("Super").style(Style::default().fg(Color::Green))
             } else {
                 ListItem::new("Super")
             },
-            if current_config.modkey == "Mod5" {
+            if check_modifier(Some(&current_config.modkey), "Mod5") {
                 ListItem::new("Mod5").style(Style::default().fg(Color::Green))
             } else {
                 ListItem::new("Mod5")
@@ -137,29 +143,61 @@ fn check_modifier(modifier: Option<&KeyModifier>, name: &str) -> bool {
     }
 }
 
-pub fn max_window_width(
+/// A text input for a [`Size`], showing below it which unit the input is read as.
+pub fn size(
+    current_popup_state: &mut PopupState,
+    name: &str,
+    f: &mut Frame<CrosstermBackend<Stdout>>,
+) -> Result<()> {
+    checked_input(current_popup_state, name, f, |string| {
+        if string.trim().is_empty() {
+            return Span::styled("not set", Style::default().fg(Color::DarkGray));
+        }
+        match string.parse::<Size>() {
+            Ok(size) => Span::styled(size.describe(), Style::default().fg(Color::Green)),
+            Err(e) => Span::styled(e.to_string(), Style::default().fg(Color::Red)),
+        }
+    })
+}
+
+/// A text input for the value of `keybind`, showing below it why leftwm would not accept it.
+pub fn keybind_value(
     current_popup_state: &mut PopupState,
+    config: &Config,
+    keybind: &Keybind,
     f: &mut Frame<CrosstermBackend<Stdout>>,
+) -> Result<()> {
+    let name = format!("Value ({})", keybind.command.argument().expected());
+    checked_input(current_popup_state, &name, f, |string| {
+        match keybind.check_value(config, string) {
+            Ok(_) => Span::styled("OK", Style::default().fg(Color::Green)),
+            Err(e) => Span::styled(e.to_string(), Style::default().fg(Color::Red)),
+        }
+    })
+}
+
+/// A text input with a line below it saying what `check` makes of the input.
+fn checked_input(
+    current_popup_state: &mut PopupState,
+    name: &str,
+    f: &mut Frame<CrosstermBackend<Stdout>>,
+    check: impl Fn(&str) -> Span<'static>,
 ) -> Result<()> {
     let block = Block::default()
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::White))
         .border_type(BorderType::Rounded)
         .style(Style::default().bg(Color::Black))
-        .title("Max Window Width");
+        .title(name);
 
-    let area = centered_rect(60, 4, f.size());
+    // the border, the input and the status line
+    let mut area = centered_rect(60, 4, f.size());
+    area.height = area.height.max(4).min(f.size().height);
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints(
-            [
-                Constraint::Ratio(1, 3),
-                Constraint::Ratio(1, 3),
-                Constraint::Ratio(1, 3),
-            ]
-            .as_ref(),
-        )
+        .margin(1)
+        .constraints([Constraint::Length(1), Constraint::Length(1)].as_ref())
         .split(area);
 
     let string = if let PopupState::String(s) = current_popup_state {
@@ -168,16 +206,29 @@ pub fn max_window_width(
         bail!("Invalid popup state");
     };
 
-    let text = vec![Spans::from(vec![Span::raw(string)])];
+    let status = check(&string);
 
-    let text = Paragraph::new(text)
+    let text_len = u16::try_from(string.len())?;
+
+    let text = Paragraph::new(vec![Spans::from(vec![Span::raw(string)])])
         .style(Style::default().fg(Color::White).bg(Color::Black))
+        .alignment(Alignment::Center);
+
+    let status = Paragraph::new(vec![Spans::from(vec![status])])
+        .style(Style::default().bg(Color::Black))
         .alignment(Alignment::Center)
         .wrap(Wrap { trim: true });
 
     f.render_widget(Clear, area); //this clears out the background
     f.render_widget(block, area);
-    f.render_widget(text, *chunks.get(1).unwrap_or(&area));
+    let text_area = *chunks.first().unwrap_or(&area);
+    f.render_widget(text, text_area);
+    f.render_widget(status, *chunks.get(1).unwrap_or(&area));
+
+    f.set_cursor(
+        text_area.x + text_area.width / 2 + text_len.div_ceil(2),
+        text_area.y,
+    );
 
     Ok(())
 }
@@ -294,6 +345,54 @@ pub fn insert_behavior(
     Ok(())
 }
 
+pub fn window_type(
+    current_config: &Config,
+    index: usize,
+    current_popup_state: &mut PopupState,
+    f: &mut Frame<CrosstermBackend<Stdout>>,
+) -> Result<()> {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::White))
+        .border_type(BorderType::Rounded)
+        .style(Style::default().bg(Color::Black))
+        .title("Spawn As Type");
+    let area = centered_rect(60, 50, f.size());
+    let current = current_config
+        .window_rules
+        .as_ref()
+        .try_unwrap()?
+        .get(index)
+        .try_unwrap()?
+        .spawn_as_type;
+    let type_list = WindowType::ALL
+        .iter()
+        .map(|window_type| {
+            if current == Some(*window_type) {
+                ListItem::new(format!("{window_type:?}")).style(Style::default().fg(Color::Green))
+            } else {
+                ListItem::new(format!("{window_type:?}"))
+            }
+        })
+        .collect::<Vec<ListItem>>();
+    let list = List::new(type_list)
+        .block(Block::default().borders(Borders::NONE))
+        .style(Style::default().fg(Color::White))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD))
+        .highlight_symbol(">>");
+
+    f.render_widget(Clear, area); //this clears out the background
+    f.render_widget(block, area);
+
+    if let PopupState::List(e) = current_popup_state {
+        f.render_stateful_widget(list, centered_rect(30, 90, area), e);
+    } else {
+        bail!("Invalid popup state");
+    }
+
+    Ok(())
+}
+
 pub fn layout_mode(
     current_config: &Config,
     current_popup_state: &mut PopupState,
@@ -340,7 +439,10 @@ pub fn layout_mode(
     Ok(())
 }
 
+/// Selected layouts are numbered in the order they were picked, which is the order
+/// leftwm cycles through them.
 pub fn layouts(
+    choices: &[LayoutName],
     current_popup_state: &mut PopupState,
     f: &mut Frame<CrosstermBackend<Stdout>>,
 ) -> Result<()> {
@@ -352,93 +454,19 @@ pub fn layouts(
         .title("Layouts");
     let area = centered_rect(60, 20, f.size());
 
-    let mut layout_list = vec![
-        ListItem::new("MainAndVertStack"),
-        ListItem::new("MainAndHorizontalStack"),
-        ListItem::new("MainAndDeck"),
-        ListItem::new("GridHorizontal"),
-        ListItem::new("EvenHorizontal"),
-        ListItem::new("EvenVertical"),
-        ListItem::new("Fibonacci"),
-        ListItem::new("LeftMain"),
-        ListItem::new("CenterMain"),
-        ListItem::new("CenterMainBalanced"),
-        ListItem::new("CenterMainFluid"),
-        ListItem::new("Monocle"),
-        ListItem::new("RightWiderLeftStack"),
-        ListItem::new("LeftWiderRightStack"),
-    ];
-
-    if let PopupState::MultiList(e) = current_popup_state {
-        for i in &e.selected {
-            // we allow this here because clippy thinks
-            // we are initializing a new thing here (probably because of the _ => {..})
-            // while we are just using the let _ to get rid of the result of mem::replace()
-            #[allow(let_underscore_drop)]
-            let _ = match i {
-                0 => mem::replace::<ListItem<'_>>(
-                    layout_list.get_mut(0).try_unwrap()?,
-                    ListItem::new("MainAndVertStack").style(Style::default().fg(Color::Green)),
-                ),
-                1 => mem::replace::<ListItem<'_>>(
-                    layout_list.get_mut(1).try_unwrap()?,
-                    ListItem::new("MainAndHorizontalStack")
-                        .style(Style::default().fg(Color::Green)),
-                ),
-                2 => mem::replace::<ListItem<'_>>(
-                    layout_list.get_mut(2).try_unwrap()?,
-                    ListItem::new("MainAndDeck").style(Style::default().fg(Color::Green)),
-                ),
-                3 => mem::replace::<ListItem<'_>>(
-                    layout_list.get_mut(3).try_unwrap()?,
-                    ListItem::new("GridHorizontal").style(Style::default().fg(Color::Green)),
-                ),
-                4 => mem::replace::<ListItem<'_>>(
-                    layout_list.get_mut(4).try_unwrap()?,
-                    ListItem::new("EvenHorizontal").style(Style::default().fg(Color::Green)),
-                ),
-                5 => mem::replace::<ListItem<'_>>(
-                    layout_list.get_mut(5).try_unwrap()?,
-                    ListItem::new("EvenVertical").style(Style::default().fg(Color::Green)),
-                ),
-                6 => mem::replace::<ListItem<'_>>(
-                    layout_list.get_mut(6).try_unwrap()?,
-                    ListItem::new("Fibonacci").style(Style::default().fg(Color::Green)),
-                ),
-                7 => mem::replace::<ListItem<'_>>(
-                    layout_list.get_mut(7).try_unwrap()?,
-                    ListItem::new("LeftMain").style(Style::default().fg(Color::Green)),
-                ),
-                8 => mem::replace::<ListItem<'_>>(
-                    layout_list.get_mut(8).try_unwrap()?,
-                    ListItem::new("CenterMain").style(Style::default().fg(Color::Green)),
-                ),
-                9 => mem::replace::<ListItem<'_>>(
-                    layout_list.get_mut(9).try_unwrap()?,
-                    ListItem::new("CenterMainBalanced").style(Style::default().fg(Color::Green)),
-                ),
-                10 => mem::replace::<ListItem<'_>>(
-                    layout_list.get_mut(10).try_unwrap()?,
-                    ListItem::new("CenterMainFluid").style(Style::default().fg(Color::Green)),
-                ),
-                11 => mem::replace::<ListItem<'_>>(
-                    layout_list.get_mut(11).try_unwrap()?,
-                    ListItem::new("Monocle").style(Style::default().fg(Color::Green)),
-                ),
-                12 => mem::replace::<ListItem<'_>>(
-                    layout_list.get_mut(12).try_unwrap()?,
-                    ListItem::new("RightWiderLeftStack").style(Style::default().fg(Color::Green)),
-                ),
-                13 => mem::replace::<ListItem<'_>>(
-                    layout_list.get_mut(13).try_unwrap()?,
-                    ListItem::new("LeftWiderRightStack").style(Style::default().fg(Color::Green)),
-                ),
-                _ => ListItem::new(""),
-            };
-        }
+    let layout_list = if let PopupState::MultiList(e) = current_popup_state {
+        choices
+            .iter()
+            .enumerate()
+            .map(|(i, name)| match e.selected.iter().position(|s| *s == i) {
+                Some(position) => ListItem::new(format!("{}. {name}", position + 1))
+                    .style(Style::default().fg(Color::Green)),
+                None => ListItem::new(name.to_string()),
+            })
+            .collect::<Vec<ListItem>>()
     } else {
         bail!("Invalid popup state");
-    }
+    };
     let list = List::new(layout_list)
         .block(Block::default().borders(Borders::NONE))
         .style(Style::default().fg(Color::White))
@@ -1,5 +1,10 @@
 use ::tui::layout::{Constraint, Direction, Layout, Rect};
 use anyhow::{Context, Result, bail};
+use std::fs::{self, DirBuilder};
+use std::io::ErrorKind;
+use std::os::unix::fs::DirBuilderExt;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 mod x11_keys;
 pub(crate) mod xkeysym_lookup;
@@ -56,3 +61,38 @@ impl<T> TryRemove<T> for Vec<T> {
         }
     }
 }
+
+/// A directory only the current user can access, removed with everything in it when dropped.
+pub(crate) struct PrivateTempDir {
+    pub path: PathBuf,
+}
+
+impl PrivateTempDir {
+    pub fn new(prefix: &str) -> Result<Self> {
+        let base =
+            std::env::var_os("XDG_RUNTIME_DIR").map_or_else(std::env::temp_dir, PathBuf::from);
+        for attempt in 0..16 {
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .subsec_nanos();
+            let path = base.join(format!("{prefix}-{}-{nanos}-{attempt}", std::process::id()));
+            // `create` fails if the path exists, so nobody else can have prepared it
+            match DirBuilder::new().mode(0o700).create(&path) {
+                Ok(()) => return Ok(Self { path }),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e).context(format!("Could not create {}", path.display())),
+            }
+        }
+        bail!(
+            "Could not create a temporary directory in {}",
+            base.display()
+        )
+    }
+}
+
+impl Drop for PrivateTempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
@@ -1,4 +1,5 @@
 #![allow(clippy::wildcard_imports)]
+use crate::config::modifier::canonical;
 use crate::utils::x11_keys::*;
 use std::os::raw::c_uint;
 
@@ -7,15 +8,14 @@ pub type ModMask = c_uint;
 
 #[must_use]
 pub fn into_mod(key: &str) -> ModMask {
-    match key {
+    match canonical(key).unwrap_or(key) {
         "None" => AnyModifier,
         "Shift" => ShiftMask,
         "Control" => ControlMask,
-        "Mod1" | "Alt" => Mod1Mask,
-        //"Mod2" => xlib::Mod2Mask,     // NOTE: we are ignoring the state of Numlock
-        //"NumLock" => xlib::Mod2Mask,  // this is left here as a reminder
+        "Mod1" => Mod1Mask,
+        // Mod2 is NumLock, which is ignored, see `config::modifier`
         "Mod3" => Mod3Mask,
-        "Mod4" | "Super" => Mod4Mask,
+        "Mod4" => Mod4Mask,
         "Mod5" => Mod5Mask,
         _ => 0,
     }